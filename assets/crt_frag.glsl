@@ -5,6 +5,9 @@ varying vec4 color;
 varying vec2 uv;
 
 uniform sampler2D Texture;
+uniform float SharpBilinear;
+uniform vec2 TextureSize;
+uniform vec2 OutputSize;
 
 // https://www.shadertoy.com/view/XtlSD7
 
@@ -17,6 +20,17 @@ vec2 CRTCurveUV(vec2 uv)
     return uv;
 }
 
+// Bilinear filtering limited to the edges of each texel, keeps pixels crisp at non-integer scales
+vec2 SharpBilinearUV(vec2 uv)
+{
+    highp vec2 texel = uv * TextureSize;
+    highp vec2 scale = max(floor(OutputSize / TextureSize), vec2(1.0, 1.0));
+    highp vec2 regionRange = 0.5 - 0.5 / scale;
+    highp vec2 centerDist = fract(texel) - 0.5;
+    highp vec2 f = (centerDist - clamp(centerDist, -regionRange, regionRange)) * scale + 0.5;
+    return (floor(texel) + f) / TextureSize;
+}
+
 void DrawVignette( inout vec3 color, vec2 uv )
 {
     float vignette = uv.x * uv.y * ( 1.0 - uv.x ) * ( 1.0 - uv.y );
@@ -35,7 +49,8 @@ void DrawScanline( inout vec3 color, vec2 uv )
 
 void main() {
     vec2 crtUV = CRTCurveUV(uv);
-    vec3 res = texture2D(Texture, uv).rgb * color.rgb;
+    vec2 sampleUV = SharpBilinear > 0.5 ? SharpBilinearUV(uv) : uv;
+    vec3 res = texture2D(Texture, sampleUV).rgb * color.rgb;
     if (crtUV.x < 0.0 || crtUV.x > 1.0 || crtUV.y < 0.0 || crtUV.y > 1.0)
     {
         res = vec3(0.0, 0.0, 0.0);
@@ -0,0 +1,61 @@
+//! Runs eight headless emulators on their own threads, e.g. `cargo run --example parallel -- game.ch8`.
+
+use std::{
+    env,
+    fs,
+    thread,
+    time::Instant,
+};
+
+use chip8rs::emulator::Emulator;
+
+const INSTANCES: usize = 8;
+const INSTRUCTIONS: usize = 1_000_000;
+
+fn main() -> Result<(), anyhow::Error> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "assets/roms/test/3-corax+.ch8".to_owned());
+    let rom = fs::read(&path)?;
+
+    let emulators = (0..INSTANCES)
+        .map(|_| Emulator::headless(&rom))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let start = Instant::now();
+    let results = thread::scope(|scope| {
+        let workers: Vec<_> = emulators
+            .into_iter()
+            .map(|mut emulator| {
+                scope.spawn(move || -> Result<Emulator, anyhow::Error> {
+                    emulator.run_for(INSTRUCTIONS)?;
+                    Ok(emulator)
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("emulator thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    for (idx, result) in results.into_iter().enumerate() {
+        let emulator = result?;
+        let lit = emulator.framebuffer().pixels().iter().filter(|lit| **lit).count();
+        println!(
+            "instance {}: {} instructions, {} draws, {} pixels lit",
+            idx,
+            emulator.instruction_count(),
+            emulator.draw_count(),
+            lit
+        );
+    }
+    println!(
+        "{} instructions across {} instances in {:?}",
+        INSTRUCTIONS * INSTANCES,
+        INSTANCES,
+        start.elapsed()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Static control flow of a ROM, found by following jumps and calls from the entry point without running it.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt::Write as _,
+    ops::Range,
+};
+
+use crate::{
+    emulator::Interpreter,
+    symbols::SymbolTable,
+};
+
+/// Hires programs jump over the original hires interpreter and continue here.
+const HIRES_ENTRY: u16 = 0x2C0;
+
+/// XO-CHIP's `F000 NNNN` loads I from the two bytes after it, making it the only four byte instruction.
+const LONG_LOAD: u16 = 0xF000;
+
+/// Code reachable from one entry point without following its calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Subroutine {
+    pub calls: BTreeSet<u16>,
+    pub jumps: BTreeSet<u16>,
+    /// Takes a BNNN jump, whose target depends on V0 and isn't followed.
+    pub computed_jump: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ControlFlow {
+    load_address: u16,
+    /// Whether each byte of the ROM was reached as part of an instruction.
+    code: Vec<bool>,
+    instructions: BTreeSet<u16>,
+    subroutines: BTreeMap<u16, Subroutine>,
+}
+
+impl ControlFlow {
+    pub fn analyze(rom: &[u8], load_address: u16) -> Self {
+        let mut flow = Self {
+            load_address,
+            code: vec![false; rom.len()],
+            instructions: BTreeSet::new(),
+            subroutines: BTreeMap::new(),
+        };
+        let entry = match Interpreter::detect(rom) {
+            Some(Interpreter::HiresVIP) => HIRES_ENTRY,
+            _ => load_address,
+        };
+
+        let mut pending = vec![entry];
+        while let Some(entry) = pending.pop() {
+            if flow.subroutines.contains_key(&entry) {
+                continue;
+            }
+            let subroutine = flow.walk(rom, entry);
+            pending.extend(subroutine.calls.iter().copied());
+            flow.subroutines.insert(entry, subroutine);
+        }
+        flow
+    }
+
+    fn walk(&mut self, rom: &[u8], entry: u16) -> Subroutine {
+        let mut subroutine = Subroutine::default();
+        let mut visited = BTreeSet::new();
+        let mut queue = vec![entry];
+        while let Some(address) = queue.pop() {
+            if !visited.insert(address) {
+                continue;
+            }
+            let Some(offset) = address.checked_sub(self.load_address).map(usize::from) else {
+                continue;
+            };
+            let Some(op_code) = op_code_at(rom, offset) else {
+                continue;
+            };
+            let len = instruction_len(op_code);
+            let end = (offset + len).min(rom.len());
+            self.code[offset..end].fill(true);
+            self.instructions.insert(address);
+
+            let Some(next) = address.checked_add(len as u16) else {
+                continue;
+            };
+            // Skips step over the whole next instruction, which may be a long load
+            let skipped = next.checked_add(op_code_at(rom, offset + len).map_or(2, instruction_len) as u16);
+            let nnn = op_code & 0x0FFF;
+            match op_code & 0xF000 {
+                0x0000 if op_code == 0x00EE || op_code == 0x00FD => {}
+                0x1000 => {
+                    subroutine.jumps.insert(nnn);
+                    queue.push(nnn);
+                }
+                0x2000 => {
+                    subroutine.calls.insert(nnn);
+                    queue.push(next);
+                }
+                0x3000 | 0x4000 | 0x5000 | 0x9000 => queue.extend([Some(next), skipped].into_iter().flatten()),
+                0xE000 if matches!(op_code & 0x00FF, 0x9E | 0xA1) => {
+                    queue.extend([Some(next), skipped].into_iter().flatten())
+                }
+                0xB000 => subroutine.computed_jump = true,
+                _ => queue.push(next),
+            }
+        }
+        subroutine
+    }
+
+    /// Subroutines by entry address, the program's entry point included.
+    pub fn subroutines(&self) -> &BTreeMap<u16, Subroutine> {
+        &self.subroutines
+    }
+
+    /// Addresses of every instruction reached.
+    pub fn instructions(&self) -> &BTreeSet<u16> {
+        &self.instructions
+    }
+
+    /// Address ranges never reached as code, most likely sprites and other data.
+    pub fn data_ranges(&self) -> Vec<Range<u16>> {
+        let mut ranges: Vec<Range<u16>> = Vec::new();
+        for (offset, _) in self.code.iter().enumerate().filter(|(_, code)| !**code) {
+            // Bytes past the end of the address space can't be reached by anything
+            let Ok(address) = u16::try_from(self.load_address as usize + offset) else {
+                break;
+            };
+            match ranges.last_mut() {
+                Some(range) if range.end == address => range.end = range.end.saturating_add(1),
+                _ => ranges.push(address..address.saturating_add(1)),
+            }
+        }
+        ranges
+    }
+
+    fn contains(&self, address: u16) -> bool {
+        (self.load_address as usize..self.load_address as usize + self.code.len()).contains(&(address as usize))
+    }
+
+    /// A Graphviz graph with a node per subroutine and data range. Calls are solid edges, jumps into another
+    /// subroutine dashed and jumps out of the program red.
+    pub fn to_dot(&self, symbols: &SymbolTable) -> String {
+        let mut dot = String::from("digraph rom {\n    node [shape=box, fontname=monospace];\n");
+        for (entry, subroutine) in &self.subroutines {
+            let mut label = format!("0x{:03X}", entry);
+            if let Some(name) = symbols.label(*entry) {
+                label = format!("{}\\n{}", name.replace('"', "'"), label);
+            }
+            if subroutine.computed_jump {
+                label.push_str("\\n(computed jump)");
+            }
+            let _ = writeln!(dot, "    \"0x{:03X}\" [label=\"{}\"];", entry, label);
+        }
+        for range in self.data_ranges() {
+            let _ = writeln!(
+                dot,
+                "    \"0x{:03X}\" [label=\"data 0x{:03X}-0x{:03X}\\n{} bytes\", shape=note, style=dashed];",
+                range.start,
+                range.start,
+                range.end - 1,
+                range.len()
+            );
+        }
+        for (entry, subroutine) in &self.subroutines {
+            for call in &subroutine.calls {
+                let _ = writeln!(dot, "    \"0x{:03X}\" -> \"0x{:03X}\";", entry, call);
+            }
+            for jump in &subroutine.jumps {
+                if !self.contains(*jump) {
+                    let _ = writeln!(dot, "    \"0x{:03X}\" -> \"0x{:03X}\" [color=red];", entry, jump);
+                } else if jump != entry && self.subroutines.contains_key(jump) {
+                    let _ = writeln!(dot, "    \"0x{:03X}\" -> \"0x{:03X}\" [style=dashed];", entry, jump);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn op_code_at(rom: &[u8], offset: usize) -> Option<u16> {
+    let bytes = rom.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn instruction_len(op_code: u16) -> usize {
+    if op_code == LONG_LOAD {
+        4
+    } else {
+        2
+    }
+}
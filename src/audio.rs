@@ -0,0 +1,195 @@
+//! Beep and key click playback, a no-op when the `audio` feature is off.
+
+#[cfg(feature = "audio")]
+use std::{
+    env,
+    path::PathBuf,
+};
+
+#[cfg(feature = "audio")]
+use macroquad::audio::{
+    load_sound,
+    load_sound_from_bytes,
+    play_sound,
+    set_sound_volume,
+    stop_sound,
+    PlaySoundParams,
+    Sound,
+};
+
+use crate::emulator::Buzzer;
+
+#[cfg(feature = "audio")]
+pub(crate) struct Beep(Sound);
+
+#[cfg(not(feature = "audio"))]
+pub(crate) struct Beep;
+
+#[cfg(feature = "audio")]
+pub(crate) struct Click(Sound);
+
+#[cfg(not(feature = "audio"))]
+pub(crate) struct Click;
+
+pub(crate) const SAMPLE_RATE: u32 = 44_100;
+
+const BEEP_PERIOD: u64 = 100;
+
+/// Sample `index` of the synthesized beep, a 441 Hz square wave at a quarter of full scale.
+pub(crate) fn beep_sample(index: u64) -> i16 {
+    if index % BEEP_PERIOD < BEEP_PERIOD / 2 {
+        i16::MAX / 4
+    } else {
+        -i16::MAX / 4
+    }
+}
+
+/// `assets/beep.wav` next to the executable, in the working directory or in the copy the build script left in
+/// `OUT_DIR`, whichever comes first. None when the crate is used as a dependency somewhere without any of them.
+#[cfg(feature = "audio")]
+fn default_beep_path() -> Option<PathBuf> {
+    let beside_executable = env::current_exe()
+        .ok()
+        .and_then(|executable| executable.parent().map(|dir| dir.join("assets")));
+    [
+        beside_executable,
+        Some(PathBuf::from("assets")),
+        Some(PathBuf::from(concat!(env!("OUT_DIR"), "/assets"))),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|dir| dir.join("beep.wav"))
+    .find(|path| path.is_file())
+}
+
+#[cfg(feature = "audio")]
+impl Beep {
+    /// Whole periods only, so the tone loops without a click.
+    const SAMPLES: u64 = BEEP_PERIOD * 44;
+
+    pub(crate) async fn load(path: &str) -> Result<Self, anyhow::Error> {
+        Ok(Self::primed(load_sound(path).await?))
+    }
+
+    /// The beep found by [`default_beep_path`], or a synthesized 441 Hz square wave if there is none or it doesn't
+    /// load.
+    pub(crate) async fn load_default() -> Result<Self, anyhow::Error> {
+        if let Some(path) = default_beep_path() {
+            match Self::load(&path.to_string_lossy()).await {
+                Ok(beep) => return Ok(beep),
+                Err(error) => log::warn!("Can't load {}, using a synthesized beep: {}", path.display(), error),
+            }
+        }
+        let samples: Vec<i16> = (0..Self::SAMPLES).map(beep_sample).collect();
+        Ok(Self::primed(load_sound_from_bytes(&wav(&samples)).await?))
+    }
+
+    fn primed(sound: Sound) -> Self {
+        // Playing once while muted primes the audio backend so the first real beep isn't delayed
+        play_sound(
+            &sound,
+            PlaySoundParams {
+                looped: false,
+                volume: 0.0,
+            },
+        );
+        Self(sound)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Buzzer for Beep {
+    fn start(&mut self, volume: f32) {
+        play_sound(&self.0, PlaySoundParams { looped: true, volume });
+    }
+
+    fn stop(&mut self) {
+        stop_sound(&self.0);
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        set_sound_volume(&self.0, volume);
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Click {
+    const SAMPLES: u32 = SAMPLE_RATE / 100;
+    const FREQUENCY: f32 = 2_000.0;
+
+    /// Synthesizes a 10 ms tone that fades out, short enough not to mask the beep.
+    pub(crate) async fn load() -> Result<Self, anyhow::Error> {
+        let samples: Vec<i16> = (0..Self::SAMPLES)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let fade = 1.0 - i as f32 / Self::SAMPLES as f32;
+                ((t * Self::FREQUENCY * std::f32::consts::TAU).sin() * fade * i16::MAX as f32) as i16
+            })
+            .collect();
+        Ok(Self(load_sound_from_bytes(&wav(&samples)).await?))
+    }
+}
+
+/// Starting plays the click once, it ends on its own so stopping does nothing.
+#[cfg(feature = "audio")]
+impl Buzzer for Click {
+    fn start(&mut self, volume: f32) {
+        play_sound(&self.0, PlaySoundParams { looped: false, volume });
+    }
+
+    fn stop(&mut self) {}
+}
+
+/// 16-bit mono PCM in a WAV container.
+pub(crate) fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(not(feature = "audio"))]
+impl Click {
+    pub(crate) async fn load() -> Result<Self, anyhow::Error> {
+        Ok(Self)
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+impl Buzzer for Click {
+    fn start(&mut self, _volume: f32) {}
+
+    fn stop(&mut self) {}
+}
+
+#[cfg(not(feature = "audio"))]
+impl Beep {
+    pub(crate) async fn load(_path: &str) -> Result<Self, anyhow::Error> {
+        Ok(Self)
+    }
+
+    pub(crate) async fn load_default() -> Result<Self, anyhow::Error> {
+        Ok(Self)
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+impl Buzzer for Beep {
+    fn start(&mut self, _volume: f32) {}
+
+    fn stop(&mut self) {}
+}
@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    audio::{
+        self,
+        SAMPLE_RATE,
+    },
+    emulator::Emulator,
+};
+
+/// Samples of audio per 60 Hz timer tick.
+pub const TICK_SAMPLES: u64 = SAMPLE_RATE as u64 / 60;
+
+/// Records what the buzzer plays, one timer tick at a time, and saves it as a WAV file when the run ends. The file
+/// always holds the synthesized square wave, standing in for a beep loaded with `--beep` or from `assets/beep.wav`.
+/// Every tick is 1/60 s of audio, so with `--deterministic`, where each frame advances one tick, the file lines up with
+/// a frame dump or screen recording of the same run.
+pub struct AudioDump {
+    path: PathBuf,
+    /// Volume of each tick, 0 while the buzzer is off.
+    ticks: Vec<f32>,
+}
+
+impl AudioDump {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            ticks: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Adds the tick about to end, to be called right before ticking `emulator`'s timers so a sound timer of 1 still
+    /// counts as a tick of tone.
+    pub fn record_tick(&mut self, emulator: &Emulator, volume: f32) {
+        let sound_on = emulator.sound_timer() > 0;
+        self.ticks.push(if sound_on { volume.clamp(0.0, 1.0) } else { 0.0 });
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(&self.path, encode(&self.ticks))
+    }
+}
+
+/// A WAV file with [`TICK_SAMPLES`] samples per tick, the beep at each tick's volume or silence. The wave runs on
+/// across ticks, so a beep lasting several ticks doesn't click at their edges.
+pub fn encode(ticks: &[f32]) -> Vec<u8> {
+    let samples: Vec<i16> = (0..ticks.len() as u64 * TICK_SAMPLES)
+        .map(|index| {
+            let volume = ticks[(index / TICK_SAMPLES) as usize];
+            (audio::beep_sample(index) as f32 * volume) as i16
+        })
+        .collect();
+    audio::wav(&samples)
+}
@@ -0,0 +1,79 @@
+//! Synthetic programs for measuring emulation speed, shared by `bench-compare` and the performance test.
+
+/// Programs the benchmark runs, each looping over a different mix of op codes forever without waiting for input.
+/// The test ROMs settle into a single self-jump within a few thousand instructions, which would measure only 1NNN.
+pub const BENCHMARKS: [(&str, &[u16]); 4] = [
+    (
+        "arithmetic",
+        &[
+            0x6001, // V0 = 1
+            0x6103, // V1 = 3
+            0x7105, // loop: V1 += 5
+            0x8014, // V0 += V1
+            0x8215, // V2 -= V1
+            0x8306, // V3 >>= 1
+            0x840E, // V4 <<= 1
+            0x8011, // V0 |= V1
+            0x8122, // V1 &= V2
+            0x8233, // V2 ^= V3
+            0xC5FF, // V5 = random
+            0x3500, // skip if V5 == 0
+            0x8450, // V4 = V5
+            0x9010, // skip if V0 != V1
+            0x6000, // V0 = 0
+            0x1204, // jump to loop
+        ],
+    ),
+    (
+        "memory",
+        &[
+            0xA300, // loop: I = 0x300
+            0x7A01, // VA += 1
+            0xFA33, // BCD of VA at I
+            0xF265, // load V0-V2
+            0xF255, // store V0-V2
+            0x6B02, // VB = 2
+            0xFB1E, // I += VB
+            0xF965, // load V0-V9
+            0x1200, // jump to loop
+        ],
+    ),
+    (
+        "drawing",
+        &[
+            0x6000, // clear: V0 = 0
+            0x6100, // V1 = 0
+            0xF229, // draw: I = glyph V2
+            0xD015, // draw it at V0, V1
+            0x7008, // V0 += 8
+            0x7201, // V2 += 1
+            0x3040, // skip if V0 == 64
+            0x1204, // jump to draw
+            0x6000, // V0 = 0
+            0x7106, // V1 += 6
+            0x311E, // skip if V1 == 30
+            0x1204, // jump to draw
+            0x00E0, // clear the screen
+            0x1200, // jump to clear
+        ],
+    ),
+    (
+        "calls",
+        &[
+            0x2206, // loop: call outer
+            0xFB07, // VB = delay timer
+            0x1200, // jump to loop
+            0x220C, // outer: call inner
+            0x6A05, // VA = 5
+            0x00EE, // return
+            0xFA15, // inner: delay timer = VA
+            0xFA18, // sound timer = VA
+            0x00EE, // return
+        ],
+    ),
+];
+
+/// Big endian bytes of `program`, loadable with `Emulator::headless`.
+pub fn rom(program: &[u16]) -> Vec<u8> {
+    program.iter().copied().flat_map(u16::to_be_bytes).collect()
+}
@@ -0,0 +1,233 @@
+//! Emulator shortcuts and the keys they are bound to, kept apart from the keypad layout.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+use macroquad::input::{
+    is_key_down,
+    is_key_pressed,
+    KeyCode,
+};
+
+use crate::config;
+
+/// Keys only shortcuts can use, on top of the ones the keypad can be bound to.
+#[rustfmt::skip]
+const SHORTCUT_KEY_NAMES: [(KeyCode, &str); 16] = [
+    (KeyCode::Escape, "escape"), (KeyCode::Tab, "tab"), (KeyCode::Space, "space"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::F1, "f1"), (KeyCode::F2, "f2"), (KeyCode::F3, "f3"), (KeyCode::F4, "f4"),
+    (KeyCode::F5, "f5"), (KeyCode::F6, "f6"), (KeyCode::F7, "f7"), (KeyCode::F8, "f8"),
+    (KeyCode::F9, "f9"), (KeyCode::F10, "f10"), (KeyCode::F11, "f11"), (KeyCode::F12, "f12"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Screenshot,
+    PixelGrid,
+    Keypad,
+    FrameGraph,
+    Watches,
+    RecordInputs,
+    Heatmap,
+    Stack,
+    Sound,
+    Menu,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Screenshot,
+        Action::PixelGrid,
+        Action::Keypad,
+        Action::FrameGraph,
+        Action::Watches,
+        Action::RecordInputs,
+        Action::Heatmap,
+        Action::Stack,
+        Action::Sound,
+        Action::Menu,
+        Action::Quit,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Screenshot => "screenshot",
+            Action::PixelGrid => "pixel_grid",
+            Action::Keypad => "keypad",
+            Action::FrameGraph => "frame_graph",
+            Action::Watches => "watches",
+            Action::RecordInputs => "record_inputs",
+            Action::Heatmap => "heatmap",
+            Action::Stack => "stack",
+            Action::Sound => "sound",
+            Action::Menu => "menu",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+/// A key, optionally held together with shift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: KeyCode,
+    pub shift: bool,
+}
+
+impl Binding {
+    pub fn new(key: KeyCode) -> Self {
+        Self { key, shift: false }
+    }
+
+    pub fn shifted(key: KeyCode) -> Self {
+        Self { key, shift: true }
+    }
+
+    /// Parses a key name like `p` or `f6`, prefixed with `shift+` for the shifted key.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (shift, name) = match text.strip_prefix("shift+") {
+            Some(name) => (true, name),
+            None => (false, text),
+        };
+        key_from_name(name).map(|key| Self { key, shift })
+    }
+
+    pub fn text(&self) -> String {
+        let name = key_name(self.key).unwrap_or("unknown");
+        if self.shift {
+            format!("shift+{}", name)
+        } else {
+            name.to_owned()
+        }
+    }
+
+    /// Whether the key went down this frame with shift held exactly as the binding asks.
+    fn is_pressed(&self) -> bool {
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        is_key_pressed(self.key) && shift == self.shift
+    }
+}
+
+/// Two things that react to the same key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// The shortcut's key is also on the keypad, so the game sees the press too.
+    Keypad { action: Action, key: KeyCode },
+    /// Both shortcuts run on the same press.
+    Shortcuts { first: Action, second: Action },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::Keypad { action, key } => write!(
+                f,
+                "shortcut {} uses keypad key {}",
+                action.name(),
+                key_name(*key).unwrap_or("unknown")
+            ),
+            Conflict::Shortcuts { first, second } => {
+                write!(f, "shortcuts {} and {} share a key", first.name(), second.name())
+            }
+        }
+    }
+}
+
+/// Which binding triggers each action, stored in the config as `bind.<action> = <key>` lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings {
+    bindings: BTreeMap<Action, Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            bindings: BTreeMap::from([
+                (Action::Screenshot, Binding::new(KeyCode::P)),
+                (Action::PixelGrid, Binding::new(KeyCode::G)),
+                (Action::Keypad, Binding::new(KeyCode::F2)),
+                (Action::FrameGraph, Binding::new(KeyCode::F3)),
+                (Action::Watches, Binding::new(KeyCode::F4)),
+                (Action::RecordInputs, Binding::new(KeyCode::F6)),
+                (Action::Heatmap, Binding::new(KeyCode::F7)),
+                (Action::Stack, Binding::new(KeyCode::F8)),
+                (Action::Sound, Binding::new(KeyCode::F9)),
+                (Action::Menu, Binding::new(KeyCode::Escape)),
+                (Action::Quit, Binding::shifted(KeyCode::Escape)),
+            ]),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> Option<Binding> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// Binds `action` to `binding`, or leaves it without a key for `None`.
+    pub fn set(&mut self, action: Action, binding: Option<Binding>) {
+        match binding {
+            Some(binding) => self.bindings.insert(action, binding),
+            None => self.bindings.remove(&action),
+        };
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Action, Binding)> + '_ {
+        self.bindings.iter().map(|(action, binding)| (*action, *binding))
+    }
+
+    /// Bindings that would also press one of the `keypad` keys, held with shift or not, and actions sharing a
+    /// binding.
+    pub fn conflicts(&self, keypad: &[KeyCode]) -> Vec<Conflict> {
+        let mut conflicts: Vec<Conflict> = self
+            .iter()
+            .filter(|(_, binding)| keypad.contains(&binding.key))
+            .map(|(action, binding)| Conflict::Keypad {
+                action,
+                key: binding.key,
+            })
+            .collect();
+        for (i, (first, binding)) in self.iter().enumerate() {
+            conflicts.extend(
+                self.iter()
+                    .skip(i + 1)
+                    .filter(|(_, other)| *other == binding)
+                    .map(|(second, _)| Conflict::Shortcuts { first, second }),
+            );
+        }
+        conflicts
+    }
+
+    /// Actions whose binding was pressed this frame.
+    pub fn pressed(&self) -> Vec<Action> {
+        self.iter()
+            .filter(|(_, binding)| binding.is_pressed())
+            .map(|(action, _)| action)
+            .collect()
+    }
+}
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    config::key_name(key).or_else(|| {
+        SHORTCUT_KEY_NAMES
+            .iter()
+            .find(|(code, _)| *code == key)
+            .map(|(_, name)| *name)
+    })
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    config::key_from_name(name).or_else(|| {
+        SHORTCUT_KEY_NAMES
+            .iter()
+            .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+            .map(|(code, _)| *code)
+    })
+}
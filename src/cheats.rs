@@ -0,0 +1,136 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use thiserror::Error;
+
+use crate::constants;
+
+#[derive(Error, Debug)]
+pub enum CheatError {
+    #[error("cheat file io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("line {line}: expected `name = address value [off]`")]
+    InvalidLine { line: usize },
+
+    #[error("line {line}: invalid address `{value}`")]
+    InvalidAddress { line: usize, value: String },
+
+    #[error("line {line}: invalid value `{value}`")]
+    InvalidValue { line: usize, value: String },
+}
+
+/// A memory address frozen to a value, e.g. the lives counter of a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheat {
+    pub name: String,
+    pub address: u16,
+    pub value: u8,
+    pub enabled: bool,
+}
+
+/// Cheats for one ROM, stored next to it as `name = address value [off]` lines with hex numbers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheatList {
+    pub cheats: Vec<Cheat>,
+}
+
+impl CheatList {
+    /// The cheat file belonging to the ROM at `rom`, `game.ch8` keeps its cheats in `game.cht`.
+    pub fn path_for_rom<P: AsRef<Path>>(rom: P) -> PathBuf {
+        rom.as_ref().with_extension("cht")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, CheatError> {
+        let mut cheats = Vec::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let (name, value) = content.split_once('=').ok_or(CheatError::InvalidLine { line })?;
+            let mut fields = value.split_whitespace();
+            let (Some(address), Some(value)) = (fields.next(), fields.next()) else {
+                Err(CheatError::InvalidLine { line })?
+            };
+            let enabled = match fields.next() {
+                None => true,
+                Some("off") => false,
+                Some(_) => Err(CheatError::InvalidLine { line })?,
+            };
+
+            cheats.push(Cheat {
+                name: name.trim().to_owned(),
+                address: parse_hex(address)
+                    .filter(|address| (*address as usize) < constants::TOTAL_RAM)
+                    .ok_or_else(|| CheatError::InvalidAddress {
+                        line,
+                        value: address.to_owned(),
+                    })?,
+                value: parse_hex(value)
+                    .and_then(|value| u8::try_from(value).ok())
+                    .ok_or_else(|| CheatError::InvalidValue {
+                        line,
+                        value: value.to_owned(),
+                    })?,
+                enabled,
+            });
+        }
+
+        Ok(Self { cheats })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for cheat in &self.cheats {
+            let _ = write!(text, "{} = 0x{:03X} 0x{:02X}", cheat.name, cheat.address, cheat.value);
+            let _ = writeln!(text, "{}", if cheat.enabled { "" } else { " off" });
+        }
+        text
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CheatError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Loads the cheats at `path`, an empty list if the ROM has none.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self, CheatError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CheatError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &Cheat> {
+        self.cheats.iter().filter(|cheat| cheat.enabled)
+    }
+
+    pub fn toggle(&mut self, idx: usize) {
+        if let Some(cheat) = self.cheats.get_mut(idx) {
+            cheat.enabled = !cheat.enabled;
+        }
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u16> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u16::from_str_radix(digits, 16).ok()
+}
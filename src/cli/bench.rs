@@ -0,0 +1,97 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    time::Instant,
+};
+
+use anyhow::Context;
+use chip8rs::{
+    bench::{
+        self,
+        BENCHMARKS,
+    },
+    emulator::Emulator,
+};
+use clap::Args;
+use serde_json::{
+    json,
+    Value,
+};
+
+#[derive(Args, Debug)]
+pub struct BenchCompareArgs {
+    /// Results of an earlier run, as written by `--save`
+    #[arg(long, value_name = "FILE")]
+    baseline: PathBuf,
+
+    /// Write this run's results to the baseline file instead of comparing against it
+    #[arg(long)]
+    save: bool,
+
+    /// Instructions each ROM runs per measurement
+    #[arg(long, default_value_t = 1_000_000)]
+    instructions: usize,
+
+    /// Measurements per ROM, the fastest one counts
+    #[arg(long, default_value_t = 3)]
+    runs: u32,
+}
+
+pub fn run(args: &BenchCompareArgs) -> Result<(), anyhow::Error> {
+    let mut results = BTreeMap::new();
+    for (name, program) in BENCHMARKS {
+        let rom = bench::rom(program);
+        let mut best = 0f64;
+        for _ in 0..args.runs.max(1) {
+            best = best.max(measure(&rom, args.instructions)?);
+        }
+        results.insert(name, best);
+    }
+
+    if args.save {
+        let text = serde_json::to_string_pretty(&json!({
+            "instructions": args.instructions,
+            "results": results,
+        }))?;
+        fs::write(&args.baseline, text + "\n").with_context(|| format!("can't write {}", args.baseline.display()))?;
+        for (name, rate) in &results {
+            println!("{:<14}{:>12}", name, format_rate(*rate));
+        }
+        println!("saved to {}", args.baseline.display());
+        return Ok(());
+    }
+
+    let text = fs::read_to_string(&args.baseline)
+        .with_context(|| format!("can't read {}, create it with --save", args.baseline.display()))?;
+    let baseline: Value =
+        serde_json::from_str(&text).with_context(|| format!("{} is not a results file", args.baseline.display()))?;
+    if baseline["instructions"].as_u64() != Some(args.instructions as u64) {
+        println!(
+            "baseline ran {} instructions per measurement, this run {}",
+            baseline["instructions"], args.instructions
+        );
+    }
+
+    println!("{:<14}{:>12}{:>12}{:>10}", "rom", "baseline", "current", "delta");
+    for (name, rate) in &results {
+        let (before, delta) = match baseline["results"][*name].as_f64() {
+            Some(before) if before > 0.0 => (format_rate(before), format!("{:+.1}%", (rate / before - 1.0) * 100.0)),
+            _ => ("-".to_owned(), "new".to_owned()),
+        };
+        println!("{:<14}{:>12}{:>12}{:>10}", name, before, format_rate(*rate), delta);
+    }
+    Ok(())
+}
+
+/// Instructions per second running `rom` headless with the timers ticking.
+fn measure(rom: &[u8], instructions: usize) -> Result<f64, anyhow::Error> {
+    let mut emulator = Emulator::headless(rom)?;
+    let start = Instant::now();
+    emulator.run_for(instructions)?;
+    Ok(instructions as f64 / start.elapsed().as_secs_f64().max(f64::EPSILON))
+}
+
+fn format_rate(rate: f64) -> String {
+    format!("{:.2} M/s", rate / 1e6)
+}
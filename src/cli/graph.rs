@@ -0,0 +1,86 @@
+use std::{
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use chip8rs::{
+    analysis::ControlFlow,
+    emulator::Interpreter,
+    symbols::SymbolTable,
+};
+use clap::Args;
+use serde_json::{
+    json,
+    Value,
+};
+
+use super::read_rom;
+
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    rom: PathBuf,
+
+    /// Load the ROM at 0x600 like the ETI-660 instead of 0x200
+    #[arg(long)]
+    eti_660: bool,
+
+    /// Labels for the nodes, defaults to the ROM's `.sym` file if there is one
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+
+    /// Write the graph to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write the subroutines and data ranges as a JSON object instead of a Graphviz graph
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: &GraphArgs) -> Result<(), anyhow::Error> {
+    let rom = read_rom(&args.rom)?;
+    let symbols = match &args.symbols {
+        Some(path) => SymbolTable::load(path).with_context(|| format!("can't read {}", path.display()))?,
+        None => SymbolTable::load_or_default(SymbolTable::path_for_rom(&args.rom))?,
+    };
+    let interpreter = if args.eti_660 {
+        Interpreter::Eti660
+    } else {
+        Interpreter::default()
+    };
+
+    let flow = ControlFlow::analyze(&rom, interpreter.load_address() as u16);
+    let text = if args.json {
+        to_json(&flow, &symbols).to_string() + "\n"
+    } else {
+        flow.to_dot(&symbols)
+    };
+    match &args.output {
+        Some(path) => fs::write(path, text).with_context(|| format!("can't write {}", path.display()))?,
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn to_json(flow: &ControlFlow, symbols: &SymbolTable) -> Value {
+    let subroutines: Vec<Value> = flow
+        .subroutines()
+        .iter()
+        .map(|(address, subroutine)| {
+            json!({
+                "address": address,
+                "label": symbols.locate(*address),
+                "calls": subroutine.calls,
+                "jumps": subroutine.jumps,
+                "computed_jump": subroutine.computed_jump,
+            })
+        })
+        .collect();
+    let data: Vec<Value> = flow
+        .data_ranges()
+        .iter()
+        .map(|range| json!({ "start": range.start, "end": range.end }))
+        .collect();
+    json!({ "subroutines": subroutines, "data": data })
+}
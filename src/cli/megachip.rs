@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chip8rs::megachip::MegaChip;
+use clap::Args;
+
+use super::{
+    read_rom,
+    After,
+    Failure,
+};
+
+#[derive(Args, Debug)]
+pub struct MegaChipArgs {
+    rom: PathBuf,
+
+    /// Emulated time to run for, in seconds (`2s`) or instructions (`10000i`)
+    #[arg(long, default_value = "2s")]
+    after: After,
+
+    /// Save the 256x192 display as an image, the format follows the extension
+    #[cfg(feature = "image")]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: &MegaChipArgs) -> Result<(), anyhow::Error> {
+    let rom = read_rom(&args.rom)?;
+    let mut machine = MegaChip::new(&rom).map_err(|error| Failure::RomInvalid(error.into()))?;
+    machine
+        .run_for(args.after.instructions() as usize)
+        .map_err(Failure::Emulation)?;
+
+    println!(
+        "{} instructions, {} mode{}",
+        machine.instruction_count(),
+        if machine.is_mega() { "MegaChip" } else { "CHIP-8" },
+        if machine.has_exited() { ", exited" } else { "" }
+    );
+    if let Some(sample) = machine.sample() {
+        println!("playing digitised sound at 0x{:06X} (not audible)", sample.address);
+    }
+    let unimplemented: Vec<String> = machine
+        .unimplemented_opcodes()
+        .iter()
+        .map(|op_code| format!("{:04X}", op_code))
+        .collect();
+    if !unimplemented.is_empty() {
+        println!("skipped unimplemented {}", unimplemented.join(" "));
+    }
+
+    #[cfg(feature = "image")]
+    if let Some(output) = &args.output {
+        machine
+            .image()
+            .save(output)
+            .with_context(|| format!("can't write {}", output.display()))?;
+    }
+    Ok(())
+}
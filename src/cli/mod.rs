@@ -0,0 +1,151 @@
+//! Headless subcommands of the client, run without opening a window.
+
+mod bench;
+mod graph;
+#[cfg(feature = "megachip")]
+mod megachip;
+mod quirks;
+mod scan;
+#[cfg(feature = "image")]
+mod screenshot;
+mod sprites;
+mod trace;
+mod verify;
+
+use std::{
+    fs,
+    io,
+    path::Path,
+    str::FromStr,
+};
+
+use chip8rs::emulator::Emulator;
+use clap::Subcommand;
+use thiserror::Error;
+
+const INSTRUCTIONS_PER_SECOND: f64 = 700.0;
+
+/// How long a headless run lasts, `2s` of emulated time at the default speed or `10000i` instructions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum After {
+    Seconds(f64),
+    Instructions(u64),
+}
+
+impl After {
+    fn instructions(&self) -> u64 {
+        match self {
+            After::Seconds(seconds) => (seconds * INSTRUCTIONS_PER_SECOND).round() as u64,
+            After::Instructions(instructions) => *instructions,
+        }
+    }
+}
+
+impl FromStr for After {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a duration like 2s or 10000i", value);
+        if let Some(seconds) = value.strip_suffix('s') {
+            match seconds.parse::<f64>() {
+                Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(After::Seconds(seconds)),
+                _ => Err(invalid()),
+            }
+        } else if let Some(instructions) = value.strip_suffix('i') {
+            instructions.parse().map(After::Instructions).map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// A failure scripts may want to tell apart from the rest without parsing messages, each with its own exit code.
+/// Everything else, like a settings file that can't be read, exits with 1.
+#[derive(Error, Debug)]
+pub enum Failure {
+    #[error(transparent)]
+    RomNotFound(anyhow::Error),
+
+    #[error(transparent)]
+    RomInvalid(anyhow::Error),
+
+    #[error(transparent)]
+    Emulation(anyhow::Error),
+
+    #[error("{0}")]
+    Verification(String),
+}
+
+impl Failure {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Failure::RomNotFound(_) => 2,
+            Failure::RomInvalid(_) => 3,
+            Failure::Emulation(_) => 4,
+            Failure::Verification(_) => 5,
+        }
+    }
+}
+
+/// The exit code for `error`, 1 unless a [`Failure`] caused it.
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    error.downcast_ref::<Failure>().map_or(1, Failure::exit_code)
+}
+
+/// Reads a ROM, reporting a missing file as [`Failure::RomNotFound`].
+pub fn read_rom(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+    fs::read(path).map_err(|error| {
+        let not_found = error.kind() == io::ErrorKind::NotFound;
+        let error = anyhow::Error::new(error).context(format!("can't read {}", path.display()));
+        if not_found {
+            Failure::RomNotFound(error).into()
+        } else {
+            error
+        }
+    })
+}
+
+/// A headless emulator running `rom`, reporting a ROM it can't load as [`Failure::RomInvalid`].
+fn headless(rom: &[u8]) -> Result<Emulator, Failure> {
+    Emulator::headless(rom).map_err(Failure::RomInvalid)
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the bundled test ROMs headless and report their speed against a baseline from an earlier build
+    BenchCompare(bench::BenchCompareArgs),
+    /// Follow the jumps and calls of a ROM from its entry point and print a Graphviz graph of its subroutines
+    Graph(graph::GraphArgs),
+    /// Run a MegaChip-8 ROM headless on the experimental MegaChip core and save its display
+    #[cfg(feature = "megachip")]
+    Megachip(megachip::MegaChipArgs),
+    /// Run a probe for each quirk the Timendus quirks ROM checks under every interpreter and print which ones show
+    Quirks(quirks::QuirksArgs),
+    /// Run every ROM in a directory headless and report crashes, unimplemented op codes and display hashes
+    Scan(scan::ScanArgs),
+    /// Run a ROM headless and print or save every sprite it draws with its address
+    Sprites(sprites::SpritesArgs),
+    /// Run a ROM headless and print the machine state before every instruction
+    Trace(trace::TraceArgs),
+    /// Run a ROM headless for a while and save the display as an image
+    #[cfg(feature = "image")]
+    Screenshot(screenshot::ScreenshotArgs),
+    /// Run the bundled test ROM suite headless and fail if any check does
+    Verify(verify::VerifyArgs),
+}
+
+pub fn run(command: &Command) -> Result<(), anyhow::Error> {
+    match command {
+        Command::BenchCompare(args) => bench::run(args),
+        Command::Graph(args) => graph::run(args),
+        #[cfg(feature = "megachip")]
+        Command::Megachip(args) => megachip::run(args),
+        Command::Quirks(args) => quirks::run(args),
+        Command::Scan(args) => scan::run(args),
+        Command::Sprites(args) => sprites::run(args),
+        Command::Trace(args) => trace::run(args),
+        #[cfg(feature = "image")]
+        Command::Screenshot(args) => screenshot::run(args),
+        Command::Verify(args) => verify::run(args),
+    }
+}
@@ -0,0 +1,280 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use chip8rs::{
+    display::Framebuffer,
+    emulator::{
+        Emulator,
+        Interpreter,
+    },
+};
+use clap::Args;
+
+use super::{
+    read_rom,
+    After,
+};
+
+/// Every probe halts well within this many instructions.
+const INSTRUCTIONS: usize = 32;
+
+const INTERPRETERS: [Interpreter; 5] = [
+    Interpreter::CosmacVIP,
+    Interpreter::HiresVIP,
+    Interpreter::Eti660,
+    Interpreter::Chip48,
+    Interpreter::SuperChip,
+];
+
+/// Where the quirks ROM expects to be loaded.
+const LOAD_ADDRESS: usize = 0x200;
+
+/// How long the platform key is held to get past the quirks ROM's menu.
+const KEY_HOLD: usize = 360;
+
+#[derive(Args, Debug)]
+pub struct QuirksArgs {
+    /// The Timendus quirks ROM, `5-quirks.ch8`, to run instead of the built-in probes
+    #[arg(long)]
+    rom: Option<PathBuf>,
+
+    /// Hex key picking the platform in the quirks ROM's menu, by default 1 (CHIP-8) for the VIP interpreters and 2
+    /// (SUPER-CHIP) for the rest
+    #[arg(long, value_parser = parse_hex_key)]
+    platform_key: Option<u8>,
+
+    /// Emulated time the quirks ROM gets after the menu, in seconds (`10s`) or instructions (`100000i`)
+    #[arg(long, default_value = "10s")]
+    after: After,
+}
+
+fn parse_hex_key(value: &str) -> Result<u8, String> {
+    u8::from_str_radix(value, 16)
+        .ok()
+        .filter(|key| *key < 16)
+        .ok_or_else(|| format!("`{}` isn't a hex key from 0 to F", value))
+}
+
+/// A short program testing one of the quirks the Timendus quirks ROM checks, built for a load address, and whether
+/// the quirk showed once it halted, on and off meaning the same as in the ROM's results.
+struct Probe {
+    name: &'static str,
+    program: fn(u16) -> Vec<u16>,
+    observed: fn(&Emulator) -> bool,
+}
+
+const PROBES: [Probe; 6] = [
+    Probe {
+        name: "vF reset",
+        // VF = 5, V1 |= V2
+        program: |base| vec![0x6F05, 0x8121, 0x1000 | (base + 4)],
+        observed: |emulator| emulator.registers()[0xF] == 0,
+    },
+    Probe {
+        name: "memory",
+        // I = 0x300, store V0 and V1
+        program: |base| vec![0xA300, 0xF155, 0x1000 | (base + 4)],
+        observed: |emulator| emulator.index_register() == 0x302,
+    },
+    Probe {
+        name: "display wait",
+        // Delay 1, draw twice and read the delay back before the first tick
+        program: |base| vec![0x6101, 0xF115, 0xD005, 0xD005, 0xF207, 0x1000 | (base + 10)],
+        observed: |emulator| emulator.registers()[2] == 0,
+    },
+    Probe {
+        name: "clipping",
+        // A solid 8x4 sprite at (60, 30), half of it past the right edge
+        program: |base| {
+            vec![
+                0x603C,
+                0x611E,
+                0xA000 | (base + 10),
+                0xD014,
+                0x1000 | (base + 8),
+                0xFFFF,
+                0xFFFF,
+            ]
+        },
+        observed: |emulator| emulator.framebuffer().get(60, 30) && !emulator.framebuffer().get(0, 30),
+    },
+    Probe {
+        name: "shifting",
+        // V1 = 0x10, V2 = 0x04, V1 = V2 >> 1 on the VIP, V1 >>= 1 with the quirk
+        program: |base| vec![0x6110, 0x6204, 0x8126, 0x1000 | (base + 6)],
+        observed: |emulator| emulator.registers()[1] == 0x08,
+    },
+    Probe {
+        name: "jumping",
+        // V0 = 0, V2 = 2, jump to base + 10 plus V0 on the VIP or V2 with the quirk, skipping V3 = 1
+        program: |base| {
+            vec![
+                0x6000,
+                0x6202,
+                0xB000 | (base + 10),
+                0x1000 | (base + 6),
+                0x0000,
+                0x6301,
+                0x1000 | (base + 12),
+            ]
+        },
+        observed: |emulator| emulator.registers()[3] == 0,
+    },
+];
+
+pub fn run(args: &QuirksArgs) -> Result<(), anyhow::Error> {
+    let results = match &args.rom {
+        Some(path) => run_rom(&read_rom(path)?, args)?,
+        None => run_probes()?,
+    };
+
+    let header = INTERPRETERS.map(|interpreter| interpreter.name());
+    print_row("quirk", &header);
+    for (name, row) in results {
+        print_row(
+            name,
+            &row.iter()
+                .map(|on| match on {
+                    Some(true) => "on",
+                    Some(false) => "off",
+                    None => "-",
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+    Ok(())
+}
+
+fn print_row(name: &str, cells: &[&str]) {
+    let mut line = format!("{:<14}", name);
+    for cell in cells {
+        line.push_str(&format!("{:<12}", cell));
+    }
+    println!("{}", line.trim_end());
+}
+
+/// Each quirk and whether every interpreter has it, `None` where it couldn't be checked.
+type Results = Vec<(&'static str, Vec<Option<bool>>)>;
+
+fn run_probes() -> Result<Results, anyhow::Error> {
+    let mut results = Vec::new();
+    for probe in &PROBES {
+        let row = INTERPRETERS
+            .iter()
+            .map(|interpreter| run_probe(probe, *interpreter).map(Some))
+            .collect::<Result<Vec<_>, _>>()?;
+        results.push((probe.name, row));
+    }
+    Ok(results)
+}
+
+fn run_probe(probe: &Probe, interpreter: Interpreter) -> Result<bool, anyhow::Error> {
+    let program: Vec<u8> = (probe.program)(interpreter.load_address() as u16)
+        .into_iter()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+    let mut emulator = Emulator::headless(&program)?;
+    emulator.set_interpreter(interpreter);
+    emulator.run_for(INSTRUCTIONS)?;
+    Ok((probe.observed)(&emulator))
+}
+
+/// Runs the quirks ROM under every interpreter, picking the platform in its menu, and reads the result screen. The
+/// ROM is built for 0x200 and can't run under the ETI-660.
+fn run_rom(rom: &[u8], args: &QuirksArgs) -> Result<Results, anyhow::Error> {
+    let mut columns = Vec::new();
+    for interpreter in INTERPRETERS {
+        if interpreter.load_address() != LOAD_ADDRESS {
+            columns.push(None);
+            continue;
+        }
+        let key = args
+            .platform_key
+            .unwrap_or(if interpreter.vip_quirks() { 1 } else { 2 });
+        let mut emulator = Emulator::headless(rom)?;
+        emulator.set_interpreter(interpreter);
+        emulator.inject_key(key, true);
+        emulator.run_for(KEY_HOLD)?;
+        emulator.inject_key(key, false);
+        emulator.run_for(args.after.instructions() as usize)?;
+
+        let Some(column) = read_results(emulator.framebuffer()) else {
+            bail!(
+                "can't read the quirks ROM's results under {}, the display was:\n{}",
+                interpreter.name(),
+                to_text(emulator.framebuffer())
+            );
+        };
+        columns.push(Some(column));
+    }
+
+    Ok(PROBES
+        .iter()
+        .enumerate()
+        .map(|(quirk, probe)| {
+            (
+                probe.name,
+                columns
+                    .iter()
+                    .map(|column| column.as_ref().map(|column| column[quirk]))
+                    .collect(),
+            )
+        })
+        .collect())
+}
+
+/// Reads the ON or OFF that ends each of the last six lines of text, in the order of [`PROBES`]. Letters are runs
+/// of lit columns one blank column apart, so ON ends a line with two of them and OFF with three.
+fn read_results(framebuffer: &Framebuffer) -> Option<Vec<bool>> {
+    let lit_row = |y: usize| (0..framebuffer.width()).any(|x| framebuffer.get(x, y));
+    let mut lines = Vec::new();
+    let mut y = 0;
+    while y < framebuffer.height() {
+        if !lit_row(y) {
+            y += 1;
+            continue;
+        }
+        let top = y;
+        while y < framebuffer.height() && lit_row(y) {
+            y += 1;
+        }
+        lines.push(top..y);
+    }
+
+    let first = lines.len().checked_sub(PROBES.len())?;
+    lines[first..]
+        .iter()
+        .map(|rows| {
+            let lit_column = |x: usize| rows.clone().any(|y| framebuffer.get(x, y));
+            let mut letters = 0;
+            let mut x = (0..framebuffer.width()).rev().find(|x| lit_column(*x))?;
+            loop {
+                while lit_column(x) {
+                    x = x.checked_sub(1)?;
+                }
+                letters += 1;
+                // A wider gap ends the word
+                if x == 0 || !lit_column(x - 1) {
+                    break;
+                }
+                x -= 1;
+            }
+            match letters {
+                2 => Some(true),
+                3 => Some(false),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn to_text(framebuffer: &Framebuffer) -> String {
+    (0..framebuffer.height())
+        .map(|y| {
+            (0..framebuffer.width())
+                .map(|x| if framebuffer.get(x, y) { '#' } else { '.' })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
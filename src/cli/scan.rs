@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    panic,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+    thread,
+};
+
+use anyhow::Context;
+use chip8rs::emulator::Emulator;
+use clap::Args;
+use serde_json::json;
+
+const ROM_EXTENSIONS: [&str; 4] = ["ch8", "c8", "sc8", "xo8"];
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Directory searched recursively for .ch8, .c8, .sc8 and .xo8 files
+    dir: PathBuf,
+
+    /// Instructions to run per ROM
+    #[arg(long, default_value_t = 100_000)]
+    instructions: u64,
+
+    /// Worker threads, defaults to the number of CPUs
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Print one JSON object per ROM instead of a report
+    #[arg(long)]
+    json: bool,
+}
+
+enum Outcome {
+    Finished,
+    Exited,
+    Failed(String),
+    Panicked(String),
+}
+
+struct ScanResult {
+    path: PathBuf,
+    outcome: Outcome,
+    instructions: u64,
+    unimplemented: Vec<u16>,
+    checksum: Option<u64>,
+}
+
+pub fn run(args: &ScanArgs) -> Result<(), anyhow::Error> {
+    let mut roms = Vec::new();
+    find_roms(&args.dir, &mut roms)?;
+    roms.sort();
+
+    let threads = args
+        .threads
+        .or_else(|| thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1)
+        .clamp(1, roms.len().max(1));
+    let next = AtomicUsize::new(0);
+    // Panics are reported per ROM, keep the default hook from printing each one
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut results: Vec<ScanResult> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    while let Some(path) = roms.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        results.push(scan_rom(path, args.instructions));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    panic::set_hook(hook);
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if args.json {
+        for result in &results {
+            print_json(result, &args.dir);
+        }
+        return Ok(());
+    }
+    for result in &results {
+        let status = match &result.outcome {
+            Outcome::Finished => "ok".to_owned(),
+            Outcome::Exited => "exited".to_owned(),
+            Outcome::Failed(error) => format!("error: {}", error.lines().next().unwrap_or_default()),
+            Outcome::Panicked(message) => format!("panicked: {}", message),
+        };
+        let checksum = result
+            .checksum
+            .map(|checksum| format!("{:016x}", checksum))
+            .unwrap_or_else(|| "-".repeat(16));
+        let path = result.path.strip_prefix(&args.dir).unwrap_or(&result.path);
+        print!("{} {:>9} {} {}", checksum, result.instructions, path.display(), status);
+        if !result.unimplemented.is_empty() {
+            let opcodes: Vec<String> = result.unimplemented.iter().map(|op| format!("{:04X}", op)).collect();
+            print!(" (unimplemented: {})", opcodes.join(" "));
+        }
+        println!();
+    }
+
+    let count = |matches: fn(&ScanResult) -> bool| results.iter().filter(|result| matches(result)).count();
+    println!(
+        "{} ROMs: {} failed, {} panicked, {} used unimplemented op codes",
+        results.len(),
+        count(|result| matches!(result.outcome, Outcome::Failed(_))),
+        count(|result| matches!(result.outcome, Outcome::Panicked(_))),
+        count(|result| !result.unimplemented.is_empty()),
+    );
+
+    Ok(())
+}
+
+fn print_json(result: &ScanResult, dir: &Path) {
+    let (status, error) = match &result.outcome {
+        Outcome::Finished => ("ok", None),
+        Outcome::Exited => ("exited", None),
+        Outcome::Failed(error) => ("error", Some(error.lines().next().unwrap_or_default())),
+        Outcome::Panicked(message) => ("panicked", Some(message.as_str())),
+    };
+    let path = result.path.strip_prefix(dir).unwrap_or(&result.path);
+    println!(
+        "{}",
+        json!({
+            "path": path.to_string_lossy(),
+            "status": status,
+            "error": error,
+            "instructions": result.instructions,
+            "unimplemented": result.unimplemented,
+            "checksum": result.checksum.map(|checksum| format!("{:016x}", checksum)),
+        })
+    );
+}
+
+fn find_roms(dir: &Path, roms: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+    let entries = fs::read_dir(dir).with_context(|| format!("can't read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_roms(&path, roms)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| ROM_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+        {
+            roms.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn scan_rom(path: &Path, instructions: u64) -> ScanResult {
+    let mut result = ScanResult {
+        path: path.to_owned(),
+        outcome: Outcome::Finished,
+        instructions: 0,
+        unimplemented: Vec::new(),
+        checksum: None,
+    };
+    let mut emulator = match fs::read(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|rom| Emulator::headless(&rom))
+    {
+        Ok(emulator) => emulator,
+        Err(error) => {
+            result.outcome = Outcome::Failed(error.to_string());
+            return result;
+        }
+    };
+
+    let run = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        emulator.run_for(instructions as usize)?;
+        Ok::<_, anyhow::Error>(if emulator.has_exited() {
+            Outcome::Exited
+        } else {
+            Outcome::Finished
+        })
+    }));
+    result.outcome = match run {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(error)) => Outcome::Failed(error.to_string()),
+        Err(payload) => Outcome::Panicked(
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned()),
+        ),
+    };
+    result.instructions = emulator.instruction_count();
+    result.unimplemented = emulator.unimplemented_opcodes().iter().copied().collect();
+    result.checksum = Some(emulator.framebuffer().checksum());
+
+    result
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chip8rs::{
+    emulator::Palette,
+    screenshot,
+};
+use clap::Args;
+
+use super::{
+    headless,
+    read_rom,
+    After,
+    Failure,
+};
+
+#[derive(Args, Debug)]
+pub struct ScreenshotArgs {
+    rom: PathBuf,
+
+    /// Emulated time before the capture, in seconds (`2s`) or instructions (`10000i`)
+    #[arg(long, default_value = "2s")]
+    after: After,
+
+    /// Image to write, the format follows the extension
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Size of a CHIP-8 pixel in the image
+    #[arg(long, default_value_t = 10, conflicts_with = "native")]
+    pixel_size: u32,
+
+    /// Save one image pixel per CHIP-8 pixel
+    #[arg(long)]
+    native: bool,
+
+    /// Embed the ROM name and instruction count as PNG text chunks
+    #[arg(long)]
+    metadata: bool,
+
+    #[arg(long, default_value = "green", value_parser = parse_palette)]
+    palette: Palette,
+}
+
+pub fn run(args: &ScreenshotArgs) -> Result<(), anyhow::Error> {
+    let rom = read_rom(&args.rom)?;
+    let mut emulator = headless(&rom)?;
+    emulator.set_palette(args.palette);
+
+    emulator
+        .run_for(args.after.instructions() as usize)
+        .map_err(Failure::Emulation)?;
+
+    let image = emulator.framebuffer_image(if args.native { 1 } else { args.pixel_size.max(1) });
+    if !args.metadata {
+        return image
+            .save(&args.output)
+            .with_context(|| format!("can't write {}", args.output.display()));
+    }
+    let name = args.rom.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let metadata = [
+        ("Title", name),
+        ("Instructions", emulator.instruction_count().to_string()),
+        ("Software", "chip8rs".to_owned()),
+    ];
+    screenshot::save_png(&image, &metadata, &args.output)?;
+    Ok(())
+}
+
+fn parse_palette(value: &str) -> Result<Palette, String> {
+    Palette::from_name(value).ok_or_else(|| format!("`{}` is not one of {}", value, Palette::NAMES.join(", ")))
+}
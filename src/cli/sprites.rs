@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chip8rs::sprites::SpriteRecorder;
+use clap::Args;
+
+use super::{
+    headless,
+    read_rom,
+    After,
+    Failure,
+};
+
+/// Cells per row of the sprite sheet.
+#[cfg(feature = "image")]
+const SHEET_COLUMNS: u32 = 16;
+
+#[derive(Args, Debug)]
+pub struct SpritesArgs {
+    rom: PathBuf,
+
+    /// Emulated time to record for, in seconds (`2s`) or instructions (`10000i`)
+    #[arg(long, default_value = "10s")]
+    after: After,
+
+    /// Save the sprites as an image sheet, 16 per row in address order, instead of printing them
+    #[cfg(feature = "image")]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Size of a sprite pixel in the sheet
+    #[cfg(feature = "image")]
+    #[arg(long, default_value_t = 4)]
+    pixel_size: u32,
+}
+
+pub fn run(args: &SpritesArgs) -> Result<(), anyhow::Error> {
+    let rom = read_rom(&args.rom)?;
+    let mut emulator = headless(&rom)?;
+    let recorder = SpriteRecorder::default();
+    emulator.add_observer(Box::new(recorder.clone()));
+
+    emulator
+        .run_for(args.after.instructions() as usize)
+        .map_err(Failure::Emulation)?;
+
+    let sprites = recorder.sprites();
+    #[cfg(feature = "image")]
+    if let Some(output) = &args.output {
+        recorder
+            .sheet(SHEET_COLUMNS, args.pixel_size.max(1))
+            .save(output)
+            .with_context(|| format!("can't write {}", output.display()))?;
+        for (cell, sprite) in sprites.iter().enumerate() {
+            println!(
+                "row {:>2} column {:>2}: 0x{:03X}, {} rows",
+                cell as u32 / SHEET_COLUMNS,
+                cell as u32 % SHEET_COLUMNS,
+                sprite.address,
+                sprite.rows.len()
+            );
+        }
+        return Ok(());
+    }
+
+    for sprite in &sprites {
+        println!("0x{:03X}, {} rows", sprite.address, sprite.rows.len());
+        println!("{}", sprite.to_text());
+    }
+    Ok(())
+}
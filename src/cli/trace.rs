@@ -0,0 +1,186 @@
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufWriter,
+        Write,
+    },
+    mem,
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use anyhow::Context;
+use chip8rs::{
+    emulator::{
+        Emulator,
+        InstructionData,
+    },
+    observer::Observer,
+    symbols::SymbolTable,
+};
+use clap::{
+    Args,
+    ValueEnum,
+};
+
+use super::{
+    headless,
+    read_rom,
+    Failure,
+};
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Args, Debug)]
+pub struct TraceArgs {
+    rom: PathBuf,
+
+    /// Instructions to trace, stops early when the program exits or fails
+    #[arg(long, default_value_t = 1000)]
+    instructions: u64,
+
+    #[arg(long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+
+    /// Same as `--format jsonl`
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+
+    /// Write the trace to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Labels for the `label` column, defaults to the ROM's `.sym` file if there is one
+    #[arg(long, value_name = "FILE")]
+    symbols: Option<PathBuf>,
+}
+
+pub fn run(args: &TraceArgs) -> Result<(), anyhow::Error> {
+    let rom = read_rom(&args.rom)?;
+    let mut emulator = headless(&rom)?;
+    let symbols = match &args.symbols {
+        Some(path) => SymbolTable::load(path).with_context(|| format!("can't read {}", path.display()))?,
+        None => SymbolTable::load_or_default(SymbolTable::path_for_rom(&args.rom))?,
+    };
+
+    let mut writer: BufWriter<Box<dyn Write + Send>> = BufWriter::new(match &args.output {
+        Some(path) => Box::new(File::create(path).with_context(|| format!("can't create {}", path.display()))?),
+        None => Box::new(io::stdout()),
+    });
+    let format = if args.json { Format::Jsonl } else { args.format };
+    if let Format::Csv = format {
+        let registers: Vec<String> = (0..16).map(|register| format!("v{:x}", register)).collect();
+        writeln!(writer, "pc,opcode,{},i,sp,dt,st,label", registers.join(","))?;
+    }
+
+    let trace = TraceWriter::new(writer, symbols, format);
+    emulator.add_observer(Box::new(trace.clone()));
+    let result = emulator.run_for(args.instructions as usize);
+    trace.finish()?;
+
+    result.map_err(|error| Failure::Emulation(error).into())
+}
+
+/// Writes the state before every instruction as one record and keeps the first write error, after which it writes
+/// nothing more. Add a clone to an emulator as an observer and finish the trace with the one kept.
+#[derive(Clone)]
+struct TraceWriter {
+    output: Arc<Mutex<TraceOutput>>,
+}
+
+struct TraceOutput {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    symbols: SymbolTable,
+    format: Format,
+    result: io::Result<()>,
+}
+
+impl TraceWriter {
+    fn new(writer: BufWriter<Box<dyn Write + Send>>, symbols: SymbolTable, format: Format) -> Self {
+        Self {
+            output: Arc::new(Mutex::new(TraceOutput {
+                writer,
+                symbols,
+                format,
+                result: Ok(()),
+            })),
+        }
+    }
+
+    /// The first write error, or the result of flushing what was written.
+    fn finish(&self) -> io::Result<()> {
+        let mut output = self.output.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        mem::replace(&mut output.result, Ok(()))?;
+        output.writer.flush()
+    }
+}
+
+impl Observer for TraceWriter {
+    fn before_instruction(&mut self, emulator: &Emulator, instruction: &InstructionData) {
+        let Ok(mut output) = self.output.lock() else {
+            return;
+        };
+        let TraceOutput {
+            writer,
+            symbols,
+            format,
+            result,
+        } = &mut *output;
+        if result.is_ok() {
+            *result = write_record(writer, emulator, instruction, symbols, *format);
+        }
+    }
+}
+
+fn write_record(
+    writer: &mut impl Write,
+    emulator: &Emulator,
+    instruction: &InstructionData,
+    symbols: &SymbolTable,
+    format: Format,
+) -> io::Result<()> {
+    // Observers run once the program counter has moved past the instruction
+    let pc = emulator.pc() - 2;
+    let opcode = instruction.op_code;
+    let registers = emulator.registers();
+    let (i, sp) = (emulator.index_register(), emulator.stack().len());
+    let (dt, st) = (emulator.delay_timer(), emulator.sound_timer());
+    let label = symbols.locate(pc as u16);
+
+    match format {
+        Format::Csv => {
+            write!(writer, "{:03X},{:04X}", pc, opcode)?;
+            for value in registers {
+                write!(writer, ",{:02X}", value)?;
+            }
+            writeln!(writer, ",{:03X},{},{},{},{}", i, sp, dt, st, label.unwrap_or_default())
+        }
+        Format::Jsonl => {
+            let registers: Vec<String> = registers.iter().map(u8::to_string).collect();
+            let label = label.map(|label| {
+                let escaped = label.replace('\\', r"\\").replace('"', r#"\""#);
+                format!(r#","label":"{}""#, escaped)
+            });
+            writeln!(
+                writer,
+                r#"{{"pc":{},"opcode":{},"v":[{}],"i":{},"sp":{},"dt":{},"st":{}{}}}"#,
+                pc,
+                opcode,
+                registers.join(","),
+                i,
+                sp,
+                dt,
+                st,
+                label.unwrap_or_default()
+            )
+        }
+    }
+}
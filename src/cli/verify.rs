@@ -0,0 +1,158 @@
+use anyhow::bail;
+use chip8rs::{
+    display::Framebuffer,
+    emulator::{
+        Emulator,
+        Interpreter,
+    },
+};
+use clap::{
+    Args,
+    ValueEnum,
+};
+use serde_json::json;
+
+use super::Failure;
+
+/// Instructions each test ROM gets before its display is compared, enough for every suite ROM to settle.
+const INSTRUCTIONS: usize = 20_000;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Profile {
+    Vip,
+    Schip,
+    Xochip,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Quirk profile to run the suite with
+    #[arg(long, value_enum, default_value_t = Profile::Schip)]
+    profile: Profile,
+
+    /// Print the results as a JSON object instead of a report
+    #[arg(long)]
+    json: bool,
+}
+
+/// A ROM from the Timendus test suite and the checksum of its display once every test on it passed.
+struct Check {
+    name: &'static str,
+    rom: &'static [u8],
+    checksum: u64,
+}
+
+const CHECKS: [Check; 4] = [
+    Check {
+        name: "1-chip8-logo",
+        rom: include_bytes!("../../assets/roms/test/1-chip8-logo.ch8"),
+        checksum: 0xf9f0851b7979c971,
+    },
+    Check {
+        name: "IBM Logo",
+        rom: include_bytes!("../../assets/roms/test/IBM Logo.ch8"),
+        checksum: 0x8bdcc6951ac87709,
+    },
+    Check {
+        name: "3-corax+",
+        rom: include_bytes!("../../assets/roms/test/3-corax+.ch8"),
+        checksum: 0x14645f42c52c1836,
+    },
+    Check {
+        name: "4-flags",
+        rom: include_bytes!("../../assets/roms/test/4-flags.ch8"),
+        checksum: 0x4726f7c490272cae,
+    },
+];
+
+pub fn run(args: &VerifyArgs) -> Result<(), anyhow::Error> {
+    let interpreter = match args.profile {
+        Profile::Vip => Interpreter::CosmacVIP,
+        Profile::Schip => Interpreter::SuperChip,
+        Profile::Xochip => bail!("there is no XO-CHIP interpreter to verify yet"),
+    };
+
+    if !args.json {
+        println!("verifying {}", interpreter.name());
+    }
+    let mut failed = 0;
+    let mut results = Vec::new();
+    for check in &CHECKS {
+        let result = run_check(check, interpreter);
+        if result.is_err() {
+            failed += 1;
+        }
+        if args.json {
+            results.push(json!({
+                "name": check.name,
+                "passed": result.is_ok(),
+                "error": result.err().map(|report| report.lines().next().unwrap_or_default().to_owned()),
+            }));
+            continue;
+        }
+        match result {
+            Ok(()) => println!("  ok      {}", check.name),
+            Err(report) => println!("  FAILED  {}: {}", check.name, report),
+        }
+    }
+
+    if args.json {
+        println!(
+            "{}",
+            json!({
+                "interpreter": interpreter.name(),
+                "checks": results,
+                "failed": failed,
+            })
+        );
+    }
+    if failed > 0 {
+        Err(Failure::Verification(format!(
+            "{} of {} checks failed",
+            failed,
+            CHECKS.len()
+        )))?;
+    }
+    if !args.json {
+        println!("all {} checks passed", CHECKS.len());
+    }
+    Ok(())
+}
+
+fn run_check(check: &Check, interpreter: Interpreter) -> Result<(), String> {
+    let mut emulator = Emulator::headless(check.rom).map_err(|error| error.to_string())?;
+    emulator.set_interpreter(interpreter);
+    emulator
+        .run_for(INSTRUCTIONS)
+        .map_err(|error| format!("{} after {} instructions", error, emulator.instruction_count()))?;
+
+    let checksum = emulator.framebuffer().checksum();
+    if checksum != check.checksum {
+        return Err(format!(
+            "display {:016x} instead of {:016x}\n{}",
+            checksum,
+            check.checksum,
+            draw(emulator.framebuffer())
+        ));
+    }
+    Ok(())
+}
+
+/// The display as text, two pixel rows per line, so a failing test's crosses can be read in the terminal.
+fn draw(framebuffer: &Framebuffer) -> String {
+    let mut text = String::new();
+    for y in (0..framebuffer.height()).step_by(2) {
+        text.push_str("    ");
+        for x in 0..framebuffer.width() {
+            let bottom = y + 1 < framebuffer.height() && framebuffer.get(x, y + 1);
+            text.push(match (framebuffer.get(x, y), bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
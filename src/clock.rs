@@ -0,0 +1,77 @@
+//! Where the runner gets the time from, so tests can drive it without waiting.
+
+use std::{
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Blocks until `deadline`, or returns right away if it already passed.
+    fn sleep_until(&self, deadline: Instant);
+}
+
+/// The wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Time that only moves when told to. Clones share the same time, so a test can keep one to advance while the
+/// runner reads another, e.g. by 16.7ms from a frame callback to make every frame last exactly one 60 Hz tick.
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut now) = self.now.lock() {
+            *now += duration;
+        }
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.now
+            .lock()
+            .map(|now| *now)
+            .unwrap_or_else(|poisoned| *poisoned.into_inner())
+    }
+
+    /// Jumps straight to `deadline` instead of waiting for it.
+    fn sleep_until(&self, deadline: Instant) {
+        if let Ok(mut now) = self.now.lock() {
+            *now = (*now).max(deadline);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs,
+    io,
+    path::Path,
+};
+
+use macroquad::input::KeyCode;
+use thiserror::Error;
+
+use crate::{
+    bindings::{
+        Action,
+        Binding,
+        Bindings,
+        Conflict,
+    },
+    emulator::{
+        Interpreter,
+        Palette,
+        KEY_LAYOUT,
+    },
+    persist::PersistRange,
+    screenshot::ScreenshotSize,
+};
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("config io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("line {line}: expected `key = value`")]
+    InvalidLine { line: usize },
+
+    #[error("line {line}: unknown setting `{key}`")]
+    UnknownSetting { line: usize, key: String },
+
+    #[error("line {line}: invalid value `{value}` for `{key}`")]
+    InvalidValue { line: usize, key: String, value: String },
+}
+
+#[rustfmt::skip]
+const KEY_NAMES: [(KeyCode, &str); 52] = [
+    (KeyCode::Key0, "0"), (KeyCode::Key1, "1"), (KeyCode::Key2, "2"), (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"), (KeyCode::Key5, "5"), (KeyCode::Key6, "6"), (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"), (KeyCode::Key9, "9"),
+    (KeyCode::A, "a"), (KeyCode::B, "b"), (KeyCode::C, "c"), (KeyCode::D, "d"), (KeyCode::E, "e"),
+    (KeyCode::F, "f"), (KeyCode::G, "g"), (KeyCode::H, "h"), (KeyCode::I, "i"), (KeyCode::J, "j"),
+    (KeyCode::K, "k"), (KeyCode::L, "l"), (KeyCode::M, "m"), (KeyCode::N, "n"), (KeyCode::O, "o"),
+    (KeyCode::P, "p"), (KeyCode::Q, "q"), (KeyCode::R, "r"), (KeyCode::S, "s"), (KeyCode::T, "t"),
+    (KeyCode::U, "u"), (KeyCode::V, "v"), (KeyCode::W, "w"), (KeyCode::X, "x"), (KeyCode::Y, "y"),
+    (KeyCode::Z, "z"),
+    (KeyCode::Kp0, "kp0"), (KeyCode::Kp1, "kp1"), (KeyCode::Kp2, "kp2"), (KeyCode::Kp3, "kp3"),
+    (KeyCode::Kp4, "kp4"), (KeyCode::Kp5, "kp5"), (KeyCode::Kp6, "kp6"), (KeyCode::Kp7, "kp7"),
+    (KeyCode::Kp8, "kp8"), (KeyCode::Kp9, "kp9"),
+    (KeyCode::KpDivide, "kpdivide"), (KeyCode::KpMultiply, "kpmultiply"), (KeyCode::KpSubtract, "kpsubtract"),
+    (KeyCode::KpAdd, "kpadd"), (KeyCode::KpEnter, "kpenter"), (KeyCode::KpDecimal, "kpdecimal"),
+];
+
+/// Name used for `key` in the config file, `None` if it can't be bound.
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(code, _)| *code == key).map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+        .map(|(code, _)| *code)
+}
+
+/// User settings that can be changed while running, stored as `key = value` lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub speed: f64,
+    pub palette: Palette,
+    pub interpreter: Interpreter,
+    pub volume: f32,
+    pub key_click: bool,
+    /// Show the display's changes once per timer tick, see [`crate::emulator::Emulator::set_draw_batching`].
+    pub draw_batching: bool,
+    /// Continue from the state saved when the same ROM was last closed.
+    pub resume_last_session: bool,
+    /// Save the state when quitting and offer to continue from it next time, without resuming right away.
+    pub auto_save: bool,
+    /// Physical keys laid out like the hex keypad, see [`crate::emulator::Emulator::set_key_layout`].
+    pub keys: [[KeyCode; 4]; 4],
+    /// A second set of physical keys merged into the same keypad, so two players can share a keyboard.
+    pub second_keys: Option<[[KeyCode; 4]; 4]>,
+    /// Keys for emulator shortcuts like screenshots and the pause menu.
+    pub bindings: Bindings,
+    /// Send every key to the game and ignore all shortcuts except the pause menu.
+    pub game_capture: bool,
+    pub screenshot_size: ScreenshotSize,
+    /// Embed the ROM name and frame number in screenshots.
+    pub screenshot_metadata: bool,
+    /// RAM kept across runs, keyed by ROM file name, for games that store high scores at fixed addresses.
+    pub persist: BTreeMap<String, PersistRange>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            palette: Palette::default(),
+            interpreter: Interpreter::default(),
+            volume: 0.5,
+            key_click: false,
+            draw_batching: false,
+            resume_last_session: false,
+            auto_save: false,
+            keys: KEY_LAYOUT,
+            second_keys: None,
+            bindings: Bindings::default(),
+            game_capture: false,
+            screenshot_size: ScreenshotSize::default(),
+            screenshot_metadata: true,
+            persist: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let (key, value) = content.split_once('=').ok_or(ConfigError::InvalidLine { line })?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || ConfigError::InvalidValue {
+                line,
+                key: key.to_owned(),
+                value: value.to_owned(),
+            };
+            match key {
+                "speed" => {
+                    config.speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed: &f64| speed.is_finite() && *speed > 0.0)
+                        .ok_or_else(invalid)?
+                }
+                "palette" => config.palette = Palette::from_name(value).ok_or_else(invalid)?,
+                "interpreter" => config.interpreter = Interpreter::from_name(value).ok_or_else(invalid)?,
+                "volume" => {
+                    config.volume = value
+                        .parse()
+                        .ok()
+                        .filter(|volume| (0.0..=1.0).contains(volume))
+                        .ok_or_else(invalid)?
+                }
+                "key_click" => config.key_click = parse_switch(value).ok_or_else(invalid)?,
+                "draw_batching" => config.draw_batching = parse_switch(value).ok_or_else(invalid)?,
+                "resume_last_session" => config.resume_last_session = parse_switch(value).ok_or_else(invalid)?,
+                "auto_save" => config.auto_save = parse_switch(value).ok_or_else(invalid)?,
+                "game_capture" => config.game_capture = parse_switch(value).ok_or_else(invalid)?,
+                "screenshot_size" => config.screenshot_size = ScreenshotSize::from_name(value).ok_or_else(invalid)?,
+                "screenshot_metadata" => config.screenshot_metadata = parse_switch(value).ok_or_else(invalid)?,
+                "keys" => config.keys = parse_layout(value).ok_or_else(invalid)?,
+                "second_keys" => config.second_keys = Some(parse_layout(value).ok_or_else(invalid)?),
+                _ if key.starts_with("persist.") => {
+                    let range = PersistRange::parse(value).ok_or_else(invalid)?;
+                    config.persist.insert(key["persist.".len()..].to_owned(), range);
+                }
+                _ => {
+                    let action = key.strip_prefix("bind.").and_then(Action::from_name).ok_or_else(|| {
+                        ConfigError::UnknownSetting {
+                            line,
+                            key: key.to_owned(),
+                        }
+                    })?;
+                    let binding = match value {
+                        "none" => None,
+                        _ => Some(Binding::parse(value).ok_or_else(invalid)?),
+                    };
+                    config.bindings.set(action, binding);
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "speed = {}", self.speed);
+        let _ = writeln!(text, "palette = {}", self.palette.name());
+        let _ = writeln!(text, "interpreter = {}", self.interpreter.name());
+        let _ = writeln!(text, "volume = {}", self.volume);
+        let _ = writeln!(text, "key_click = {}", switch_text(self.key_click));
+        let _ = writeln!(text, "draw_batching = {}", switch_text(self.draw_batching));
+        let _ = writeln!(text, "resume_last_session = {}", switch_text(self.resume_last_session));
+        let _ = writeln!(text, "auto_save = {}", switch_text(self.auto_save));
+        let _ = writeln!(text, "game_capture = {}", switch_text(self.game_capture));
+        let _ = writeln!(text, "screenshot_size = {}", self.screenshot_size.name());
+        let _ = writeln!(text, "screenshot_metadata = {}", switch_text(self.screenshot_metadata));
+        let _ = writeln!(text, "keys = {}", layout_text(&self.keys));
+        if let Some(second_keys) = &self.second_keys {
+            let _ = writeln!(text, "second_keys = {}", layout_text(second_keys));
+        }
+        for action in Action::ALL {
+            let binding = self.bindings.get(action).map(|binding| binding.text());
+            let _ = writeln!(
+                text,
+                "bind.{} = {}",
+                action.name(),
+                binding.as_deref().unwrap_or("none")
+            );
+        }
+        for (rom, range) in &self.persist {
+            let _ = writeln!(text, "persist.{} = {}", rom, range.text());
+        }
+        text
+    }
+
+    /// The RAM to keep across runs of the ROM at `rom`, looked up by its file name.
+    pub fn persist_range(&self, rom: &str) -> Option<PersistRange> {
+        let name = Path::new(rom).file_name()?.to_str()?;
+        self.persist.get(name).copied()
+    }
+
+    /// Shortcuts that clash with the keypad or each other, only counting the pause menu in game capture mode.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let keypad: Vec<KeyCode> = self
+            .keys
+            .iter()
+            .chain(self.second_keys.iter().flatten())
+            .flatten()
+            .copied()
+            .collect();
+        let mut conflicts = self.bindings.conflicts(&keypad);
+        if self.game_capture {
+            conflicts.retain(|conflict| {
+                matches!(
+                    conflict,
+                    Conflict::Keypad {
+                        action: Action::Menu,
+                        ..
+                    }
+                )
+            });
+        }
+        conflicts
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Loads the config at `path`, falling back to the defaults if the file doesn't exist yet.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn switch_text(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// 16 key names in keypad order, row by row.
+fn parse_layout(value: &str) -> Option<[[KeyCode; 4]; 4]> {
+    let keys: Vec<KeyCode> = value.split_whitespace().map(key_from_name).collect::<Option<_>>()?;
+    if keys.len() != 16 {
+        return None;
+    }
+    let mut layout = [[KeyCode::Unknown; 4]; 4];
+    for (i, key) in keys.into_iter().enumerate() {
+        layout[i / 4][i % 4] = key;
+    }
+    Some(layout)
+}
+
+fn layout_text(layout: &[[KeyCode; 4]; 4]) -> String {
+    let keys: Vec<&str> = layout.iter().flatten().filter_map(|key| key_name(*key)).collect();
+    keys.join(" ")
+}
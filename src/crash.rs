@@ -0,0 +1,52 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use crate::{
+    emulator::Emulator,
+    symbols::SymbolTable,
+};
+
+pub(crate) fn write_crash_dump(
+    dir: &Path,
+    rom_path: &str,
+    emulator: &Emulator,
+    symbols: &SymbolTable,
+    error: &dyn Error,
+) -> anyhow::Result<PathBuf> {
+    let bundle = dir.join(format!(
+        "{}_{}",
+        Path::new(rom_path).file_stem().unwrap_or_default().to_string_lossy(),
+        SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()
+    ));
+    fs::create_dir_all(&bundle)?;
+
+    let mut report = format!("rom: {}\nerror: {}\n", rom_path, error);
+    let mut source = error.source();
+    while let Some(cause) = source {
+        writeln!(report, "caused by: {}", cause)?;
+        source = cause.source();
+    }
+    if !symbols.is_empty() {
+        writeln!(report, "recent instructions with labels:")?;
+        for entry in emulator.history().iter() {
+            writeln!(report, "  {}", entry.describe(symbols))?;
+        }
+    }
+    fs::write(bundle.join("crash.txt"), report)?;
+
+    emulator.savestate().save(bundle.join("state.sav"))?;
+    emulator.export_render_target(&bundle.join("screenshot.png").to_string_lossy());
+
+    Ok(bundle)
+}
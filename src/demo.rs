@@ -0,0 +1,38 @@
+//! ROMs compiled into the library, so the emulator has something to run before any ROM file is at hand.
+
+/// A public domain program bundled with the emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Demo {
+    IbmLogo,
+    Maze,
+    Puzzle,
+}
+
+impl Demo {
+    pub const NAMES: [&'static str; 3] = ["ibm-logo", "maze", "puzzle"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ibm-logo" | "ibm" => Some(Demo::IbmLogo),
+            "maze" => Some(Demo::Maze),
+            "puzzle" => Some(Demo::Puzzle),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Demo::IbmLogo => "ibm-logo",
+            Demo::Maze => "maze",
+            Demo::Puzzle => "puzzle",
+        }
+    }
+
+    pub fn rom(&self) -> &'static [u8] {
+        match self {
+            Demo::IbmLogo => include_bytes!("../assets/roms/test/IBM Logo.ch8"),
+            Demo::Maze => include_bytes!("../assets/roms/maze.ch8"),
+            Demo::Puzzle => include_bytes!("../assets/roms/puzzle.ch8"),
+        }
+    }
+}
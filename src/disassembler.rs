@@ -0,0 +1,57 @@
+use crate::symbols::SymbolTable;
+
+pub fn mnemonic(op_code: u16) -> String {
+    mnemonic_with_symbols(op_code, &SymbolTable::default())
+}
+
+/// Like [`mnemonic`], with jump, call and index targets shown as their label when `symbols` has one.
+pub fn mnemonic_with_symbols(op_code: u16, symbols: &SymbolTable) -> String {
+    let x = (op_code & 0x0F00) >> 8;
+    let y = (op_code & 0x00F0) >> 4;
+    let n = op_code & 0x000F;
+    let nn = op_code & 0x00FF;
+    let nnn = op_code & 0x0FFF;
+    let target = symbols
+        .label(nnn)
+        .map_or_else(|| format!("0x{:03X}", nnn), str::to_owned);
+
+    match (op_code & 0xF000, n, nn) {
+        (0x0000, _, _) if op_code == 0x00E0 => "CLS".into(),
+        (0x0000, _, _) if op_code == 0x00EE => "RET".into(),
+        (0x0000, _, _) if op_code == 0x00FD => "EXIT".into(),
+        (0x0000, _, _) => format!("SYS 0x{:03X}", nnn),
+        (0x1000, _, _) => format!("JP {}", target),
+        (0x2000, _, _) => format!("CALL {}", target),
+        (0x3000, _, _) => format!("SE V{:X}, 0x{:02X}", x, nn),
+        (0x4000, _, _) => format!("SNE V{:X}, 0x{:02X}", x, nn),
+        (0x5000, 0x0, _) => format!("SE V{:X}, V{:X}", x, y),
+        (0x6000, _, _) => format!("LD V{:X}, 0x{:02X}", x, nn),
+        (0x7000, _, _) => format!("ADD V{:X}, 0x{:02X}", x, nn),
+        (0x8000, 0x0, _) => format!("LD V{:X}, V{:X}", x, y),
+        (0x8000, 0x1, _) => format!("OR V{:X}, V{:X}", x, y),
+        (0x8000, 0x2, _) => format!("AND V{:X}, V{:X}", x, y),
+        (0x8000, 0x3, _) => format!("XOR V{:X}, V{:X}", x, y),
+        (0x8000, 0x4, _) => format!("ADD V{:X}, V{:X}", x, y),
+        (0x8000, 0x5, _) => format!("SUB V{:X}, V{:X}", x, y),
+        (0x8000, 0x6, _) => format!("SHR V{:X}, V{:X}", x, y),
+        (0x8000, 0x7, _) => format!("SUBN V{:X}, V{:X}", x, y),
+        (0x8000, 0xE, _) => format!("SHL V{:X}, V{:X}", x, y),
+        (0x9000, 0x0, _) => format!("SNE V{:X}, V{:X}", x, y),
+        (0xA000, _, _) => format!("LD I, {}", target),
+        (0xB000, _, _) => format!("JP V0, {}", target),
+        (0xC000, _, _) => format!("RND V{:X}, 0x{:02X}", x, nn),
+        (0xD000, _, _) => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        (0xE000, _, 0x9E) => format!("SKP V{:X}", x),
+        (0xE000, _, 0xA1) => format!("SKNP V{:X}", x),
+        (0xF000, _, 0x07) => format!("LD V{:X}, DT", x),
+        (0xF000, _, 0x0A) => format!("LD V{:X}, K", x),
+        (0xF000, _, 0x15) => format!("LD DT, V{:X}", x),
+        (0xF000, _, 0x18) => format!("LD ST, V{:X}", x),
+        (0xF000, _, 0x1E) => format!("ADD I, V{:X}", x),
+        (0xF000, _, 0x29) => format!("LD F, V{:X}", x),
+        (0xF000, _, 0x33) => format!("LD B, V{:X}", x),
+        (0xF000, _, 0x55) => format!("LD [I], V{:X}", x),
+        (0xF000, _, 0x65) => format!("LD V{:X}, [I]", x),
+        _ => format!("DW 0x{:04X}", op_code),
+    }
+}
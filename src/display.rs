@@ -0,0 +1,226 @@
+/// Monochrome display contents, independent of how they end up on screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+    dirty: bool,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+            dirty: true,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the pixel at `(x, y)` is lit, `false` outside the display.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    /// Row-major pixels, `width * height` long.
+    pub fn pixels(&self) -> &[bool] {
+        &self.pixels
+    }
+
+    /// FNV-1a hash of the size and pixels, stable across runs and builds for comparing displays.
+    pub fn checksum(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let size = [self.width as u64, self.height as u64];
+        for byte in size
+            .iter()
+            .flat_map(|dimension| dimension.to_le_bytes())
+            .chain(self.pixels.iter().map(|lit| *lit as u8))
+        {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// One bit per pixel, rows top to bottom with the leftmost pixel in the most significant bit and every row
+    /// padded to a whole byte. 256 bytes for the 64x32 display.
+    pub fn to_bits(&self) -> Vec<u8> {
+        let row_len = self.width.div_ceil(8);
+        let mut bits = vec![0; row_len * self.height];
+        for (idx, _) in self.pixels.iter().enumerate().filter(|(_, lit)| **lit) {
+            let (x, y) = (idx % self.width, idx / self.width);
+            bits[y * row_len + x / 8] |= 0x80 >> (x % 8);
+        }
+        bits
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(false);
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Returns whether the display changed since the last call and resets the flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Bytes needed by [`Framebuffer::pack`].
+    pub(crate) fn packed_len(&self) -> usize {
+        self.pixels.len().div_ceil(8)
+    }
+
+    /// Packs the pixels one bit each, least significant bit first, the layout savestates keep in display RAM.
+    pub(crate) fn pack(&self, bytes: &mut [u8]) {
+        bytes.fill(0);
+        for (idx, _) in self.pixels.iter().enumerate().filter(|(_, lit)| **lit) {
+            if let Some(byte) = bytes.get_mut(idx / 8) {
+                *byte |= 1 << (idx % 8);
+            }
+        }
+    }
+
+    fn change(&self, idx: usize) -> PixelChange {
+        PixelChange {
+            x: idx % self.width,
+            y: idx / self.width,
+            lit: self.pixels[idx],
+        }
+    }
+
+    pub(crate) fn unpack(&mut self, bytes: &[u8]) {
+        for (idx, pixel) in self.pixels.iter_mut().enumerate() {
+            *pixel = bytes.get(idx / 8).is_some_and(|byte| (byte >> (idx % 8)) & 1 == 1);
+        }
+        self.dirty = true;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelChange {
+    pub x: usize,
+    pub y: usize,
+    pub lit: bool,
+}
+
+/// What changed on the display between two [`DisplayDelta::update`] calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// The display is new or changed size, so every pixel is listed and the whole screen should be redrawn.
+    pub full: bool,
+    /// Changed pixels in row-major order.
+    pub pixels: Vec<PixelChange>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty() && !self.full
+    }
+
+    /// Rows with at least one changed pixel, top to bottom.
+    pub fn rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = self.pixels.iter().map(|change| change.y).collect();
+        rows.dedup();
+        rows
+    }
+}
+
+/// Keeps a copy of the display as last seen, so frontends drawing over slow links can send only what changed.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayDelta {
+    previous: Option<Framebuffer>,
+}
+
+impl DisplayDelta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares `framebuffer` with the display passed to the previous call and remembers it for the next.
+    pub fn update(&mut self, framebuffer: &Framebuffer) -> Changes {
+        let full = self
+            .previous
+            .as_ref()
+            .is_none_or(|previous| (previous.width, previous.height) != (framebuffer.width, framebuffer.height));
+        let mut changes = Changes {
+            full,
+            pixels: Vec::new(),
+        };
+        match self.previous.as_mut() {
+            Some(previous) if !full => {
+                if previous.pixels == framebuffer.pixels {
+                    return changes;
+                }
+                for (idx, (before, now)) in previous.pixels.iter_mut().zip(&framebuffer.pixels).enumerate() {
+                    if before != now {
+                        changes.pixels.push(framebuffer.change(idx));
+                        *before = *now;
+                    }
+                }
+            }
+            _ => {
+                changes.pixels = (0..framebuffer.pixels.len())
+                    .map(|idx| framebuffer.change(idx))
+                    .collect();
+                self.previous = Some(framebuffer.clone());
+            }
+        }
+        changes
+    }
+
+    /// Forgets the last display, so the next update lists every pixel, e.g. after a frontend reconnects.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+/// XORs `sprite` onto the display with its top left corner at `(x, y)`, one byte per row with the most significant
+/// bit leftmost.
+///
+/// The start position wraps around the display while the parts of the sprite that extend past the right or bottom
+/// edge are clipped. Returns whether any lit pixel was turned off.
+pub fn draw_sprite(fb: &mut Framebuffer, x: usize, y: usize, sprite: &[u8]) -> bool {
+    if fb.width == 0 || fb.height == 0 {
+        return false;
+    }
+    let (start_x, start_y) = (x % fb.width, y % fb.height);
+
+    let mut collision = false;
+    for (row, bits) in sprite.iter().enumerate() {
+        let y = start_y + row;
+        if y >= fb.height {
+            break;
+        }
+        for col in 0..8 {
+            let x = start_x + col;
+            if x >= fb.width {
+                break;
+            }
+            if (bits >> (7 - col)) & 1 == 0 {
+                continue;
+            }
+
+            let pixel = &mut fb.pixels[y * fb.width + x];
+            collision |= *pixel;
+            *pixel = !*pixel;
+        }
+    }
+    fb.dirty = true;
+
+    collision
+}
@@ -1,18 +1,18 @@
 use std::{
-    collections::HashMap,
-    fs,
+    collections::BTreeSet,
+    fmt,
 };
 
 use macroquad::{
-    audio::{
-        stop_sound,
-        Sound,
-    },
     camera::{
+        set_camera,
         set_default_camera,
         Camera2D,
     },
-    color,
+    color::{
+        self,
+        Color,
+    },
     input::{
         is_key_down,
         is_key_released,
@@ -23,18 +23,25 @@ use macroquad::{
         gl_use_default_material,
         gl_use_material,
         load_material,
-        next_frame,
         render_target,
         Material,
+        MaterialParams,
         Rect,
         ShaderSource,
+        UniformDesc,
+        UniformType,
     },
+    shapes::draw_rectangle,
     texture::{
         draw_texture_ex,
         DrawTextureParams,
+        FilterMode,
         RenderTarget,
+        Texture2D,
     },
     window::{
+        clear_background,
+        screen_dpi_scale,
         screen_height,
         screen_width,
     },
@@ -42,50 +49,222 @@ use macroquad::{
 use thiserror::Error;
 
 use crate::{
+    audio::{
+        Beep,
+        Click,
+    },
+    cheats::CheatList,
     constants,
+    display::Framebuffer,
+    font::Font,
+    history::{
+        History,
+        HistoryEntry,
+        HistorySnapshot,
+    },
     mem::{
         AddressStack,
         Ram,
         RamError,
         Register,
         Rom,
+        VF,
+    },
+    observer::{
+        EmulatorEvent,
+        Observer,
     },
+    overlay,
     process::{
         self,
         ProcessingError,
     },
+    rng::Rng,
+    screenshot::{
+        ScreenshotError,
+        ScreenshotOptions,
+    },
+    state::Savestate,
 };
 
-#[rustfmt::skip]
-const FONT: [u8; 80] = [
-    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
-    0x20, 0x60, 0x20, 0x20, 0x70, // 1
-    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
-    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
-    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
-    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
-    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
-    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
-    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
-    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
-
-    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
-    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
-    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
-    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
-    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
-    0xF0, 0x80, 0xF0, 0x80, 0x80  // F
-];
-
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Interpreter {
     CosmacVIP,
+    /// The two-page COSMAC VIP variant with a 64x64 display.
+    HiresVIP,
+    /// The ETI-660 kit computer, loading programs at 0x600 with a 64x48 display.
+    Eti660,
     Chip48,
+    #[default]
     SuperChip,
 }
 
+impl Interpreter {
+    pub const NAMES: [&'static str; 5] = ["cosmac-vip", "hires-vip", "eti-660", "chip-48", "super-chip"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cosmac-vip" => Some(Interpreter::CosmacVIP),
+            "hires-vip" => Some(Interpreter::HiresVIP),
+            "eti-660" => Some(Interpreter::Eti660),
+            "chip-48" => Some(Interpreter::Chip48),
+            "super-chip" => Some(Interpreter::SuperChip),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpreter::CosmacVIP => "cosmac-vip",
+            Interpreter::HiresVIP => "hires-vip",
+            Interpreter::Eti660 => "eti-660",
+            Interpreter::Chip48 => "chip-48",
+            Interpreter::SuperChip => "super-chip",
+        }
+    }
+
+    /// Picks the interpreter a ROM can only run under, if it announces one. Hires programs start by jumping over
+    /// the original hires interpreter with `0x1260`.
+    pub fn detect(rom: &[u8]) -> Option<Self> {
+        rom.starts_with(&[0x12, 0x60]).then_some(Interpreter::HiresVIP)
+    }
+
+    /// Whether shifts, BNNN and FX55/FX65 behave like on the original COSMAC VIP.
+    pub fn vip_quirks(&self) -> bool {
+        matches!(
+            self,
+            Interpreter::CosmacVIP | Interpreter::HiresVIP | Interpreter::Eti660
+        )
+    }
+
+    /// Address programs are loaded to and start executing from.
+    pub fn load_address(&self) -> usize {
+        match self {
+            Interpreter::Eti660 => constants::ETI_660_MEMORY_OFFSET,
+            _ => constants::MEMORY_OFFSET,
+        }
+    }
+
+    /// Display size in CHIP-8 pixels.
+    pub fn resolution(&self) -> (i32, i32) {
+        match self {
+            Interpreter::HiresVIP => (constants::DISPLAY_WIDTH, constants::HIRES_DISPLAY_HEIGHT),
+            Interpreter::Eti660 => (constants::DISPLAY_WIDTH, constants::ETI_660_DISPLAY_HEIGHT),
+            _ => (constants::DISPLAY_WIDTH, constants::DISPLAY_HEIGHT),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Palette {
+    #[default]
+    Green,
+    Amber,
+    Paper,
+}
+
+impl Palette {
+    pub const NAMES: [&'static str; 3] = ["green", "amber", "paper"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "green" => Some(Palette::Green),
+            "amber" => Some(Palette::Amber),
+            "paper" => Some(Palette::Paper),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Green => "green",
+            Palette::Amber => "amber",
+            Palette::Paper => "paper",
+        }
+    }
+
+    pub fn foreground(&self) -> Color {
+        match self {
+            Palette::Green => Color::new(0.0, 1.0, 0.0, 1.0),
+            Palette::Amber => Color::new(1.0, 0.69, 0.0, 1.0),
+            Palette::Paper => Color::new(0.15, 0.15, 0.15, 1.0),
+        }
+    }
+
+    pub fn background(&self) -> Color {
+        match self {
+            Palette::Green | Palette::Amber => color::BLACK,
+            Palette::Paper => Color::new(0.85, 0.85, 0.8, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+impl Rotation {
+    pub fn from_degrees(degrees: u16) -> Option<Self> {
+        match degrees {
+            0 => Some(Rotation::None),
+            90 => Some(Rotation::Clockwise90),
+            180 => Some(Rotation::Clockwise180),
+            270 => Some(Rotation::Clockwise270),
+            _ => None,
+        }
+    }
+
+    pub fn radians(&self) -> f32 {
+        match self {
+            Rotation::None => 0.0,
+            Rotation::Clockwise90 => std::f32::consts::FRAC_PI_2,
+            Rotation::Clockwise180 => std::f32::consts::PI,
+            Rotation::Clockwise270 => 3.0 * std::f32::consts::FRAC_PI_2,
+        }
+    }
+
+    pub fn swaps_axes(&self) -> bool {
+        matches!(self, Rotation::Clockwise90 | Rotation::Clockwise270)
+    }
+
+    fn rotate_cell(&self, row: usize, col: usize, size: usize) -> (usize, usize) {
+        match self {
+            Rotation::None => (row, col),
+            Rotation::Clockwise90 => (col, size - 1 - row),
+            Rotation::Clockwise180 => (size - 1 - row, size - 1 - col),
+            Rotation::Clockwise270 => (size - 1 - col, row),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScaleFilter {
+    #[default]
+    Nearest,
+    Linear,
+    SharpBilinear,
+}
+
+impl ScaleFilter {
+    pub const NAMES: [&'static str; 3] = ["nearest", "linear", "sharp-bilinear"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nearest" => Some(ScaleFilter::Nearest),
+            "linear" => Some(ScaleFilter::Linear),
+            "sharp-bilinear" => Some(ScaleFilter::SharpBilinear),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct ProgramCounter(usize);
+pub struct ProgramCounter(pub(crate) usize);
 
 impl ProgramCounter {
     pub fn inner(&self) -> &usize {
@@ -104,84 +283,487 @@ impl ProgramCounter {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct InstructionData {
     pub op_code: u16,
     pub instruction: u16,
-    pub x: String,
-    pub y: String,
+    pub x: u8,
+    pub y: u8,
     pub n: u16,
     pub nn: u8,
     pub nnn: u16,
 }
 
+/// State from before an instruction that its [`EmulatorEvent`] is derived from.
+struct EventContext {
+    address: usize,
+    stack_top: Option<u16>,
+    stack_depth: usize,
+    sound_on: bool,
+    sprite_origin: (u8, u8),
+}
+
+/// Where the held state of the 16 hex keys comes from, polled once per frame. Implemented for closures taking the
+/// hex value, so an embedder can wire its own buttons.
+pub trait KeySource: Send {
+    fn is_down(&self, hex: u8) -> bool;
+
+    /// A key let go since the last poll, for sources that see presses shorter than a frame.
+    fn released(&self) -> Option<u8> {
+        None
+    }
+}
+
+impl<F: Fn(u8) -> bool + Send> KeySource for F {
+    fn is_down(&self, hex: u8) -> bool {
+        self(hex)
+    }
+}
+
+/// Drives the buzzer, switched on when the sound timer is set and off when it runs out. Implemented for closures
+/// taking the new on state, so an embedder can toggle a real buzzer or its own audio.
+pub trait Buzzer: Send {
+    fn start(&mut self, volume: f32);
+
+    fn stop(&mut self);
+
+    fn set_volume(&mut self, _volume: f32) {}
+}
+
+impl<F: FnMut(bool) + Send> Buzzer for F {
+    fn start(&mut self, _volume: f32) {
+        self(true)
+    }
+
+    fn stop(&mut self) {
+        self(false)
+    }
+}
+
+/// Physical keyboard keys, one per hex value in each layer. A hex key is down while any layer holds it.
+struct Keyboard {
+    layers: Vec<[KeyCode; 16]>,
+}
+
+impl KeySource for Keyboard {
+    fn is_down(&self, hex: u8) -> bool {
+        self.layers
+            .iter()
+            .filter_map(|key_codes| key_codes.get(hex as usize))
+            .any(|key_code| is_key_down(*key_code))
+    }
+
+    fn released(&self) -> Option<u8> {
+        self.layers
+            .iter()
+            .find_map(|key_codes| key_codes.iter().position(|key_code| is_key_released(*key_code)))
+            .map(|hex| hex as u8)
+    }
+}
+
+/// How often the keypad is read while stepping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputPolling {
+    /// Once in [`Emulator::begin_frame`], so every instruction of a frame sees the same keys. Recording and
+    /// replaying input rely on this.
+    #[default]
+    PerFrame,
+    /// Again before every `n`th instruction of a frame, for custom [`KeySource`]s that change within a frame. Lowers
+    /// their latency when many instructions run per frame. macroquad only updates key state in `next_frame`, so
+    /// this makes no difference for the keyboard and the client has no flag for it.
+    EveryInstructions(u32),
+}
+
 pub struct KeyPad {
-    key_code_hex_mapping: HashMap<u8, KeyCode>,
+    source: Option<Box<dyn KeySource>>,
+    pressed: [bool; 16],
+    released: Option<u8>,
+    /// Keys a mid-frame refresh saw go up, so the source doesn't report the release again at the next poll.
+    refreshed_releases: [bool; 16],
+    injected: [bool; 16],
+    injected_release: Option<u8>,
 }
 
+/// What the keypad reports to the program at one point, so rewinding can replay instructions against the keys that
+/// were held when they first ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeySnapshot {
+    pressed: [bool; 16],
+    released: Option<u8>,
+    refreshed_releases: [bool; 16],
+    injected: [bool; 16],
+    injected_release: Option<u8>,
+    waiting_for_key: bool,
+}
+
+#[rustfmt::skip]
+pub(crate) const HEX_LAYOUT: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+#[rustfmt::skip]
+pub(crate) const KEY_LAYOUT: [[KeyCode; 4]; 4] = [
+    [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4],
+    [KeyCode::Q,    KeyCode::W,    KeyCode::E,    KeyCode::R],
+    [KeyCode::A,    KeyCode::S,    KeyCode::D,    KeyCode::F],
+    [KeyCode::Z,    KeyCode::X,    KeyCode::C,    KeyCode::V],
+];
+
 impl KeyPad {
-    fn new() -> Self {
-        let key_code_hex_mapping: HashMap<u8, KeyCode> = HashMap::from([
-            (0x1, KeyCode::Key1),
-            (0x2, KeyCode::Key2),
-            (0x3, KeyCode::Key3),
-            (0xC, KeyCode::Key4),
-            (0x4, KeyCode::Q),
-            (0x5, KeyCode::W),
-            (0x6, KeyCode::E),
-            (0xD, KeyCode::R),
-            (0x7, KeyCode::A),
-            (0x8, KeyCode::S),
-            (0x9, KeyCode::D),
-            (0xE, KeyCode::F),
-            (0xA, KeyCode::Z),
-            (0x0, KeyCode::X),
-            (0xB, KeyCode::C),
-            (0xF, KeyCode::V),
-        ]);
-
-        Self { key_code_hex_mapping }
-    }
-
-    pub fn get_key_released(&self) -> Option<u8> {
-        self.key_code_hex_mapping
+    /// A keypad reading its keys from `source`, see [`Emulator::set_keypad`].
+    pub fn new(source: Box<dyn KeySource>) -> Self {
+        Self {
+            source: Some(source),
+            ..Self::headless()
+        }
+    }
+
+    /// Only sees injected keys, for emulators running without a window.
+    pub(crate) fn headless() -> Self {
+        Self {
+            source: None,
+            pressed: [false; 16],
+            released: None,
+            refreshed_releases: [false; 16],
+            injected: [false; 16],
+            injected_release: None,
+        }
+    }
+
+    /// Maps each hex key to the physical key found at its position after rotating each 4x4 layout.
+    fn rotated(layouts: &[[[KeyCode; 4]; 4]], rotation: Rotation) -> Self {
+        let layers = layouts
             .iter()
-            .find(|(_, code)| is_key_released(**code))
-            .map(|(hex, _)| *hex)
+            .map(|layout| {
+                let mut key_codes = [KeyCode::Unknown; 16];
+                for (row, hexes) in HEX_LAYOUT.iter().enumerate() {
+                    for (col, hex) in hexes.iter().enumerate() {
+                        let (key_row, key_col) = rotation.rotate_cell(row, col, 4);
+                        key_codes[*hex as usize] = layout[key_row][key_col];
+                    }
+                }
+                key_codes
+            })
+            .collect();
+
+        Self::new(Box::new(Keyboard { layers }))
+    }
+
+    /// Snapshots the source, so every instruction within a frame sees the same state.
+    fn poll(&mut self) {
+        let Some(source) = &self.source else {
+            return;
+        };
+        self.released = source
+            .released()
+            .filter(|hex| !self.refreshed_releases.get(*hex as usize).copied().unwrap_or(false));
+        self.refreshed_releases = [false; 16];
+        for (hex, pressed) in self.pressed.iter_mut().enumerate() {
+            let down = source.is_down(hex as u8);
+            if *pressed && !down && self.released.is_none() {
+                self.released = Some(hex as u8);
+            }
+            *pressed = down;
+        }
+    }
+
+    /// Reads the held keys again within a frame, releases only come from keys seen going up since the last read.
+    fn refresh(&mut self) {
+        let Some(source) = &self.source else {
+            return;
+        };
+        for (hex, pressed) in self.pressed.iter_mut().enumerate() {
+            let down = source.is_down(hex as u8);
+            if *pressed && !down {
+                self.refreshed_releases[hex] = true;
+                if self.released.is_none() {
+                    self.released = Some(hex as u8);
+                }
+            }
+            *pressed = down;
+        }
+    }
+
+    pub fn get_key_released(&mut self) -> Option<u8> {
+        self.released.take().or_else(|| self.injected_release.take())
     }
+
     pub fn is_key_pressed(&self, hex: u8) -> bool {
-        if let Some(key_code) = self.key_code_hex_mapping.get(&hex) {
-            is_key_down(*key_code)
-        } else {
-            false
+        let hex = hex as usize;
+        self.injected.get(hex).copied().unwrap_or(false) || self.pressed.get(hex).copied().unwrap_or(false)
+    }
+
+    pub fn inject(&mut self, hex: u8, pressed: bool) {
+        let Some(state) = self.injected.get_mut(hex as usize) else {
+            return;
+        };
+        if *state && !pressed {
+            self.injected_release = Some(hex);
+        }
+        *state = pressed;
+    }
+
+    fn end_frame(&mut self) {
+        self.released = None;
+        self.injected_release = None;
+    }
+
+    fn snapshot(&self, waiting_for_key: bool) -> KeySnapshot {
+        KeySnapshot {
+            pressed: self.pressed,
+            released: self.released,
+            refreshed_releases: self.refreshed_releases,
+            injected: self.injected,
+            injected_release: self.injected_release,
+            waiting_for_key,
         }
     }
+
+    fn restore(&mut self, snapshot: &KeySnapshot) {
+        self.pressed = snapshot.pressed;
+        self.released = snapshot.released;
+        self.refreshed_releases = snapshot.refreshed_releases;
+        self.injected = snapshot.injected;
+        self.injected_release = snapshot.injected_release;
+    }
+}
+
+/// Signs that a file is not a CHIP-8 program, like an HTML error page saved instead of the ROM.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomWarning {
+    /// A weak sign on its own, some programs end in an odd number of bytes of sprite data.
+    #[error("odd length of {len} bytes, instructions are two bytes each")]
+    OddLength { len: usize },
+
+    #[error("starts with text rather than instructions")]
+    Text,
+
+    #[error("first instruction jumps to 0x{target:03X}, below the program")]
+    JumpBelowProgram { target: u16 },
+}
+
+impl RomWarning {
+    /// Bytes needed before a ROM is judged to start with text.
+    const TEXT_PREFIX: usize = 8;
+
+    pub fn check(rom: &[u8]) -> Vec<Self> {
+        let mut warnings = Vec::new();
+        if rom.len() % 2 != 0 {
+            warnings.push(RomWarning::OddLength { len: rom.len() });
+        }
+        let prefix = rom.get(..Self::TEXT_PREFIX).unwrap_or_default();
+        if !prefix.is_empty()
+            && prefix
+                .iter()
+                .all(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace())
+        {
+            warnings.push(RomWarning::Text);
+        }
+        if let [high, low, ..] = *rom {
+            let op_code = u16::from_be_bytes([high, low]);
+            let target = op_code & 0x0FFF;
+            if matches!(op_code & 0xF000, 0x1000 | 0x2000) && (target as usize) < constants::MEMORY_OFFSET {
+                warnings.push(RomWarning::JumpBelowProgram { target });
+            }
+        }
+        warnings
+    }
+}
+
+/// Machine state when an instruction failed, enough to tell a stuck loop from a bad pointer in a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ErrorContext {
+    instructions: u64,
+    /// How often the failing address ran, this time included.
+    executions: u32,
+    index: u16,
+    delay_timer: u8,
+    sound_timer: u8,
+    stack: Vec<u16>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "after {} instructions, {} at this address",
+            self.instructions, self.executions
+        )?;
+        writeln!(
+            f,
+            "i 0x{:03X}  delay {}  sound {}",
+            self.index, self.delay_timer, self.sound_timer
+        )?;
+        write!(f, "stack:")?;
+        if self.stack.is_empty() {
+            write!(f, " empty")?;
+        }
+        for address in &self.stack {
+            write!(f, " 0x{:03X}", address)?;
+        }
+        writeln!(f)
+    }
 }
 
 #[derive(Error, Debug)]
 pub(crate) enum EmulatorError {
-    #[error("failed processing op code 0x{:04X}", op_code)]
-    OpError { source: ProcessingError, op_code: u16 },
+    #[error(
+        "failed processing op code 0x{:04X}\n{context}recent instructions:\n{history}",
+        op_code
+    )]
+    OpError {
+        source: ProcessingError,
+        op_code: u16,
+        context: ErrorContext,
+        history: HistorySnapshot,
+    },
 
-    #[error("failed to fetch instruction 0x{:04X}", pc.inner())]
-    PCInvalid { pc: ProgramCounter, source: RamError },
+    #[error("failed to fetch instruction 0x{:04X}\n{context}recent instructions:\n{history}", pc.inner())]
+    PCInvalid {
+        pc: ProgramCounter,
+        source: RamError,
+        context: ErrorContext,
+        history: HistorySnapshot,
+    },
 
-    #[error("failed renderingop code 0x{:04X}", op_code)]
-    RenderingFailed { source: ProcessingError, op_code: u16 },
+    #[error(
+        "failed renderingop code 0x{:04X}\n{context}recent instructions:\n{history}",
+        op_code
+    )]
+    RenderingFailed {
+        source: ProcessingError,
+        op_code: u16,
+        context: ErrorContext,
+        history: HistorySnapshot,
+    },
 }
 
 impl EmulatorError {
-    fn from_processing_error(source: ProcessingError, op_code: u16) -> EmulatorError {
+    fn from_processing_error(
+        source: ProcessingError,
+        op_code: u16,
+        context: ErrorContext,
+        history: HistorySnapshot,
+    ) -> EmulatorError {
         match op_code {
-            val if (val & 0xF000) == 0xD000 => EmulatorError::RenderingFailed { source, op_code },
-            _ => EmulatorError::OpError { source, op_code },
+            val if (val & 0xF000) == 0xD000 => EmulatorError::RenderingFailed {
+                source,
+                op_code,
+                context,
+                history,
+            },
+            _ => EmulatorError::OpError {
+                source,
+                op_code,
+                context,
+                history,
+            },
+        }
+    }
+}
+
+struct Video {
+    render_target: RenderTarget,
+    camera: Camera2D,
+    crt_material: Material,
+    /// The composed window contents, in physical pixels, redrawn only when something on it changed.
+    screen: RenderTarget,
+    screen_camera: Camera2D,
+    screen_size: (u32, u32),
+}
+
+impl Video {
+    fn new(window_size: (i32, i32)) -> Self {
+        let crt_material = load_material(
+            ShaderSource::Glsl {
+                vertex: include_str!("../assets/crt_vert.glsl"),
+                fragment: include_str!("../assets/crt_frag.glsl"),
+            },
+            MaterialParams {
+                uniforms: vec![
+                    UniformDesc::new("SharpBilinear", UniformType::Float1),
+                    UniformDesc::new("TextureSize", UniformType::Float2),
+                    UniformDesc::new("OutputSize", UniformType::Float2),
+                ],
+                ..Default::default()
+            },
+        )
+        .expect("Failed to create post processing material");
+        let (render_target, camera) = Self::target(window_size);
+        let screen_size = screen_pixels();
+        let (screen, screen_camera) = Self::screen_target(screen_size);
+
+        Self {
+            render_target,
+            camera,
+            crt_material,
+            screen,
+            screen_camera,
+            screen_size,
+        }
+    }
+
+    fn resize(&mut self, window_size: (i32, i32)) {
+        (self.render_target, self.camera) = Self::target(window_size);
+    }
+
+    /// One texel per CHIP-8 pixel, all scaling happens when the texture is presented.
+    fn target(window_size: (i32, i32)) -> (RenderTarget, Camera2D) {
+        let render_target = render_target(window_size.0 as u32, window_size.1 as u32);
+        render_target.texture.set_filter(FilterMode::Nearest);
+        let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., window_size.0 as f32, window_size.1 as f32));
+        camera.render_target = Some(render_target.clone());
+
+        (render_target, camera)
+    }
+
+    /// Returns whether the window changed size since the screen was last composed.
+    fn fit_screen(&mut self) -> bool {
+        let size = screen_pixels();
+        if size == self.screen_size {
+            return false;
         }
+        (self.screen, self.screen_camera) = Self::screen_target(size);
+        self.screen_size = size;
+        true
     }
+
+    /// A physical pixel per texel, drawn to in logical coordinates like the window itself.
+    fn screen_target(size: (u32, u32)) -> (RenderTarget, Camera2D) {
+        let screen = render_target(size.0, size.1);
+        screen.texture.set_filter(FilterMode::Nearest);
+        let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., screen_width(), screen_height()));
+        camera.render_target = Some(screen.clone());
+
+        (screen, camera)
+    }
+}
+
+fn screen_pixels() -> (u32, u32) {
+    let dpi = screen_dpi_scale();
+    (
+        ((screen_width() * dpi).round() as u32).max(1),
+        ((screen_height() * dpi).round() as u32).max(1),
+    )
+}
+
+struct Bezel {
+    texture: Texture2D,
+    margin: f32,
 }
 
 pub struct Emulator {
     interpreter: Interpreter,
+    selected_interpreter: Interpreter,
     memory: Ram,
+    framebuffer: Framebuffer,
+    /// The display as of the last timer tick, shown instead of the live one while draw batching is on.
+    batched: Option<Framebuffer>,
+    rom: Rom,
+    font: Font,
     pc: ProgramCounter,
     stack: AddressStack,
     register: Register,
@@ -189,75 +771,339 @@ pub struct Emulator {
     delay_timer: u8,
     sound_timer: u8,
     keypad: KeyPad,
+    custom_keypad: bool,
+    input_polling: InputPolling,
+    /// Instructions run since the last [`Emulator::begin_frame`].
+    frame_instructions: u32,
     pixel_size: i32,
     window_size: (i32, i32),
-    render_target: RenderTarget,
-    camera: Camera2D,
-    sound: Sound,
-    crt_material: Material,
+    video: Option<Video>,
+    sound: Option<Box<dyn Buzzer>>,
+    buzzing: bool,
+    click: Option<Box<dyn Buzzer>>,
+    key_click: bool,
+    last_pressed: [bool; 16],
+    just_pressed: [bool; 16],
+    rng: Rng,
+    history: History,
+    observers: Vec<Box<dyn Observer>>,
+    cheats: CheatList,
+    unimplemented: BTreeSet<u16>,
+    instruction_count: u64,
+    /// Times each address was fetched as an instruction since the last reset.
+    execution_counts: Vec<u32>,
+    draw_count: u64,
+    exited: bool,
+    waiting_for_key: bool,
+    /// Rewinding is running instructions again, which observers, counters and the key source must not see twice.
+    replaying: bool,
+    rotation: Rotation,
+    show_pixel_grid: bool,
+    bezel: Option<Bezel>,
+    /// A presentation setting changed, so the screen has to be composed again even if the display didn't.
+    recompose: bool,
+    pixel_aspect: f32,
+    scale_filter: ScaleFilter,
+    palette: Palette,
+    volume: f32,
+    key_layout: [[KeyCode; 4]; 4],
+    second_key_layout: Option<[[KeyCode; 4]; 4]>,
+    rotate_input: bool,
 }
 
 impl Emulator {
-    pub(crate) fn start(rom: Rom, pixel_size: i32, window_size: (i32, i32), beep: Sound) -> Self {
-        let material = load_material(
-            ShaderSource::Glsl {
-                vertex: &fs::read_to_string("assets/crt_vert.glsl").expect("Vertex shader missing"),
-                fragment: &fs::read_to_string("assets/crt_frag.glsl").expect("Fragment shader missing"),
-            },
-            Default::default(),
-        )
-        .expect("Failed to create post processing material");
-        let render_target = render_target((pixel_size * window_size.0) as u32, (pixel_size * window_size.1) as u32);
-        render_target
-            .texture
-            .set_filter(macroquad::texture::FilterMode::Nearest);
-        let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., screen_width(), screen_height()));
-        camera.render_target = Some(render_target.clone());
-        Self {
-            interpreter: Interpreter::SuperChip,
-            memory: Ram::load(rom, &FONT),
+    pub(crate) fn start(rom: Rom, pixel_size: i32, window_size: (i32, i32), beep: Beep, click: Click) -> Self {
+        let mut emulator = Self::new(rom, pixel_size, window_size);
+        emulator.video = Some(Video::new(emulator.window_size));
+        emulator.sound = Some(Box::new(beep));
+        emulator.click = Some(Box::new(click));
+        emulator.rebuild_keypad();
+        emulator
+    }
+
+    /// Creates an emulator without window, rendering or audio, driven through [`Emulator::step`] and the timer
+    /// ticks. Input only comes from [`Emulator::inject_key`].
+    pub fn headless(rom: &[u8]) -> Result<Self, anyhow::Error> {
+        let rom = Rom::from_bytes(rom.to_vec())?;
+        Ok(Self::new(rom, 1, (constants::DISPLAY_WIDTH, constants::DISPLAY_HEIGHT)))
+    }
+
+    fn new(rom: Rom, pixel_size: i32, window_size: (i32, i32)) -> Self {
+        let mut emulator = Self {
+            interpreter: Interpreter::default(),
+            selected_interpreter: Interpreter::default(),
+            memory: Ram::load(&rom, constants::MEMORY_OFFSET, Font::default().data()),
+            framebuffer: Framebuffer::new(window_size.0 as usize, window_size.1 as usize),
+            batched: None,
+            rom,
+            font: Font::default(),
             pc: ProgramCounter(constants::MEMORY_OFFSET),
             stack: AddressStack::default(),
             register: Register::new(),
             index_register: 0,
             delay_timer: 0,
             sound_timer: 0,
-            keypad: KeyPad::new(),
+            keypad: KeyPad::headless(),
+            custom_keypad: false,
+            input_polling: InputPolling::default(),
+            frame_instructions: 0,
             pixel_size,
             window_size,
-            render_target,
-            camera,
-            sound: beep,
-            crt_material: material,
+            video: None,
+            sound: None,
+            buzzing: false,
+            click: None,
+            key_click: false,
+            last_pressed: [false; 16],
+            just_pressed: [false; 16],
+            rng: Rng::new(),
+            history: History::new(constants::HISTORY_LENGTH),
+            observers: Vec::new(),
+            cheats: CheatList::default(),
+            unimplemented: BTreeSet::new(),
+            instruction_count: 0,
+            execution_counts: vec![0; constants::TOTAL_RAM],
+            draw_count: 0,
+            exited: false,
+            waiting_for_key: false,
+            replaying: false,
+            rotation: Rotation::None,
+            show_pixel_grid: false,
+            bezel: None,
+            recompose: true,
+            pixel_aspect: 1.,
+            scale_filter: ScaleFilter::Nearest,
+            palette: Palette::Green,
+            volume: 0.5,
+            key_layout: KEY_LAYOUT,
+            second_key_layout: None,
+            rotate_input: false,
+        };
+        emulator.apply_interpreter();
+        emulator.reset();
+        emulator
+    }
+
+    /// Fetches, decodes and executes a single instruction.
+    pub fn step(&mut self) -> Result<(), anyhow::Error> {
+        Ok(self.run()?)
+    }
+
+    /// Steps up to `instructions` instructions without rendering, ticking the timers after every 12th starting with
+    /// the first. Stops early when the program exits.
+    pub fn run_for(&mut self, instructions: usize) -> Result<(), anyhow::Error> {
+        self.run_until(|_| false, instructions).map(|_| ())
+    }
+
+    /// Steps like [`Emulator::run_for`] until `predicate` holds, checked before every instruction and after the
+    /// last, for at most `max` instructions. Returns whether the predicate was met.
+    pub fn run_until<F: FnMut(&Emulator) -> bool>(
+        &mut self,
+        mut predicate: F,
+        max: usize,
+    ) -> Result<bool, anyhow::Error> {
+        for _ in 0..max {
+            if predicate(self) {
+                return Ok(true);
+            }
+            if self.exited {
+                return Ok(false);
+            }
+            self.step()?;
+            if (self.instruction_count - 1) % constants::INSTRUCTIONS_PER_TICK == 0 {
+                self.tick_delay();
+                self.beep();
+            }
+        }
+        Ok(predicate(self))
+    }
+
+    fn error_context(&self, address: usize) -> ErrorContext {
+        ErrorContext {
+            instructions: self.instruction_count,
+            executions: self.execution_counts.get(address).copied().unwrap_or_default(),
+            index: self.index_register,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            stack: self.stack.as_slice().to_vec(),
         }
     }
 
-    pub(crate) async fn run(&mut self) -> Result<(), EmulatorError> {
+    pub(crate) fn run(&mut self) -> Result<(), EmulatorError> {
+        if let (InputPolling::EveryInstructions(every), false) = (self.input_polling, self.replaying) {
+            if self.frame_instructions > 0 && self.frame_instructions % every.max(1) == 0 {
+                self.keypad.refresh();
+            }
+        }
+        self.frame_instructions = self.frame_instructions.saturating_add(1);
+
         let op_code = self.memory.op_code(&self.pc).map_err(|err| EmulatorError::PCInvalid {
             pc: self.pc.clone(),
             source: err,
+            context: self.error_context(*self.pc.inner()),
+            history: self.history.snapshot(),
         })?;
 
+        let address = *self.pc.inner();
+        if !self.replaying {
+            self.history.push(HistoryEntry { pc: address, op_code });
+            if let Some(count) = self.execution_counts.get_mut(address) {
+                *count = count.saturating_add(1);
+            }
+        }
         self.pc.increment();
 
         let instruction_data = InstructionData {
             op_code,
             instruction: op_code & 0xF000,
-            x: format!("V{:X}", (op_code & 0x0F00) >> 8),
-            y: format!("V{:X}", (op_code & 0x00F0) >> 4),
+            x: ((op_code & 0x0F00) >> 8) as u8,
+            y: ((op_code & 0x00F0) >> 4) as u8,
             n: op_code & 0x000F,
             nn: (op_code & 0x00FF) as u8,
             nnn: op_code & 0x0FFF,
         };
-        self.execute(instruction_data)
-            .map_err(|err| EmulatorError::from_processing_error(err, op_code))
+
+        let mut observers = match self.replaying {
+            true => Vec::new(),
+            false => std::mem::take(&mut self.observers),
+        };
+        for observer in observers.iter_mut() {
+            observer.before_instruction(self, &instruction_data);
+        }
+        let before = EventContext {
+            address,
+            stack_top: self.stack.as_slice().last().copied(),
+            stack_depth: self.stack.as_slice().len(),
+            sound_on: self.sound_timer > 0,
+            sprite_origin: (
+                self.register.get(instruction_data.x).unwrap_or_default(),
+                self.register.get(instruction_data.y).unwrap_or_default(),
+            ),
+        };
+
+        let result = self.execute(instruction_data).map_err(|err| {
+            EmulatorError::from_processing_error(err, op_code, self.error_context(address), self.history.snapshot())
+        });
+
+        if result.is_ok() {
+            for cheat in self.cheats.enabled() {
+                if let Ok(byte) = self.memory.get_mut(cheat.address) {
+                    *byte = cheat.value;
+                }
+            }
+            self.instruction_count += 1;
+            if instruction_data.instruction == 0xD000 && !self.replaying {
+                self.draw_count += 1;
+            }
+            self.update_buzzer();
+            let event = self.instruction_event(&instruction_data, &before);
+            for observer in observers.iter_mut() {
+                if let Some(event) = &event {
+                    observer.on_event(self, event);
+                }
+                observer.after_instruction(self, &instruction_data);
+            }
+        }
+        if !self.replaying {
+            self.observers = observers;
+        }
+
+        result
+    }
+
+    fn instruction_event(
+        &mut self,
+        instruction_data: &InstructionData,
+        before: &EventContext,
+    ) -> Option<EmulatorEvent> {
+        let waiting = instruction_data.op_code & 0xF0FF == 0xF00A && self.pc() == before.address;
+        let started_waiting = waiting && !self.waiting_for_key;
+        self.waiting_for_key = waiting;
+
+        let stack = self.stack.as_slice();
+        match instruction_data.op_code {
+            0x00E0 => Some(EmulatorEvent::ScreenCleared),
+            0x0230 if self.interpreter == Interpreter::HiresVIP => Some(EmulatorEvent::ScreenCleared),
+            _ if instruction_data.instruction == 0xD000 => Some(EmulatorEvent::SpriteDrawn {
+                x: before.sprite_origin.0,
+                y: before.sprite_origin.1,
+                collision: self.register.get(VF).unwrap_or_default() == 1,
+            }),
+            _ if stack.len() > before.stack_depth => stack.last().copied().map(EmulatorEvent::StackPushed),
+            _ if stack.len() < before.stack_depth => before.stack_top.map(EmulatorEvent::StackPopped),
+            _ if !before.sound_on && self.sound_timer > 0 => Some(EmulatorEvent::SoundStarted),
+            _ if before.sound_on && self.sound_timer == 0 => Some(EmulatorEvent::SoundStopped),
+            _ if started_waiting => Some(EmulatorEvent::WaitingForKey),
+            _ => None,
+        }
+    }
+
+    fn emit(&mut self, event: EmulatorEvent) {
+        let mut observers = std::mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            observer.on_event(self, &event);
+        }
+        self.observers = observers;
+    }
+
+    pub(crate) fn rng(&self) -> &Rng {
+        &self.rng
+    }
+
+    /// Loads `state` together with the random number generator and instruction count saved with it, for rewinding.
+    pub(crate) fn restore_checkpoint(&mut self, state: &Savestate, rng: Rng, instruction_count: u64) {
+        self.load_state(state);
+        self.rng = rng;
+        self.instruction_count = instruction_count;
+    }
+
+    pub(crate) fn key_snapshot(&self) -> KeySnapshot {
+        self.keypad.snapshot(self.waiting_for_key)
+    }
+
+    pub(crate) fn restore_keys(&mut self, snapshot: &KeySnapshot) {
+        self.keypad.restore(snapshot);
+        self.waiting_for_key = snapshot.waiting_for_key;
+    }
+
+    /// Runs the next instruction again for rewinding, without observers, execution counts, the history or the key
+    /// source seeing it.
+    pub(crate) fn replay(&mut self) -> Result<(), anyhow::Error> {
+        self.replaying = true;
+        let result = self.run();
+        self.replaying = false;
+        Ok(result?)
+    }
+
+    /// Makes CXNN draw the same random numbers on every run with the same seed.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = Rng::seeded(seed);
+    }
+
+    /// Cheats whose addresses are rewritten after every instruction.
+    pub fn set_cheats(&mut self, cheats: CheatList) {
+        self.cheats = cheats;
+    }
+
+    pub fn cheats(&self) -> &CheatList {
+        &self.cheats
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
     }
 
     fn execute(&mut self, instruction_data: InstructionData) -> Result<(), ProcessingError> {
         match (instruction_data.op_code, instruction_data.instruction) {
             (0x0000, _) => {}
-            (0x00E0, _) => process::op_00E0(&self.camera, color::BLACK, &mut self.memory),
+            (0x00E0, _) => process::op_00E0(&mut self.framebuffer),
             (0x00EE, _) => process::op_00EE(&mut self.pc, &mut self.stack)?,
+            (0x00FD, _) => process::op_00FD(&mut self.exited),
+            (0x0230, _) if self.interpreter == Interpreter::HiresVIP => process::op_00E0(&mut self.framebuffer),
+            // The boot jump lands in the original hires interpreter, the program itself continues at 0x2C0
+            (0x1260, _) if self.interpreter == Interpreter::HiresVIP && self.pc() == constants::MEMORY_OFFSET + 2 => {
+                process::op_1NNN(&mut self.pc, 0x2C0)
+            }
             (_, 0x1000) => process::op_1NNN(&mut self.pc, instruction_data.nnn),
             (_, 0x2000) => process::op_2NNN(&mut self.stack, &mut self.pc, instruction_data.nnn),
             (_, 0x3000) => process::op_3XNN(&self.register, instruction_data.x, instruction_data.nn, &mut self.pc)?,
@@ -311,14 +1157,17 @@ impl Emulator {
                 instruction_data.x,
                 instruction_data.nnn,
             )?,
-            (_, 0xC000) => process::op_CXNN(&mut self.register, instruction_data.x, instruction_data.nn)?,
+            (_, 0xC000) => process::op_CXNN(
+                &mut self.register,
+                &mut self.rng,
+                instruction_data.x,
+                instruction_data.nn,
+            )?,
             (_, 0xD000) => process::DXYN(
-                &mut self.memory,
+                &self.memory,
                 &mut self.register,
                 self.index_register,
-                &self.camera,
-                &self.window_size,
-                self.pixel_size,
+                &mut self.framebuffer,
                 instruction_data,
             )?,
             (_, 0xE000) if instruction_data.op_code & 0xF0FF == 0xE09E => {
@@ -338,14 +1187,11 @@ impl Emulator {
                 process::op_FX1E(&self.register, instruction_data.x, &mut self.index_register)?
             }
             (_, 0xF000) if instruction_data.op_code & 0xF0FF == 0xF00A => {
-                process::op_FX0A(&mut self.register, &mut self.pc, &self.keypad, instruction_data.x)?
+                process::op_FX0A(&mut self.register, &mut self.pc, &mut self.keypad, instruction_data.x)?
+            }
+            (_, 0xF000) if instruction_data.op_code & 0xF0FF == 0xF018 => {
+                process::op_FX18(&mut self.register, instruction_data.x, &mut self.sound_timer)?
             }
-            (_, 0xF000) if instruction_data.op_code & 0xF0FF == 0xF018 => process::op_FX18(
-                &mut self.register,
-                instruction_data.x,
-                &mut self.sound_timer,
-                &self.sound,
-            )?,
             (_, 0xF000) if instruction_data.op_code & 0xF0FF == 0xF029 => {
                 process::op_FX29(&self.register, &mut self.index_register, instruction_data.x)?
             }
@@ -369,7 +1215,11 @@ impl Emulator {
                 &mut self.index_register,
                 instruction_data.x,
             )?,
-            _ => println!("Instruction not implemented: {:x}", instruction_data.op_code),
+            _ => {
+                if self.unimplemented.insert(instruction_data.op_code) {
+                    log::warn!("Instruction not implemented: {:04X}", instruction_data.op_code);
+                }
+            }
         }
         Ok(())
     }
@@ -377,35 +1227,615 @@ impl Emulator {
     pub fn beep(&mut self) {
         if self.sound_timer > 0 {
             self.sound_timer -= 1;
-        } else {
-            stop_sound(&self.sound);
+            if self.sound_timer == 0 {
+                self.emit(EmulatorEvent::SoundStopped);
+            }
+        }
+        self.update_buzzer();
+    }
+
+    /// Switches the buzzer on or off to follow the sound timer.
+    fn update_buzzer(&mut self) {
+        let on = self.sound_timer > 0;
+        if on == self.buzzing {
+            return;
+        }
+        self.buzzing = on;
+        if let Some(sound) = self.sound.as_mut() {
+            if on {
+                sound.start(self.volume);
+            } else {
+                sound.stop();
+            }
         }
     }
+
     pub fn tick_delay(&mut self) {
         if self.delay_timer > 0 {
             self.delay_timer -= 1;
+            if self.delay_timer == 0 {
+                self.emit(EmulatorEvent::DelayExpired);
+            }
+        }
+        if let Some(batched) = self.batched.as_mut() {
+            if self.framebuffer.take_dirty() {
+                batched.clone_from(&self.framebuffer);
+            }
         }
     }
-    pub async fn render(&self) {
+
+    /// Presents the display, redrawing and composing it again only when it or the way it's shown changed. Static
+    /// screens cost a single texture draw per frame.
+    pub fn render(&mut self) {
+        let dirty = match self.batched.as_mut() {
+            Some(batched) => batched.take_dirty(),
+            None => self.framebuffer.take_dirty(),
+        };
+        if dirty {
+            self.redraw(self.displayed());
+        }
+        let Some(video) = self.video.as_mut() else {
+            return;
+        };
+        let resized = video.fit_screen();
+        if dirty || resized || std::mem::take(&mut self.recompose) {
+            self.compose();
+        }
+
+        let Some(video) = &self.video else {
+            return;
+        };
         set_default_camera();
-        gl_use_material(&self.crt_material);
         draw_texture_ex(
-            &self.render_target.texture,
+            &video.screen.texture,
             0.,
             0.,
-            macroquad::color::WHITE,
+            color::WHITE,
             DrawTextureParams {
                 dest_size: Some(vec2(screen_width(), screen_height())),
                 flip_y: true,
                 ..Default::default()
             },
         );
+    }
+
+    /// Draws the bezel, the scaled display and the pixel grid into the screen target.
+    fn compose(&self) {
+        let Some(video) = &self.video else {
+            return;
+        };
+
+        set_camera(&video.screen_camera);
+        clear_background(color::BLACK);
+        if let Some(bezel) = &self.bezel {
+            draw_texture_ex(
+                &bezel.texture,
+                0.,
+                0.,
+                color::WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let area = self.display_rect();
+        let (width, height) = if self.rotation.swaps_axes() {
+            (area.h, area.w)
+        } else {
+            (area.w, area.h)
+        };
+        video.crt_material.set_uniform(
+            "SharpBilinear",
+            if self.scale_filter == ScaleFilter::SharpBilinear {
+                1f32
+            } else {
+                0f32
+            },
+        );
+        video
+            .crt_material
+            .set_uniform("TextureSize", video.render_target.texture.size());
+        // The shader works in framebuffer pixels, which outnumber logical ones on scaled displays
+        video
+            .crt_material
+            .set_uniform("OutputSize", vec2(width, height) * screen_dpi_scale());
+        gl_use_material(&video.crt_material);
+        draw_texture_ex(
+            &video.render_target.texture,
+            area.center().x - width / 2.,
+            area.center().y - height / 2.,
+            color::WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                rotation: self.rotation.radians(),
+                flip_y: true,
+                ..Default::default()
+            },
+        );
         gl_use_default_material();
 
-        next_frame().await
+        if self.show_pixel_grid {
+            let (columns, rows) = self.display_cells();
+            overlay::draw_pixel_grid(area, columns, rows);
+        }
+    }
+
+    fn display_cells(&self) -> (i32, i32) {
+        if self.rotation.swaps_axes() {
+            (self.window_size.1, self.window_size.0)
+        } else {
+            self.window_size
+        }
+    }
+
+    /// Where the display cells `x..x + width` and `y..y + height` show on screen, following the rotation.
+    pub(crate) fn cells_on_screen(&self, x: usize, y: usize, width: usize, height: usize) -> Rect {
+        let area = self.display_rect();
+        let (columns, rows) = (self.window_size.0 as f32, self.window_size.1 as f32);
+        let (x, y, width, height) = (x as f32, y as f32, width as f32, height as f32);
+        let (x, y, width, height) = match self.rotation {
+            Rotation::None => (x, y, width, height),
+            Rotation::Clockwise90 => (rows - y - height, x, height, width),
+            Rotation::Clockwise180 => (columns - x - width, rows - y - height, width, height),
+            Rotation::Clockwise270 => (y, columns - x - width, height, width),
+        };
+        let (columns, rows) = self.display_cells();
+        let (cell_width, cell_height) = (area.w / columns as f32, area.h / rows as f32);
+        Rect::new(
+            area.x + x * cell_width,
+            area.y + y * cell_height,
+            width * cell_width,
+            height * cell_height,
+        )
     }
 
+    /// The largest screen area with the display's aspect ratio that fits inside the bezel margin, in logical
+    /// coordinates but with its edges on whole framebuffer pixels so scaled displays stay sharp.
+    fn display_rect(&self) -> Rect {
+        let dpi = screen_dpi_scale();
+        let snap = |value: f32| (value * dpi).round() / dpi;
+        let margin = self.bezel.as_ref().map(|bezel| bezel.margin).unwrap_or(0.);
+        let available_width = screen_width() * (1. - 2. * margin);
+        let available_height = screen_height() * (1. - 2. * margin);
+        let (width, height) = (self.window_size.0 as f32 * self.pixel_aspect, self.window_size.1 as f32);
+        let (width, height) = if self.rotation.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let scale = (available_width / width).min(available_height / height);
+        let (width, height) = (snap(width * scale), snap(height * scale));
+
+        Rect::new(
+            snap((screen_width() - width) / 2.),
+            snap((screen_height() - height) / 2.),
+            width,
+            height,
+        )
+    }
+
+    pub fn set_scale_filter(&mut self, filter: ScaleFilter) {
+        self.scale_filter = filter;
+        self.recompose = true;
+        if let Some(video) = &self.video {
+            video.render_target.texture.set_filter(match filter {
+                ScaleFilter::Nearest => FilterMode::Nearest,
+                ScaleFilter::Linear | ScaleFilter::SharpBilinear => FilterMode::Linear,
+            });
+        }
+    }
+
+    /// Width of a logical pixel relative to its height.
+    pub fn set_pixel_aspect(&mut self, ratio: f32) {
+        if ratio.is_finite() && ratio > 0. {
+            self.pixel_aspect = ratio;
+            self.recompose = true;
+        }
+    }
+
+    pub fn set_bezel(&mut self, texture: Texture2D, margin: f32) {
+        self.bezel = Some(Bezel {
+            texture,
+            margin: margin.clamp(0., 0.45),
+        });
+        self.recompose = true;
+    }
+
+    pub fn toggle_pixel_grid(&mut self) {
+        self.show_pixel_grid = !self.show_pixel_grid;
+        self.recompose = true;
+    }
+
+    /// Screen pixels per CHIP-8 pixel in saved screenshots, the display itself scales to fit the window.
+    pub fn pixel_size(&self) -> i32 {
+        self.pixel_size
+    }
+
+    /// Saves the display scaled up to the pixel size the emulator was started with.
     pub fn export_render_target(&self, path: &str) {
-        self.render_target.texture.get_texture_data().export_png(path);
+        if let Err(error) = self.save_screenshot(path, &ScreenshotOptions::default()) {
+            log::warn!("Can't export {}: {}", path, error);
+        }
+    }
+
+    /// Saves the display at native or scaled size, with the ROM name and frame as PNG text chunks when given.
+    #[cfg(feature = "image")]
+    pub fn save_screenshot(&self, path: &str, options: &ScreenshotOptions) -> Result<(), ScreenshotError> {
+        crate::screenshot::save_png(&self.screenshot_image(options.size), &options.metadata(), path)
+    }
+
+    #[cfg(not(feature = "image"))]
+    pub fn save_screenshot(&self, _path: &str, _options: &ScreenshotOptions) -> Result<(), ScreenshotError> {
+        Err(ScreenshotError::Unsupported)
+    }
+
+    #[cfg(feature = "image")]
+    pub fn screenshot_image(&self, size: crate::screenshot::ScreenshotSize) -> image::RgbaImage {
+        use crate::screenshot::ScreenshotSize;
+
+        match size {
+            ScreenshotSize::Native => self.framebuffer_image(1),
+            ScreenshotSize::Scaled => self.framebuffer_image(self.pixel_size.max(1) as u32),
+        }
+    }
+
+    /// The display in the current palette with every pixel scaled up to `pixel_size`, drawn without a GPU.
+    #[cfg(feature = "image")]
+    pub fn framebuffer_image(&self, pixel_size: u32) -> image::RgbaImage {
+        let foreground = image::Rgba(self.palette.foreground().into());
+        let background = image::Rgba(self.palette.background().into());
+        let (width, height) = (self.framebuffer.width() as u32, self.framebuffer.height() as u32);
+        image::RgbaImage::from_fn(width * pixel_size, height * pixel_size, |x, y| {
+            if self
+                .framebuffer
+                .get((x / pixel_size) as usize, (y / pixel_size) as usize)
+            {
+                foreground
+            } else {
+                background
+            }
+        })
+    }
+
+    /// Display RAM sits at the end of memory, like on the VIP where the hires page starts at 0xE00.
+    fn display_start(&self) -> usize {
+        constants::TOTAL_RAM - self.framebuffer.packed_len()
+    }
+
+    pub fn savestate(&self) -> Savestate {
+        let mut memory = *self.memory.data();
+        self.framebuffer.pack(&mut memory[self.display_start()..]);
+        Savestate {
+            memory,
+            pc: *self.pc.inner() as u16,
+            index_register: self.index_register,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+            registers: self.register.values(),
+            stack: self.stack.as_slice().to_vec(),
+            rom_hash: Some(self.rom.hash()),
+        }
+    }
+
+    /// Hash of the loaded program, see [`Savestate::check_rom`].
+    pub fn rom_hash(&self) -> u64 {
+        self.rom.hash()
+    }
+
+    pub fn load_state(&mut self, state: &Savestate) {
+        self.memory.restore(&state.memory);
+        self.pc.jump(state.pc);
+        self.index_register = state.index_register;
+        self.delay_timer = state.delay_timer;
+        self.sound_timer = state.sound_timer;
+        self.register.restore(&state.registers);
+        self.stack = AddressStack::from(state.stack.clone());
+        self.update_buzzer();
+        self.framebuffer.unpack(&state.memory[self.display_start()..]);
+    }
+
+    pub fn set_font(&mut self, font: &Font) {
+        self.memory.load_font(font.data());
+        self.font = font.clone();
+    }
+
+    /// Restarts the loaded program from a clean machine state, keeping display and input settings.
+    pub fn reset(&mut self) {
+        self.memory = Ram::load(&self.rom, self.interpreter.load_address(), self.font.data());
+        self.pc = ProgramCounter(self.interpreter.load_address());
+        self.stack = AddressStack::default();
+        self.register = Register::new();
+        self.index_register = 0;
+        self.delay_timer = 0;
+        self.sound_timer = 0;
+        self.history = History::new(constants::HISTORY_LENGTH);
+        self.execution_counts.fill(0);
+        self.exited = false;
+        self.silence();
+        self.framebuffer.clear();
+    }
+
+    pub(crate) fn load_rom(&mut self, rom: Rom) {
+        self.rom = rom;
+        self.unimplemented.clear();
+        self.apply_interpreter();
+        self.reset();
+    }
+
+    pub(crate) fn silence(&mut self) {
+        if let (true, Some(sound)) = (self.buzzing, self.sound.as_mut()) {
+            sound.stop();
+        }
+        self.buzzing = false;
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation, rotate_input: bool) {
+        self.rotation = rotation;
+        self.rotate_input = rotate_input;
+        self.recompose = true;
+        self.rebuild_keypad();
+    }
+
+    /// Physical keys for the hex keypad, laid out like the original 4x4 pad (1 2 3 C on the top row).
+    pub fn set_key_layout(&mut self, layout: [[KeyCode; 4]; 4]) {
+        self.key_layout = layout;
+        self.rebuild_keypad();
+    }
+
+    /// Extra physical keys that press the same hex keys as the main layout, e.g. the numpad for a second player.
+    pub fn set_second_key_layout(&mut self, layout: Option<[[KeyCode; 4]; 4]>) {
+        self.second_key_layout = layout;
+        self.rebuild_keypad();
+    }
+
+    /// Replaces the built-in beep, the new buzzer takes over whatever state the sound timer is in.
+    pub fn set_buzzer(&mut self, buzzer: Box<dyn Buzzer>) {
+        self.silence();
+        self.sound = Some(buzzer);
+        self.update_buzzer();
+    }
+
+    /// Replaces the keyboard with a caller-supplied keypad, key layout and input rotation no longer apply.
+    pub fn set_keypad(&mut self, keypad: KeyPad) {
+        self.keypad = keypad;
+        self.custom_keypad = true;
+    }
+
+    pub fn set_input_polling(&mut self, polling: InputPolling) {
+        self.input_polling = polling;
+    }
+
+    pub fn input_polling(&self) -> InputPolling {
+        self.input_polling
+    }
+
+    fn rebuild_keypad(&mut self) {
+        if self.custom_keypad || self.video.is_none() {
+            return;
+        }
+        let rotation = if self.rotate_input {
+            self.rotation
+        } else {
+            Rotation::None
+        };
+        let layouts: Vec<_> = std::iter::once(self.key_layout).chain(self.second_key_layout).collect();
+        self.keypad = KeyPad::rotated(&layouts, rotation);
+    }
+
+    /// Selects the interpreter for ROMs that don't require a specific one, see [`Interpreter::detect`]. Switching to
+    /// one with a different load address restarts the program.
+    pub fn set_interpreter(&mut self, interpreter: Interpreter) {
+        self.selected_interpreter = interpreter;
+        self.apply_interpreter();
+    }
+
+    pub fn interpreter(&self) -> Interpreter {
+        self.interpreter
+    }
+
+    fn apply_interpreter(&mut self) {
+        let previous = std::mem::replace(
+            &mut self.interpreter,
+            self.rom.interpreter().unwrap_or(self.selected_interpreter),
+        );
+        if previous.load_address() != self.interpreter.load_address() {
+            self.reset();
+        }
+
+        let resolution = self.interpreter.resolution();
+        if resolution == self.window_size {
+            return;
+        }
+        self.window_size = resolution;
+        self.framebuffer = Framebuffer::new(resolution.0 as usize, resolution.1 as usize);
+        if let Some(video) = self.video.as_mut() {
+            video.resize(resolution);
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.framebuffer.mark_dirty();
+        if let Some(batched) = self.batched.as_mut() {
+            batched.mark_dirty();
+        }
+    }
+
+    /// Plays a short click whenever a keypad key goes down, to confirm input reaches the machine.
+    pub fn set_key_click(&mut self, enabled: bool) {
+        self.key_click = enabled;
+    }
+
+    /// Replaces the built-in key click. It is pulsed, started and right away stopped, once per press while key
+    /// click is on.
+    pub fn set_click(&mut self, click: Box<dyn Buzzer>) {
+        self.click = Some(click);
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+        if let Some(sound) = self.sound.as_mut() {
+            sound.set_volume(self.volume);
+        }
+    }
+
+    /// Keys currently seen as pressed, indexed by hex value.
+    pub fn pressed_keys(&self) -> [bool; 16] {
+        std::array::from_fn(|hex| self.keypad.is_key_pressed(hex as u8))
+    }
+
+    /// Keys that went down since the previous frame, indexed by hex value.
+    pub fn just_pressed_keys(&self) -> [bool; 16] {
+        self.just_pressed
+    }
+
+    pub fn inject_key(&mut self, hex: u8, pressed: bool) {
+        self.keypad.inject(hex, pressed);
+    }
+
+    /// Polls the keypad, call once per frame before stepping when driving the emulator yourself.
+    pub fn begin_frame(&mut self) {
+        self.keypad.poll();
+        self.frame_instructions = 0;
+
+        let pressed = self.pressed_keys();
+        self.just_pressed = std::array::from_fn(|hex| pressed[hex] && !self.last_pressed[hex]);
+        self.last_pressed = pressed;
+        let newly_pressed = self.just_pressed.contains(&true);
+        if let (true, true, Some(click)) = (self.key_click, newly_pressed, self.click.as_mut()) {
+            click.start(self.volume);
+            click.stop();
+        }
+    }
+
+    /// Forgets key releases no instruction consumed this frame.
+    pub fn end_frame(&mut self) {
+        self.keypad.end_frame();
+    }
+
+    fn redraw(&self, framebuffer: &Framebuffer) {
+        let Some(video) = &self.video else {
+            return;
+        };
+        set_camera(&video.camera);
+        clear_background(self.palette.background());
+        let width = framebuffer.width();
+        for (idx, _) in framebuffer.pixels().iter().enumerate().filter(|(_, lit)| **lit) {
+            draw_rectangle(
+                (idx % width) as f32,
+                (idx / width) as f32,
+                1.,
+                1.,
+                self.palette.foreground(),
+            );
+        }
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// What the screen shows, the display as of the last timer tick while draw batching is on.
+    pub fn displayed(&self) -> &Framebuffer {
+        self.batched.as_ref().unwrap_or(&self.framebuffer)
+    }
+
+    /// Holds back the display's changes until the next delay timer tick and shows them all at once, so sprites a
+    /// game erases and redraws within a frame don't flicker. Off by default, real hardware shows every draw.
+    pub fn set_draw_batching(&mut self, enabled: bool) {
+        if enabled == self.batched.is_some() {
+            return;
+        }
+        self.framebuffer.mark_dirty();
+        self.batched = enabled.then(|| self.framebuffer.clone());
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    pub fn draw_count(&self) -> u64 {
+        self.draw_count
+    }
+
+    /// Op codes the program ran that the emulator doesn't implement and skipped.
+    pub fn unimplemented_opcodes(&self) -> &BTreeSet<u16> {
+        &self.unimplemented
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    pub fn registers(&self) -> [u8; 16] {
+        self.register.values()
+    }
+
+    pub fn index_register(&self) -> u16 {
+        self.index_register
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    /// Sets the delay timer as if FX15 had run, reporting [`EmulatorEvent::DelayExpired`] when it drops to zero.
+    pub fn set_delay_timer(&mut self, value: u8) {
+        let expired = self.delay_timer > 0 && value == 0;
+        self.delay_timer = value;
+        if expired {
+            self.emit(EmulatorEvent::DelayExpired);
+        }
+    }
+
+    /// Sets the sound timer as if FX18 had run, switching the buzzer and reporting the sound starting or stopping.
+    pub fn set_sound_timer(&mut self, value: u8) {
+        let was_on = self.sound_timer > 0;
+        self.sound_timer = value;
+        match (was_on, value > 0) {
+            (false, true) => self.emit(EmulatorEvent::SoundStarted),
+            (true, false) => self.emit(EmulatorEvent::SoundStopped),
+            _ => {}
+        }
+        self.update_buzzer();
+    }
+
+    /// Return addresses of the active subroutine calls, innermost last.
+    pub fn stack(&self) -> &[u16] {
+        self.stack.as_slice()
+    }
+
+    /// Addresses of the 2NNN calls that entered each active subroutine, innermost last, for a backtrace.
+    pub fn call_sites(&self) -> Vec<u16> {
+        self.stack().iter().map(|address| address.wrapping_sub(2)).collect()
+    }
+
+    /// The whole address space, including the font and display RAM.
+    pub fn memory(&self) -> &[u8] {
+        self.memory.data()
+    }
+
+    /// Overwrites RAM from `address`, e.g. to restore a [`PersistRange`](crate::persist::PersistRange).
+    pub fn write_memory(&mut self, address: usize, bytes: &[u8]) {
+        self.memory.write(address, bytes);
+    }
+
+    pub fn pc(&self) -> usize {
+        *self.pc.inner()
+    }
+
+    /// Times each address was executed since the program was last reset, indexed by address.
+    pub fn execution_counts(&self) -> &[u32] {
+        &self.execution_counts
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
     }
 }
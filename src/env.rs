@@ -0,0 +1,107 @@
+use crate::{
+    constants,
+    emulator::Emulator,
+};
+
+/// The framebuffer as one byte per pixel, row-major, 1 for lit and 0 for dark.
+pub type Observation = Vec<u8>;
+
+/// Gym-style wrapper that runs a ROM headless and deterministically, one display frame per step.
+pub struct Env {
+    rom: Vec<u8>,
+    emulator: Emulator,
+    seed: u64,
+    instructions_per_step: u32,
+    max_steps: Option<u64>,
+    steps: u64,
+}
+
+impl Env {
+    pub fn new(rom: &[u8]) -> Result<Self, anyhow::Error> {
+        let mut emulator = Emulator::headless(rom)?;
+        emulator.seed_rng(0);
+
+        Ok(Self {
+            rom: rom.to_vec(),
+            emulator,
+            seed: 0,
+            instructions_per_step: (constants::INSTRUCTIONS_PER_SECOND / 60) as u32,
+            max_steps: None,
+            steps: 0,
+        })
+    }
+
+    /// Seed for CXNN, the same seed and inputs always produce the same episode.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.emulator.seed_rng(seed);
+        self
+    }
+
+    /// Instructions executed per step, one 60 Hz frame worth at the default speed.
+    pub fn with_instructions_per_step(mut self, instructions: u32) -> Self {
+        self.instructions_per_step = instructions;
+        self
+    }
+
+    /// Ends episodes after this many steps, for games that never exit on their own.
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Starts a new episode from power-on.
+    pub fn reset(&mut self) -> Observation {
+        self.emulator = Emulator::headless(&self.rom).expect("ROM was loaded once already");
+        self.emulator.seed_rng(self.seed);
+        self.steps = 0;
+        self.observation()
+    }
+
+    /// Holds `keys` (indexed by hex value) for one frame, then ticks the timers. Returns the resulting display and
+    /// whether the episode is over, because the program exited, crashed or ran out of steps.
+    pub fn step(&mut self, keys: [bool; 16]) -> (Observation, bool) {
+        for (hex, pressed) in keys.into_iter().enumerate() {
+            self.emulator.inject_key(hex as u8, pressed);
+        }
+
+        let mut done = false;
+        for _ in 0..self.instructions_per_step {
+            if let Err(error) = self.emulator.step() {
+                log::warn!("Episode ended by an emulation error: {}", error);
+                done = true;
+                break;
+            }
+            if self.emulator.has_exited() {
+                done = true;
+                break;
+            }
+        }
+        self.emulator.tick_delay();
+        self.emulator.beep();
+        self.emulator.end_frame();
+
+        self.steps += 1;
+        done |= self.max_steps.is_some_and(|max| self.steps >= max);
+        (self.observation(), done)
+    }
+
+    /// Width and height of every [`Observation`].
+    pub fn observation_shape(&self) -> (usize, usize) {
+        let framebuffer = self.emulator.framebuffer();
+        (framebuffer.width(), framebuffer.height())
+    }
+
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    fn observation(&self) -> Observation {
+        self.emulator
+            .framebuffer()
+            .pixels()
+            .iter()
+            .map(|lit| *lit as u8)
+            .collect()
+    }
+}
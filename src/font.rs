@@ -0,0 +1,169 @@
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use thiserror::Error;
+
+pub const FONT_SIZE: usize = 80;
+pub const FONT_ADDRESS: u16 = 0x000;
+pub const GLYPH_HEIGHT: u16 = 5;
+
+#[rustfmt::skip]
+const OCTO_FONT: [u8; FONT_SIZE] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80  // F
+];
+
+#[rustfmt::skip]
+const VIP_FONT: [u8; FONT_SIZE] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x60, 0x20, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0xA0, 0xA0, 0xF0, 0x20, 0x20, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x10, 0x10, 0x10, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xF0, 0x50, 0x70, 0x50, 0xF0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xF0, 0x50, 0x50, 0x50, 0xF0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80  // F
+];
+
+#[rustfmt::skip]
+const ETI_660_FONT: [u8; FONT_SIZE] = [
+    0xE0, 0xA0, 0xA0, 0xA0, 0xE0, // 0
+    0x20, 0x20, 0x20, 0x20, 0x20, // 1
+    0xE0, 0x20, 0xE0, 0x80, 0xE0, // 2
+    0xE0, 0x20, 0xE0, 0x20, 0xE0, // 3
+    0xA0, 0xA0, 0xE0, 0x20, 0x20, // 4
+    0xE0, 0x80, 0xE0, 0x20, 0xE0, // 5
+    0xE0, 0x80, 0xE0, 0xA0, 0xE0, // 6
+    0xE0, 0x20, 0x20, 0x20, 0x20, // 7
+    0xE0, 0xA0, 0xE0, 0xA0, 0xE0, // 8
+    0xE0, 0xA0, 0xE0, 0x20, 0xE0, // 9
+
+    0xE0, 0xA0, 0xE0, 0xA0, 0xA0, // A
+    0x80, 0x80, 0xE0, 0xA0, 0xE0, // B
+    0xE0, 0x80, 0x80, 0x80, 0xE0, // C
+    0x20, 0x20, 0xE0, 0xA0, 0xE0, // D
+    0xE0, 0x80, 0xE0, 0x80, 0xE0, // E
+    0xE0, 0x80, 0xC0, 0x80, 0x80  // F
+];
+
+#[rustfmt::skip]
+const DREAM_6800_FONT: [u8; FONT_SIZE] = [
+    0xE0, 0xA0, 0xA0, 0xA0, 0xE0, // 0
+    0x40, 0x40, 0x40, 0x40, 0x40, // 1
+    0xE0, 0x20, 0xE0, 0x80, 0xE0, // 2
+    0xE0, 0x20, 0xE0, 0x20, 0xE0, // 3
+    0x80, 0xA0, 0xA0, 0xE0, 0x20, // 4
+    0xE0, 0x80, 0xE0, 0x20, 0xE0, // 5
+    0xE0, 0x80, 0xE0, 0xA0, 0xE0, // 6
+    0xE0, 0x20, 0x20, 0x20, 0x20, // 7
+    0xE0, 0xA0, 0xE0, 0xA0, 0xE0, // 8
+    0xE0, 0xA0, 0xE0, 0x20, 0xE0, // 9
+
+    0xE0, 0xA0, 0xE0, 0xA0, 0xA0, // A
+    0xC0, 0xA0, 0xE0, 0xA0, 0xC0, // B
+    0xE0, 0x80, 0x80, 0x80, 0xE0, // C
+    0xC0, 0xA0, 0xA0, 0xA0, 0xC0, // D
+    0xE0, 0x80, 0xE0, 0x80, 0xE0, // E
+    0xE0, 0x80, 0xC0, 0x80, 0x80  // F
+];
+
+pub fn glyph_address(digit: u8) -> u16 {
+    FONT_ADDRESS + (digit & 0xF) as u16 * GLYPH_HEIGHT
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FontSet {
+    #[default]
+    Octo,
+    Vip,
+    Eti660,
+    Dream6800,
+}
+
+impl FontSet {
+    pub const NAMES: [&'static str; 4] = ["octo", "vip", "eti-660", "dream-6800"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "octo" => Some(FontSet::Octo),
+            "vip" => Some(FontSet::Vip),
+            "eti-660" | "eti660" => Some(FontSet::Eti660),
+            "dream-6800" | "dream6800" => Some(FontSet::Dream6800),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("loading font failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("font must be {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font([u8; FONT_SIZE]);
+
+impl Font {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FontError> {
+        let data = bytes.try_into().map_err(|_| FontError::InvalidLength {
+            expected: FONT_SIZE,
+            actual: bytes.len(),
+        })?;
+        Ok(Self(data))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FontError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn data(&self) -> &[u8; FONT_SIZE] {
+        &self.0
+    }
+}
+
+impl From<FontSet> for Font {
+    fn from(value: FontSet) -> Self {
+        match value {
+            FontSet::Octo => Self(OCTO_FONT),
+            FontSet::Vip => Self(VIP_FONT),
+            FontSet::Eti660 => Self(ETI_660_FONT),
+            FontSet::Dream6800 => Self(DREAM_6800_FONT),
+        }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        FontSet::default().into()
+    }
+}
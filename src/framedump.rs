@@ -0,0 +1,49 @@
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    display::Framebuffer,
+    emulator::Emulator,
+    runner::FrameInfo,
+};
+
+/// Saves every Nth frame's display to a directory as `frame_00000042.pbm`, to find the first frame where two builds
+/// diverge.
+pub struct FrameDump {
+    dir: PathBuf,
+    every: u64,
+}
+
+impl FrameDump {
+    pub fn create<P: AsRef<Path>>(dir: P, every: u64) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_owned(),
+            every: every.max(1),
+        })
+    }
+
+    pub(crate) fn record(&mut self, frame: &FrameInfo, emulator: &Emulator) -> io::Result<()> {
+        if frame.frame % self.every != 0 {
+            return Ok(());
+        }
+        fs::write(
+            self.dir.join(format!("frame_{:08}.pbm", frame.frame)),
+            encode(emulator.framebuffer()),
+        )
+    }
+}
+
+/// Binary PBM, a size header followed by [`Framebuffer::to_bits`]. Small enough to keep thousands of frames and
+/// readable by most image tools.
+pub fn encode(framebuffer: &Framebuffer) -> Vec<u8> {
+    let mut bytes = format!("P4\n{} {}\n", framebuffer.width(), framebuffer.height()).into_bytes();
+    bytes.extend(framebuffer.to_bits());
+    bytes
+}
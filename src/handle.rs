@@ -0,0 +1,65 @@
+use std::{
+    path::PathBuf,
+    sync::mpsc::{
+        self,
+        Receiver,
+        Sender,
+    },
+};
+
+use thiserror::Error;
+
+use crate::state::Savestate;
+
+#[derive(Debug)]
+pub enum Command {
+    Pause,
+    Resume,
+    Stop,
+    Step,
+    StepOver,
+    RunToReturn,
+    StepBack,
+    SetSpeed(f64),
+    SetBreakpoint(usize),
+    ClearBreakpoint(usize),
+    SaveState,
+    LoadState(Box<Savestate>),
+    InjectKey { key: u8, pressed: bool },
+    Screenshot(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum Response {
+    Ok,
+    State(Box<Savestate>),
+}
+
+#[derive(Error, Debug)]
+pub enum HandleError {
+    #[error("runner is no longer running")]
+    Disconnected,
+}
+
+pub(crate) type CommandReceiver = Receiver<(Command, Sender<Response>)>;
+
+#[derive(Clone)]
+pub struct RunnerHandle {
+    commands: Sender<(Command, Sender<Response>)>,
+}
+
+impl RunnerHandle {
+    pub(crate) fn new() -> (Self, CommandReceiver) {
+        let (commands, receiver) = mpsc::channel();
+        (Self { commands }, receiver)
+    }
+
+    /// Blocks until the runner has processed the command, which happens once per frame.
+    pub fn send(&self, command: Command) -> Result<Response, HandleError> {
+        let (reply, response) = mpsc::channel();
+        self.commands
+            .send((command, reply))
+            .map_err(|_| HandleError::Disconnected)?;
+        response.recv().map_err(|_| HandleError::Disconnected)
+    }
+}
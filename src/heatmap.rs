@@ -0,0 +1,36 @@
+//! How often each address was executed, drawn as a strip while running (F7) or saved as an image.
+
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+/// Addresses per row of the exported image, so a row covers 64 bytes of RAM.
+pub const ROW_LENGTH: usize = 64;
+
+/// Brightness of an address executed `count` times, on a log scale so hot loops don't wash out everything else.
+pub fn intensity(count: u32, max: u32) -> f32 {
+    if count == 0 || max == 0 {
+        return 0.0;
+    }
+    ((count as f32).ln_1p() / (max as f32).ln_1p()).clamp(0.0, 1.0)
+}
+
+/// Binary PGM with one pixel per address, [`ROW_LENGTH`] addresses per row starting at 0x000.
+pub fn encode(counts: &[u32]) -> Vec<u8> {
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let rows = counts.len().div_ceil(ROW_LENGTH);
+    let mut bytes = format!("P5\n{} {}\n255\n", ROW_LENGTH, rows).into_bytes();
+    bytes.extend(
+        counts
+            .iter()
+            .map(|count| (intensity(*count, max) * 255.0).round() as u8),
+    );
+    bytes.resize(bytes.len() + rows * ROW_LENGTH - counts.len(), 0);
+    bytes
+}
+
+pub fn save<P: AsRef<Path>>(path: P, counts: &[u32]) -> io::Result<()> {
+    fs::write(path, encode(counts))
+}
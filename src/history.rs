@@ -0,0 +1,96 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+};
+
+use crate::{
+    disassembler,
+    symbols::SymbolTable,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryEntry {
+    pub pc: usize,
+    pub op_code: u16,
+}
+
+impl HistoryEntry {
+    pub fn mnemonic(&self) -> String {
+        disassembler::mnemonic(self.op_code)
+    }
+
+    /// Like the `Display` output, with the pc placed relative to its label and operands named from `symbols`.
+    pub fn describe(&self, symbols: &SymbolTable) -> String {
+        format!(
+            "0x{:03X} {:<16} {:04X}  {}",
+            self.pc,
+            symbols.locate(self.pc as u16).unwrap_or_default(),
+            self.op_code,
+            disassembler::mnemonic_with_symbols(self.op_code, symbols)
+        )
+    }
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:03X}: {:04X}  {}", self.pc, self.op_code, self.mnemonic())
+    }
+}
+
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn snapshot(&self) -> HistorySnapshot {
+        HistorySnapshot(self.entries.iter().copied().collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistorySnapshot(Vec<HistoryEntry>);
+
+impl HistorySnapshot {
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.0
+    }
+}
+
+impl fmt::Display for HistorySnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.0 {
+            writeln!(f, "  {}", entry)?;
+        }
+        Ok(())
+    }
+}
@@ -1,36 +1,371 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::Mutex,
+};
+
+use chip8rs::{
+    audiodump::AudioDump,
+    config::Config,
+    demo::Demo,
+    emulator::{
+        RomWarning,
+        Rotation,
+        ScaleFilter,
+    },
+    font::{
+        Font,
+        FontSet,
+    },
+    framedump::FrameDump,
+    movie::InputMovie,
+    script::InputScript,
+    watch::Watch,
+    Pacing,
+};
+use clap::Parser;
+use cli::Failure;
 use macroquad::{
     miniquad,
     window::Conf,
+    Window,
 };
 
+mod cli;
+
 const SCREEN_WIDTH: i32 = 64;
 const SCREEN_HEIGHT: i32 = 32;
 
 const PIXEL_SIZE: i32 = 10;
 
-fn window_conf() -> Conf {
+#[cfg(feature = "remote")]
+const REMOTE_ADDRESS: &str = "127.0.0.1:8642";
+
+#[derive(Parser, Debug)]
+#[command(version, about = "A CHIP-8 emulator")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+
+    /// Path to the ROM to run, starts on a splash screen if omitted
+    rom: Option<String>,
+
+    /// Run a ROM compiled into the client instead: ibm-logo, maze or puzzle
+    #[arg(long, value_parser = parse_demo, conflicts_with = "rom")]
+    demo: Option<Demo>,
+
+    /// Refuse to run a ROM that starts with text or jumps below the program instead of only warning. An odd length
+    /// alone is allowed, as programs may end in sprite data
+    #[arg(long)]
+    strict: bool,
+
+    /// Clockwise display rotation in degrees: 0, 90, 180 or 270
+    #[arg(long, default_value = "0", value_parser = parse_rotation)]
+    rotation: Rotation,
+
+    /// Rotate the keypad layout together with the display
+    #[arg(long)]
+    rotate_input: bool,
+
+    /// Filter used when scaling the display: nearest, linear or sharp-bilinear
+    #[arg(long, default_value = "nearest", value_parser = parse_scale_filter)]
+    filter: ScaleFilter,
+
+    /// Width of a logical pixel relative to its height, to mimic non-square pixels of original hardware
+    #[arg(long, default_value_t = 1.0, value_parser = parse_pixel_aspect)]
+    pixel_aspect: f32,
+
+    /// 80-byte file with the 4x5 hex digit sprites, replacing the built-in font
+    #[arg(long, conflicts_with = "font_set")]
+    font: Option<String>,
+
+    /// Built-in font: octo, vip, eti-660 or dream-6800
+    #[arg(long, value_parser = parse_font_set)]
+    font_set: Option<FontSet>,
+
+    /// Settings file, written back when settings change in the pause menu
+    #[arg(long, default_value = "chip8rs.cfg")]
+    config: String,
+
+    /// Don't print anything to the console
+    #[arg(long, global = true)]
+    quiet: bool,
+
+    /// Write diagnostics to this file instead of the console
+    #[arg(long, global = true)]
+    log_file: Option<String>,
+
+    /// Image drawn around the display, e.g. an arcade bezel
+    #[arg(long)]
+    bezel: Option<String>,
+
+    /// Sound file played as the beep instead of `assets/beep.wav`
+    #[arg(long, value_name = "FILE")]
+    beep: Option<String>,
+
+    /// Fraction of the window on each side reserved for the bezel
+    #[arg(long, default_value_t = 0.1)]
+    bezel_margin: f32,
+
+    /// Value to show live in the corner: v0-vf, i, [address] or w[address] for a 16-bit word. Repeatable
+    #[arg(long = "watch", value_name = "EXPR")]
+    watches: Vec<Watch>,
+
+    /// Play back keypad input recorded with F6, from a reset
+    #[arg(long, value_name = "FILE")]
+    inputs: Option<String>,
+
+    /// Run timed keypad input like `at 120 press 5 for 10; at 300 tap A` from this file
+    #[arg(long, value_name = "FILE")]
+    script: Option<String>,
+
+    /// Run every instruction and timer tick due since the previous frame instead of at most one per frame, with vsync
+    /// limiting presentation to the display's refresh rate. Keeps the speed right when presenting a frame takes
+    /// longer than expected
+    #[arg(long)]
+    decoupled: bool,
+
+    /// Present one frame out of every N + 1 and only emulate the others, for machines that can't draw at 60fps
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "decoupled")]
+    frame_skip: u32,
+
+    /// Lock down everything that could differ between runs: seed CXNN with SEED, advance one timer tick of emulated
+    /// time per frame instead of following the clock, read the keypad once per frame and don't resume saved sessions
+    #[arg(long, value_name = "SEED", conflicts_with = "decoupled")]
+    deterministic: Option<u64>,
+
+    /// Start from this savestate, which must have been saved with the same ROM
+    #[arg(long, value_name = "FILE", requires = "rom")]
+    load_state: Option<String>,
+
+    /// Save the display of every `--dump-every`th frame to this directory as PBM bitmaps
+    #[arg(long, value_name = "DIR")]
+    dump_frames: Option<String>,
+
+    #[arg(long, default_value_t = 1, requires = "dump_frames")]
+    dump_every: u64,
+
+    /// Save when the buzzer played to this WAV file on exit, 1/60 s per timer tick. Always uses the synthesized
+    /// beep, even with --beep
+    #[arg(long, value_name = "FILE")]
+    dump_audio: Option<String>,
+
+    /// Save how often each address was executed to this file as a PGM image on exit, 64 addresses per row
+    #[arg(long, value_name = "FILE")]
+    heatmap: Option<String>,
+
+    /// Record per-frame puffin scopes and write them to this file on exit
+    #[cfg(feature = "profiling")]
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+fn parse_rotation(value: &str) -> Result<Rotation, String> {
+    value
+        .parse()
+        .ok()
+        .and_then(Rotation::from_degrees)
+        .ok_or_else(|| format!("`{}` is not one of 0, 90, 180 or 270", value))
+}
+
+fn parse_scale_filter(value: &str) -> Result<ScaleFilter, String> {
+    ScaleFilter::from_name(value).ok_or_else(|| format!("`{}` is not one of {}", value, ScaleFilter::NAMES.join(", ")))
+}
+
+fn parse_pixel_aspect(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(ratio) if ratio.is_finite() && ratio > 0.0 => Ok(ratio),
+        _ => Err(format!("`{}` is not a positive number", value)),
+    }
+}
+
+fn parse_font_set(value: &str) -> Result<FontSet, String> {
+    FontSet::from_name(value).ok_or_else(|| format!("`{}` is not one of {}", value, FontSet::NAMES.join(", ")))
+}
+
+fn parse_demo(value: &str) -> Result<Demo, String> {
+    Demo::from_name(value).ok_or_else(|| format!("`{}` is not one of {}", value, Demo::NAMES.join(", ")))
+}
+
+fn window_conf(cli: &Cli) -> Conf {
+    let display_width = (SCREEN_WIDTH * PIXEL_SIZE) as f32 * cli.pixel_aspect;
+    let display_height = (SCREEN_HEIGHT * PIXEL_SIZE) as f32;
+    let (width, height) = if cli.rotation.swaps_axes() {
+        (display_height, display_width)
+    } else {
+        (display_width, display_height)
+    };
+
     Conf {
         window_title: String::from("chip8.rs"),
         fullscreen: false,
         window_resizable: false,
-        window_width: SCREEN_WIDTH * PIXEL_SIZE,
-        window_height: SCREEN_HEIGHT * PIXEL_SIZE,
+        window_width: width.round() as i32,
+        window_height: height.round() as i32,
+        // Sizes stay logical while the framebuffer gets every physical pixel of a scaled display
+        high_dpi: true,
 
-        platform: miniquad::conf::Platform { ..Default::default() },
+        platform: miniquad::conf::Platform {
+            swap_interval: cli.decoupled.then_some(1),
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
-#[macroquad::main(window_conf)]
-async fn main() {
-    #[allow(unused_variables)]
-    let path = r"assets/roms/IBM Logo.ch8";
-    let path = r"assets/roms/test/1-chip8-logo.ch8";
-    //let path = r"roms/test_opcode.ch8";
-    //let path = r"roms/addition_problems.ch8";
-    //let path = r"roms/random_number.ch8";
-    //let path = r"roms/beep.ch8";
-    //let path = r"roms/astro_dodge.ch8";
-    if let Err(error) = chip8rs::run(path.into(), PIXEL_SIZE, (SCREEN_WIDTH, SCREEN_HEIGHT), &mut None).await {
-        println!("Chip8 emulator failed in an unexpected manner: {}", error)
+
+struct Logger {
+    file: Option<Mutex<File>>,
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
     }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match &self.file {
+            Some(file) => {
+                if let Ok(mut file) = file.lock() {
+                    let _ = writeln!(file, "[{}] {}", record.level(), record.args());
+                }
+            }
+            None => println!("[{}] {}", record.level(), record.args()),
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(Ok(mut file)) = self.file.as_ref().map(Mutex::lock) {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Routes library diagnostics to the log file, the console, or nowhere when running quietly.
+fn init_logging(cli: &Cli) -> Result<(), anyhow::Error> {
+    let file = cli.log_file.as_ref().map(File::create).transpose()?.map(Mutex::new);
+    if file.is_none() && cli.quiet {
+        return Ok(());
+    }
+    log::set_boxed_logger(Box::new(Logger { file }))?;
+    log::set_max_level(log::LevelFilter::Info);
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(error) = init_logging(&cli) {
+        eprintln!("Failed to open log file: {}", error);
+        return;
+    }
+
+    match &cli.command {
+        Some(command) => {
+            if let Err(error) = cli::run(command) {
+                log::logger().flush();
+                eprintln!("{}", error);
+                std::process::exit(cli::exit_code(&error));
+            }
+        }
+        None => Window::from_config(window_conf(&cli), async move {
+            if let Err(error) = run(&cli).await {
+                log::error!("Chip8 emulator failed in an unexpected manner: {}", error);
+                log::logger().flush();
+                std::process::exit(cli::exit_code(&error));
+            }
+        }),
+    }
+    log::logger().flush();
+}
+
+async fn run(cli: &Cli) -> Result<(), anyhow::Error> {
+    if let Some(rom) = &cli.rom {
+        let data = cli::read_rom(Path::new(rom))?;
+        let refused = RomWarning::check(&data)
+            .into_iter()
+            .find(|warning| !matches!(warning, RomWarning::OddLength { .. }));
+        if let (true, Some(warning)) = (cli.strict, refused) {
+            Err(Failure::RomInvalid(anyhow::anyhow!(
+                "refusing to run {}: {}",
+                rom,
+                warning
+            )))?;
+        }
+    }
+    let runner = match (&cli.rom, cli.demo) {
+        (Some(rom), _) => chip8rs::Runner::new(rom, PIXEL_SIZE, (SCREEN_WIDTH, SCREEN_HEIGHT))
+            .await
+            .map_err(|error| match error.is::<chip8rs::RomError>() {
+                true => Failure::RomInvalid(error).into(),
+                false => error,
+            })?,
+        (None, Some(demo)) => {
+            let name = format!("{}.ch8", demo.name());
+            chip8rs::Runner::from_bytes(&name, demo.rom(), PIXEL_SIZE, (SCREEN_WIDTH, SCREEN_HEIGHT)).await?
+        }
+        (None, None) => chip8rs::Runner::splash(PIXEL_SIZE, (SCREEN_WIDTH, SCREEN_HEIGHT)).await?,
+    };
+    let mut runner = runner
+        .with_crash_dir(".dev/crash")
+        .with_config(Config::load_or_default(&cli.config)?)
+        .with_config_file(&cli.config)
+        .with_rotation(cli.rotation, cli.rotate_input)
+        .with_pixel_aspect(cli.pixel_aspect)
+        .with_scale_filter(cli.filter)
+        .with_watches(cli.watches.clone());
+    if let Some(font) = &cli.font {
+        runner = runner.with_font(&Font::load(font)?);
+    }
+    if let Some(font_set) = cli.font_set {
+        runner = runner.with_font(&font_set.into());
+    }
+    if let Some(beep) = &cli.beep {
+        runner = runner.with_beep(beep).await?;
+    }
+    if let Some(bezel) = &cli.bezel {
+        runner = runner.with_bezel(bezel, cli.bezel_margin).await?;
+    }
+    // Before anything that restarts the ROM or restores a state, which the reset would undo
+    if let Some(seed) = cli.deterministic {
+        runner = runner.with_deterministic(seed);
+    }
+    if let Some(inputs) = &cli.inputs {
+        runner = runner.with_input_playback(InputMovie::load(inputs)?);
+    }
+    if let Some(script) = &cli.script {
+        runner = runner.with_input_script(InputScript::load(script)?);
+    }
+    if let Some(state) = &cli.load_state {
+        runner = runner.with_savestate(state)?;
+    }
+    if cli.decoupled {
+        runner = runner.with_pacing(Pacing::Decoupled).with_frame_skip(cli.frame_skip);
+    }
+    if let Some(heatmap) = &cli.heatmap {
+        runner = runner.with_heatmap_export(heatmap);
+    }
+    if let Some(dir) = &cli.dump_frames {
+        runner = runner.with_frame_dump(FrameDump::create(dir, cli.dump_every)?);
+    }
+    if let Some(path) = &cli.dump_audio {
+        runner = runner.with_audio_dump(AudioDump::new(path));
+    }
+
+    #[cfg(feature = "profiling")]
+    if let Some(profile) = &cli.profile {
+        runner = runner.with_profile(chip8rs::profiling::ProfileRecorder::new(profile));
+    }
+
+    #[cfg(feature = "remote")]
+    chip8rs::remote::serve(REMOTE_ADDRESS, runner.handle())?;
+
+    while runner.run().await.map_err(Failure::Emulation)? == chip8rs::ExitReason::ProgramExited {
+        runner.show_splash();
+        runner.notify("Program ended");
+    }
+
+    Ok(())
 }
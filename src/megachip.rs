@@ -0,0 +1,551 @@
+//! Experimental MegaChip-8 core: CHIP-8 plus a 256x192 display of palette colors, 24-bit addresses and the
+//! MegaChip op codes. The CHIP-8 op codes run through the same code as [`Emulator`](crate::emulator::Emulator) with
+//! the SCHIP quirks; only those touching memory past 4 KB are its own. It runs headless only, digitised sound is
+//! tracked but not played and the SCHIP scroll, hires and big font op codes are skipped like any other unimplemented
+//! one.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use thiserror::Error;
+
+use crate::{
+    constants,
+    display::{
+        self,
+        Framebuffer,
+    },
+    emulator::{
+        Interpreter,
+        KeyPad,
+        Palette,
+        ProgramCounter,
+    },
+    font::{
+        self,
+        Font,
+    },
+    mem::{
+        AddressStack,
+        Register,
+        VF,
+    },
+    process::{
+        self,
+        ProcessingError,
+    },
+    rng::Rng,
+};
+
+pub const WIDTH: usize = 256;
+pub const HEIGHT: usize = 192;
+/// 24-bit addresses reach 16 MB.
+pub const MEMORY_SIZE: usize = 0x100_0000;
+const LOAD_ADDRESS: usize = 0x200;
+const STACK_DEPTH: usize = 16;
+/// MegaChip grew out of SCHIP and shares its shift and jump quirks.
+const QUIRKS: Interpreter = Interpreter::SuperChip;
+/// Until the program switches MegaChip mode on it draws on a 64x32 display, shown scaled up 4 times.
+const LORES_WIDTH: usize = constants::DISPLAY_WIDTH as usize;
+const LORES_HEIGHT: usize = constants::DISPLAY_HEIGHT as usize;
+const LORES_SCALE: usize = 4;
+const LORES_TOP: usize = (HEIGHT - LORES_HEIGHT * LORES_SCALE) / 2;
+const WHITE: u32 = 0xFFFF_FFFF;
+
+#[derive(Error, Debug)]
+pub enum MegaChipError {
+    #[error("ROM of {len} bytes doesn't fit in memory")]
+    RomTooLarge { len: usize },
+
+    #[error("program counter 0x{pc:06X} ran past the end of memory")]
+    PcOutOfRange { pc: usize },
+
+    #[error("stack overflow calling from 0x{pc:06X}")]
+    StackOverflow { pc: usize },
+}
+
+/// How sprite colors combine with the display, set with 080N.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    /// The sprite at 25% opacity.
+    Quarter,
+    Half,
+    ThreeQuarters,
+    /// Channels added, saturating at white.
+    Add,
+    Multiply,
+}
+
+impl BlendMode {
+    fn from_nibble(n: u8) -> Self {
+        match n {
+            1 => BlendMode::Quarter,
+            2 => BlendMode::Half,
+            3 => BlendMode::ThreeQuarters,
+            4 => BlendMode::Add,
+            5 => BlendMode::Multiply,
+            _ => BlendMode::Normal,
+        }
+    }
+
+    /// Combines the ARGB `sprite` color with the `display` color under it.
+    fn blend(&self, display: u32, sprite: u32) -> u32 {
+        let mix = |weight: u32| {
+            channels(display, sprite, |display, sprite| {
+                (display * (4 - weight) + sprite * weight) / 4
+            })
+        };
+        match self {
+            BlendMode::Normal => sprite,
+            BlendMode::Quarter => mix(1),
+            BlendMode::Half => mix(2),
+            BlendMode::ThreeQuarters => mix(3),
+            BlendMode::Add => channels(display, sprite, |display, sprite| (display + sprite).min(0xFF)),
+            BlendMode::Multiply => channels(display, sprite, |display, sprite| display * sprite / 0xFF),
+        }
+    }
+}
+
+/// Applies `f` to the red, green and blue channels of two ARGB colors, keeping the sprite's alpha.
+fn channels(display: u32, sprite: u32, f: impl Fn(u32, u32) -> u32) -> u32 {
+    [16, 8, 0].iter().fold(sprite & 0xFF00_0000, |color, shift| {
+        color | f((display >> shift) & 0xFF, (sprite >> shift) & 0xFF) << shift
+    })
+}
+
+/// Digitised sound started by 060N: the sample header and data at `address`, looped when N is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub address: u32,
+    pub looping: bool,
+}
+
+pub struct MegaChip {
+    memory: Vec<u8>,
+    registers: Register,
+    index_register: u32,
+    pc: ProgramCounter,
+    stack: AddressStack,
+    delay_timer: u8,
+    sound_timer: u8,
+    keypad: KeyPad,
+    rng: Rng,
+    mega: bool,
+    /// ARGB colors, index 0 is transparent.
+    colors: [u32; 256],
+    sprite_width: usize,
+    sprite_height: usize,
+    alpha: u8,
+    blend: BlendMode,
+    collision_color: u8,
+    /// Drawn to since the last 00E0, which shows it and starts a new frame.
+    back: Vec<u32>,
+    back_indices: Vec<u8>,
+    front: Vec<u32>,
+    lores: Framebuffer,
+    /// Colors of the 64x32 display.
+    palette: Palette,
+    sample: Option<Sample>,
+    unimplemented: BTreeSet<u16>,
+    instruction_count: u64,
+    exited: bool,
+}
+
+impl MegaChip {
+    pub fn new(rom: &[u8]) -> Result<Self, MegaChipError> {
+        if rom.len() > MEMORY_SIZE - LOAD_ADDRESS {
+            Err(MegaChipError::RomTooLarge { len: rom.len() })?
+        }
+        let mut memory = vec![0; MEMORY_SIZE];
+        let font = Font::default();
+        memory[..font.data().len()].copy_from_slice(font.data());
+        memory[LOAD_ADDRESS..LOAD_ADDRESS + rom.len()].copy_from_slice(rom);
+
+        Ok(Self {
+            memory,
+            registers: Register::new(),
+            index_register: 0,
+            pc: ProgramCounter(LOAD_ADDRESS),
+            stack: AddressStack::default(),
+            delay_timer: 0,
+            sound_timer: 0,
+            keypad: KeyPad::headless(),
+            rng: Rng::new(),
+            mega: false,
+            colors: [0; 256],
+            sprite_width: 0,
+            sprite_height: 0,
+            alpha: 0xFF,
+            blend: BlendMode::default(),
+            collision_color: 0,
+            back: vec![0; WIDTH * HEIGHT],
+            back_indices: vec![0; WIDTH * HEIGHT],
+            front: vec![0; WIDTH * HEIGHT],
+            lores: Framebuffer::new(LORES_WIDTH, LORES_HEIGHT),
+            palette: Palette::default(),
+            sample: None,
+            unimplemented: BTreeSet::new(),
+            instruction_count: 0,
+            exited: false,
+        })
+    }
+
+    /// Holds the hex keys indexed by hex value, a key let go since the last call completes FX0A.
+    pub fn set_keys(&mut self, keys: [bool; 16]) {
+        for (hex, pressed) in keys.into_iter().enumerate() {
+            self.keypad.inject(hex as u8, pressed);
+        }
+    }
+
+    /// Colors of the display before the program switches MegaChip mode on.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Counts both timers down, call at 60 Hz.
+    pub fn tick_timers(&mut self) {
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+    }
+
+    /// Steps up to `instructions` instructions, ticking the timers like [`Emulator::run_for`]. Stops early when the
+    /// program exits.
+    ///
+    /// [`Emulator::run_for`]: crate::emulator::Emulator::run_for
+    pub fn run_for(&mut self, instructions: usize) -> Result<(), anyhow::Error> {
+        for _ in 0..instructions {
+            if self.exited {
+                break;
+            }
+            self.step()?;
+            if (self.instruction_count - 1) % constants::INSTRUCTIONS_PER_TICK == 0 {
+                self.tick_timers();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<(), anyhow::Error> {
+        let address = *self.pc.inner();
+        let op_code = self.word(address)?;
+        self.pc.increment();
+        self.execute(address, op_code)
+            .with_context(|| format!("failed processing op code 0x{:04X} at 0x{:06X}", op_code, address))?;
+        self.instruction_count += 1;
+        Ok(())
+    }
+
+    fn execute(&mut self, address: usize, op_code: u16) -> Result<(), anyhow::Error> {
+        let x = ((op_code >> 8) & 0xF) as u8;
+        let y = ((op_code >> 4) & 0xF) as u8;
+        let n = (op_code & 0xF) as u8;
+        let nn = (op_code & 0xFF) as u8;
+        let nnn = op_code & 0xFFF;
+        let (registers, pc) = (&mut self.registers, &mut self.pc);
+
+        match op_code >> 12 {
+            0x0 => self.execute_system(op_code, n, nn)?,
+            0x1 => process::op_1NNN(pc, nnn),
+            0x2 => {
+                if self.stack.as_slice().len() == STACK_DEPTH {
+                    Err(MegaChipError::StackOverflow { pc: address })?
+                }
+                process::op_2NNN(&mut self.stack, pc, nnn)
+            }
+            0x3 => self.skip_long(|chip| process::op_3XNN(&chip.registers, x, nn, &mut chip.pc))?,
+            0x4 => self.skip_long(|chip| process::op_4XNN(&chip.registers, x, nn, &mut chip.pc))?,
+            0x5 => self.skip_long(|chip| process::op_5XNN(&chip.registers, x, y, &mut chip.pc))?,
+            0x6 => process::op_6XNN(registers, x, nn)?,
+            0x7 => process::op_7XNN(registers, x, nn)?,
+            0x8 => self.execute_arithmetic(op_code, x, y, n)?,
+            0x9 => self.skip_long(|chip| process::op_9XY0(&chip.registers, x, y, &mut chip.pc))?,
+            0xA => self.index_register = nnn as u32,
+            0xB => process::op_BNNN(&QUIRKS, registers, pc, x, nnn)?,
+            0xC => process::op_CXNN(registers, &mut self.rng, x, nn)?,
+            0xD => {
+                let (vx, vy) = (registers.get(x)? as usize, registers.get(y)? as usize);
+                let collision = if self.mega {
+                    self.draw_mega(vx, vy, n)
+                } else {
+                    self.draw_lores(vx, vy, n)
+                };
+                self.registers.set(VF, collision as u8)?;
+            }
+            0xE if nn == 0x9E => {
+                self.skip_long(|chip| process::op_EX9E(&chip.registers, &chip.keypad, &mut chip.pc, x))?
+            }
+            0xE if nn == 0xA1 => {
+                self.skip_long(|chip| process::op_EXA1(&chip.registers, &chip.keypad, &mut chip.pc, x))?
+            }
+            0xF => self.execute_misc(op_code, x, nn)?,
+            _ => self.skip(op_code),
+        }
+        Ok(())
+    }
+
+    fn execute_system(&mut self, op_code: u16, n: u8, nn: u8) -> Result<(), anyhow::Error> {
+        match op_code >> 8 {
+            0x00 => match op_code {
+                0x0010 => self.mega = false,
+                0x0011 => self.mega = true,
+                0x00E0 if self.mega => {
+                    self.front.copy_from_slice(&self.back);
+                    self.back.fill(0);
+                    self.back_indices.fill(0);
+                }
+                0x00E0 => process::op_00E0(&mut self.lores),
+                0x00EE => process::op_00EE(&mut self.pc, &mut self.stack)?,
+                0x00FD => process::op_00FD(&mut self.exited),
+                _ => self.skip(op_code),
+            },
+            0x01 => {
+                let low = self.word(*self.pc.inner())?;
+                self.pc.increment();
+                self.index_register = (nn as u32) << 16 | low as u32;
+            }
+            0x02 => {
+                for color in 0..nn as u32 {
+                    let address = (self.index_register + color * 4) as usize;
+                    let argb = self
+                        .memory
+                        .get(address..address + 4)
+                        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                        .unwrap_or(0);
+                    self.colors[color as usize + 1] = argb;
+                }
+            }
+            0x03 => self.sprite_width = if nn == 0 { 256 } else { nn as usize },
+            0x04 => self.sprite_height = if nn == 0 { 256 } else { nn as usize },
+            0x05 => self.alpha = nn,
+            0x06 => {
+                self.sample = Some(Sample {
+                    address: self.index_register,
+                    looping: n == 0,
+                })
+            }
+            0x07 => self.sample = None,
+            0x08 => self.blend = BlendMode::from_nibble(n),
+            0x09 => self.collision_color = nn,
+            _ => self.skip(op_code),
+        }
+        Ok(())
+    }
+
+    fn execute_arithmetic(&mut self, op_code: u16, x: u8, y: u8, n: u8) -> Result<(), ProcessingError> {
+        let registers = &mut self.registers;
+        match n {
+            0x0 => process::op_8XY0(registers, x, y),
+            0x1 => process::op_8XY1(registers, x, y),
+            0x2 => process::op_8XY2(registers, x, y),
+            0x3 => process::op_8XY3(registers, x, y),
+            0x4 => process::op_8XY4(registers, x, y),
+            0x5 => process::op_8XY5(registers, x, y),
+            0x6 => process::op_8XY6(&QUIRKS, registers, x, y),
+            0x7 => process::op_8XY7(registers, x, y),
+            0xE => process::op_8XYE(&QUIRKS, registers, x, y),
+            _ => {
+                self.skip(op_code);
+                Ok(())
+            }
+        }
+    }
+
+    /// The op codes reading or writing memory through I work on 24-bit addresses, the rest are the core's.
+    fn execute_misc(&mut self, op_code: u16, x: u8, nn: u8) -> Result<(), ProcessingError> {
+        let registers = &mut self.registers;
+        match nn {
+            0x07 => process::op_FX07(registers, x, &self.delay_timer)?,
+            0x0A => process::op_FX0A(registers, &mut self.pc, &mut self.keypad, x)?,
+            0x15 => process::op_FX15(registers, x, &mut self.delay_timer)?,
+            0x18 => process::op_FX18(registers, x, &mut self.sound_timer)?,
+            0x1E => self.index_register = (self.index_register + registers.get(x)? as u32) % MEMORY_SIZE as u32,
+            0x29 => self.index_register = font::glyph_address(registers.get(x)?) as u32,
+            0x33 => {
+                let value = registers.get(x)?;
+                self.store(&[value / 100, value / 10 % 10, value % 10]);
+            }
+            0x55 => {
+                let values = registers.values();
+                self.store(&values[..=x as usize]);
+            }
+            0x65 => {
+                for register in 0..=x {
+                    let value = self.byte(self.index_register + register as u32);
+                    self.registers.set(register, value)?;
+                }
+            }
+            _ => self.skip(op_code),
+        }
+        Ok(())
+    }
+
+    /// Draws sprites of palette indices, or the 1-bit font when I points at it. Returns whether a pixel drawn over
+    /// had the collision color.
+    fn draw_mega(&mut self, x: usize, y: usize, n: u8) -> bool {
+        let font_sprite = (self.index_register as usize) < Font::default().data().len();
+        let (width, height) = if font_sprite {
+            (8, n as usize)
+        } else {
+            (self.sprite_width, self.sprite_height)
+        };
+
+        let mut collision = false;
+        for row in 0..height {
+            for column in 0..width {
+                let (px, py) = (x + column, y + row);
+                if px >= WIDTH || py >= HEIGHT {
+                    continue;
+                }
+                let (index, color) = if font_sprite {
+                    let bits = self.byte(self.index_register + row as u32);
+                    if bits & (0x80 >> column) == 0 {
+                        continue;
+                    }
+                    (0xFF, WHITE)
+                } else {
+                    let index = self.byte(self.index_register + (row * width + column) as u32);
+                    (index, self.colors[index as usize])
+                };
+                if index == 0 {
+                    continue;
+                }
+
+                let pixel = py * WIDTH + px;
+                collision |= self.back_indices[pixel] == self.collision_color && self.collision_color != 0;
+                self.back[pixel] = self.blend.blend(self.back[pixel], color);
+                self.back_indices[pixel] = index;
+            }
+        }
+        collision
+    }
+
+    /// Draws like DXYN on the core, reading the sprite from wherever I points.
+    fn draw_lores(&mut self, x: usize, y: usize, n: u8) -> bool {
+        let mut sprite = [0; 15];
+        let sprite = &mut sprite[..n as usize];
+        for (row, bits) in sprite.iter_mut().enumerate() {
+            *bits = self.byte(self.index_register + row as u32);
+        }
+        display::draw_sprite(&mut self.lores, x, y, sprite)
+    }
+
+    /// Runs one of the core's skips, then finishes a skip over LDHI, the only four byte instruction, which the core
+    /// steps over only half of.
+    fn skip_long(
+        &mut self,
+        skip: impl FnOnce(&mut Self) -> Result<(), ProcessingError>,
+    ) -> Result<(), ProcessingError> {
+        let next = *self.pc.inner();
+        skip(self)?;
+        if *self.pc.inner() != next && self.byte(next as u32) == 0x01 {
+            self.pc.increment();
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, op_code: u16) {
+        if self.unimplemented.insert(op_code) {
+            log::warn!("MegaChip instruction not implemented: {:04X}", op_code);
+        }
+    }
+
+    fn byte(&self, address: u32) -> u8 {
+        self.memory.get(address as usize).copied().unwrap_or(0)
+    }
+
+    fn word(&self, address: usize) -> Result<u16, MegaChipError> {
+        self.memory
+            .get(address..address + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .ok_or(MegaChipError::PcOutOfRange { pc: address })
+    }
+
+    /// Writes `bytes` from I on, dropping any past the end of memory.
+    fn store(&mut self, bytes: &[u8]) {
+        let start = self.index_register as usize;
+        if let Some(memory) = self.memory.get_mut(start..start + bytes.len()) {
+            memory.copy_from_slice(bytes);
+        }
+    }
+
+    /// The display as RGBA bytes, `WIDTH * HEIGHT * 4` long. MegaChip mode shows the frame completed by the last
+    /// 00E0 with the alpha set by 05NN, CHIP-8 mode its 64x32 display scaled up and centered in the palette's colors.
+    pub fn rgba(&self) -> Vec<u8> {
+        if self.mega {
+            return self
+                .front
+                .iter()
+                .flat_map(|argb| {
+                    let [_, r, g, b] = argb.to_be_bytes();
+                    [r, g, b, self.alpha]
+                })
+                .collect();
+        }
+
+        let bytes = |color: macroquad::color::Color| {
+            let [r, g, b, _]: [u8; 4] = color.into();
+            [r, g, b, 0xFF]
+        };
+        let (foreground, background) = (bytes(self.palette.foreground()), bytes(self.palette.background()));
+        let mut rgba = vec![0; WIDTH * HEIGHT * 4];
+        for (pixel, chunk) in rgba.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (
+                pixel % WIDTH / LORES_SCALE,
+                (pixel / WIDTH).wrapping_sub(LORES_TOP) / LORES_SCALE,
+            );
+            let lit = y < LORES_HEIGHT && self.lores.get(x, y);
+            chunk.copy_from_slice(if lit { &foreground } else { &background });
+        }
+        rgba
+    }
+
+    #[cfg(feature = "image")]
+    pub fn image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(WIDTH as u32, HEIGHT as u32, self.rgba()).unwrap_or_default()
+    }
+
+    pub fn is_mega(&self) -> bool {
+        self.mega
+    }
+
+    pub fn registers(&self) -> [u8; 16] {
+        self.registers.values()
+    }
+
+    pub fn index_register(&self) -> u32 {
+        self.index_register
+    }
+
+    pub fn pc(&self) -> u32 {
+        *self.pc.inner() as u32
+    }
+
+    /// The ARGB colors loaded with 02NN, index 0 is transparent.
+    pub fn palette(&self) -> &[u32; 256] {
+        &self.colors
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Digitised sound the program is playing, if any.
+    pub fn sample(&self) -> Option<Sample> {
+        self.sample
+    }
+
+    /// Op codes the program ran that this core doesn't implement and skipped.
+    pub fn unimplemented_opcodes(&self) -> &BTreeSet<u16> {
+        &self.unimplemented
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+}
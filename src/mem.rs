@@ -1,9 +1,9 @@
 use std::{
-    collections::HashMap,
-    fs::File,
-    io::{
-        self,
-        Read,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
     },
 };
 
@@ -11,7 +11,13 @@ use thiserror::Error;
 
 use crate::{
     constants,
-    emulator::ProgramCounter,
+    emulator::{
+        Interpreter,
+        ProgramCounter,
+        RomWarning,
+    },
+    font,
+    platform::PlatformScan,
 };
 
 #[derive(Error, Debug)]
@@ -25,12 +31,22 @@ pub(crate) struct Ram {
 }
 
 impl Ram {
-    pub fn load(rom: Rom, font: &[u8]) -> Self {
-        let mut ram: Ram = rom.into();
-        ram.memory[0..font.len()].copy_from_slice(font);
+    /// Places the ROM at `address`, cutting off whatever doesn't fit below display RAM.
+    pub fn load(rom: &Rom, address: usize, font: &[u8]) -> Self {
+        let mut memory = [0; constants::TOTAL_RAM];
+        let length = rom.len().min(constants::RAM_RANGE.1.saturating_sub(address));
+        memory[address..address + length].copy_from_slice(&rom.data()[..length]);
+
+        let mut ram = Ram { memory };
+        ram.load_font(font);
 
         ram
     }
+
+    pub fn load_font(&mut self, font: &[u8]) {
+        let start = font::FONT_ADDRESS as usize;
+        self.memory[start..start + font.len()].copy_from_slice(font);
+    }
     pub fn op_code(&self, pc: &ProgramCounter) -> Result<u16, RamError> {
         let pc = *pc.inner();
         let high = *self.memory.get(pc).ok_or(RamError::InvalidAddress(pc))? as u16;
@@ -38,10 +54,6 @@ impl Ram {
         Ok((high << 8) | low)
     }
 
-    pub fn reset_vram(&mut self) {
-        self.memory[constants::DISPLAY_RANGE.0..constants::DISPLAY_RANGE.1].fill(0);
-    }
-
     pub fn get<T: Into<usize>>(&self, index: T) -> Result<u8, RamError> {
         let idx = index.into();
         self.memory.get(idx).ok_or(RamError::InvalidAddress(idx)).copied()
@@ -51,114 +63,160 @@ impl Ram {
         let idx = index.into();
         self.memory.get_mut(idx).ok_or(RamError::InvalidAddress(idx))
     }
-}
 
-impl From<Rom> for Ram {
-    fn from(value: Rom) -> Self {
-        let mut buffer = [0; constants::TOTAL_RAM];
-        let length = std::cmp::min(constants::AVAILABLE_RAM, value.len());
-        buffer[constants::MEMORY_OFFSET..constants::MEMORY_OFFSET + length].copy_from_slice(value.data());
+    pub fn data(&self) -> &[u8; constants::TOTAL_RAM] {
+        &self.memory
+    }
+
+    pub fn restore(&mut self, memory: &[u8; constants::TOTAL_RAM]) {
+        self.memory = *memory;
+    }
 
-        Ram { memory: buffer }
+    /// Copies `bytes` in from `address`, dropping whatever runs past the end of RAM.
+    pub fn write(&mut self, address: usize, bytes: &[u8]) {
+        let start = address.min(constants::TOTAL_RAM);
+        let length = bytes.len().min(constants::TOTAL_RAM - start);
+        self.memory[start..start + length].copy_from_slice(&bytes[..length]);
     }
 }
 
 #[derive(Error, Debug)]
 pub(crate) enum RegisterError {
-    #[error("address `{0}` is not a valid register")]
-    InvalidAddress(String),
+    #[error("V{0:X} is not a valid register")]
+    InvalidAddress(u8),
 }
 
+/// Index of the flag register.
+pub(crate) const VF: u8 = 0xF;
+
 pub(crate) struct Register {
-    registers: HashMap<String, u8>,
+    registers: [u8; 16],
 }
 
 impl Register {
     pub fn new() -> Self {
-        let registers = HashMap::from([
-            ("V0".into(), 0),
-            ("V1".into(), 0),
-            ("V2".into(), 0),
-            ("V3".into(), 0),
-            ("V4".into(), 0),
-            ("V5".into(), 0),
-            ("V6".into(), 0),
-            ("V7".into(), 0),
-            ("V8".into(), 0),
-            ("V9".into(), 0),
-            ("VA".into(), 0),
-            ("VB".into(), 0),
-            ("VC".into(), 0),
-            ("VD".into(), 0),
-            ("VE".into(), 0),
-            ("VF".into(), 0),
-        ]);
-
-        Self { registers }
-    }
-
-    pub fn get(&self, key: &str) -> Result<u8, RegisterError> {
+        Self { registers: [0; 16] }
+    }
+
+    pub fn get(&self, index: u8) -> Result<u8, RegisterError> {
         self.registers
-            .get(key)
+            .get(index as usize)
             .copied()
-            .ok_or_else(|| RegisterError::InvalidAddress(key.to_owned()))
+            .ok_or(RegisterError::InvalidAddress(index))
     }
 
-    pub fn set(&mut self, key: &str, val: u8) -> Result<(), RegisterError> {
-        let register = self
-            .registers
-            .get_mut(key)
-            .ok_or_else(|| RegisterError::InvalidAddress(key.to_owned()))?;
-        *register = val;
-
+    pub fn set(&mut self, index: u8, val: u8) -> Result<(), RegisterError> {
+        *self.get_mut(index)? = val;
         Ok(())
     }
 
-    pub fn set_x_y(&mut self, x: &str, y: &str) -> Result<(), RegisterError> {
-        let y_val = *self
-            .registers
-            .get(y)
-            .ok_or_else(|| RegisterError::InvalidAddress(y.to_owned()))?;
-        let x_val = self
-            .registers
-            .get_mut(x)
-            .ok_or_else(|| RegisterError::InvalidAddress(x.to_owned()))?;
-
-        *x_val = y_val;
-        Ok(())
+    pub fn set_x_y(&mut self, x: u8, y: u8) -> Result<(), RegisterError> {
+        self.set(x, self.get(y)?)
     }
 
-    pub fn cmp_registers(&self, x: &str, y: &str) -> Result<bool, RegisterError> {
+    pub fn cmp_registers(&self, x: u8, y: u8) -> Result<bool, RegisterError> {
         Ok(self.get(x)? == self.get(y)?)
     }
 
-    pub fn get_mut(&mut self, key: &str) -> Result<&mut u8, RegisterError> {
+    pub fn get_mut(&mut self, index: u8) -> Result<&mut u8, RegisterError> {
         self.registers
-            .get_mut(key)
-            .ok_or_else(|| RegisterError::InvalidAddress(key.to_owned()))
+            .get_mut(index as usize)
+            .ok_or(RegisterError::InvalidAddress(index))
+    }
+
+    pub fn values(&self) -> [u8; 16] {
+        self.registers
+    }
+
+    pub fn restore(&mut self, values: &[u8; 16]) {
+        self.registers = *values;
     }
 }
 
+/// Why a ROM couldn't be loaded, as opposed to the window, audio or anything else failing to start.
 #[derive(Error, Debug)]
-pub(crate) enum RomError {
-    #[error("loading rom failed {0}")]
-    IoError(#[from] io::Error),
+pub enum RomError {
+    #[error("rom {} not found", path.display())]
+    NotFound { path: PathBuf },
+
+    #[error("rom {} is not a file", path.display())]
+    NotAFile { path: PathBuf },
+
+    #[error("rom {} is empty", path.display())]
+    Empty { path: PathBuf },
+
+    #[error("loading rom {} failed {source}", path.display())]
+    IoError { path: PathBuf, source: io::Error },
 
     #[error("out of memory {rom_size:?} > {ram_size:?}")]
     OutOfMemory { rom_size: usize, ram_size: usize },
 }
 
+#[derive(Clone)]
 pub(crate) struct Rom {
     data: Vec<u8>,
+    interpreter: Option<Interpreter>,
+    platform: PlatformScan,
 }
 
 impl Rom {
-    pub fn load(path: &str) -> Result<Self, RomError> {
-        let mut file = File::open(path)?;
-        let mut data = vec![];
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RomError> {
+        let path = path.as_ref();
+        let io_error = |source: io::Error| match source.kind() {
+            io::ErrorKind::NotFound => RomError::NotFound { path: path.to_owned() },
+            _ => RomError::IoError {
+                path: path.to_owned(),
+                source,
+            },
+        };
+        if !fs::metadata(path).map_err(io_error)?.is_file() {
+            Err(RomError::NotAFile { path: path.to_owned() })?
+        }
+
+        let data = fs::read(path).map_err(io_error)?;
+        if data.is_empty() {
+            Err(RomError::Empty { path: path.to_owned() })?
+        }
+        for warning in RomWarning::check(&data) {
+            log::warn!("{} looks suspicious: {}", path.display(), warning);
+        }
 
-        file.read_to_end(&mut data)?;
+        let mut rom = Self::from_bytes(data)?;
+        rom.platform = PlatformScan::scan(&rom.data, constants::MEMORY_OFFSET as u16);
+        for (extension, address) in rom.platform.unimplemented() {
+            log::warn!(
+                "{} uses {} at 0x{:03X}, which is not implemented",
+                path.display(),
+                extension.description(),
+                address
+            );
+        }
+        if rom.interpreter().is_some() {
+            return Ok(rom);
+        }
+        // Programs written for later platforms would misbehave under the configured interpreter's quirks
+        match rom.platform.platform().interpreter() {
+            Some(interpreter) => {
+                log::info!(
+                    "{} uses {}, running it as {}",
+                    path.display(),
+                    rom.platform,
+                    interpreter.name()
+                );
+                Ok(rom.with_interpreter(interpreter))
+            }
+            None => Ok(rom),
+        }
+    }
+
+    /// FNV-1a hash of the program, stored in savestates to tell which ROM they belong to.
+    pub fn hash(&self) -> u64 {
+        self.data.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
 
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, RomError> {
         if data.len() > constants::AVAILABLE_RAM {
             Err(RomError::OutOfMemory {
                 rom_size: data.len(),
@@ -166,7 +224,32 @@ impl Rom {
             })?
         }
 
-        Ok(Self { data })
+        Ok(Self {
+            data,
+            interpreter: None,
+            platform: PlatformScan::default(),
+        })
+    }
+
+    /// Pins the interpreter this ROM runs under regardless of the configured one.
+    pub fn with_interpreter(mut self, interpreter: Interpreter) -> Self {
+        self.interpreter = Some(interpreter);
+        self
+    }
+
+    /// The interpreter the ROM was pinned to or announces through its boot sequence.
+    pub fn interpreter(&self) -> Option<Interpreter> {
+        self.interpreter.or_else(|| Interpreter::detect(&self.data))
+    }
+
+    /// A one-line warning about the extensions the emulator would skip, if the ROM uses any.
+    pub fn unimplemented_warning(&self) -> Option<String> {
+        let extensions: Vec<_> = self
+            .platform
+            .unimplemented()
+            .map(|(extension, _)| extension.description())
+            .collect();
+        (!extensions.is_empty()).then(|| format!("This ROM uses {}, which is not implemented", extensions.join(", ")))
     }
 
     pub fn len(&self) -> usize {
@@ -184,6 +267,12 @@ pub struct StackEmptyError;
 #[derive(Default)]
 pub struct AddressStack(Vec<u16>);
 
+impl From<Vec<u16>> for AddressStack {
+    fn from(value: Vec<u16>) -> Self {
+        AddressStack(value)
+    }
+}
+
 impl AddressStack {
     pub fn pop<T: From<u16>>(&mut self) -> Result<T, StackEmptyError> {
         self.0.pop().ok_or(StackEmptyError).map(|val| T::from(val))
@@ -192,4 +281,8 @@ impl AddressStack {
     pub fn push<T: Into<u16>>(&mut self, val: T) {
         self.0.push(val.into());
     }
+
+    pub fn as_slice(&self) -> &[u16] {
+        &self.0
+    }
 }
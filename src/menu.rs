@@ -0,0 +1,548 @@
+use macroquad::{
+    camera::set_default_camera,
+    color::{
+        Color,
+        RED,
+        WHITE,
+        YELLOW,
+    },
+    input::{
+        get_char_pressed,
+        get_last_key_pressed,
+        is_key_pressed,
+        KeyCode,
+    },
+    shapes::draw_rectangle,
+    text::draw_text,
+    window::{
+        screen_height,
+        screen_width,
+    },
+};
+
+use crate::{
+    cheats::CheatList,
+    config::{
+        self,
+        Config,
+    },
+    emulator::{
+        Interpreter,
+        Palette,
+        HEX_LAYOUT,
+    },
+    search::SearchFilter,
+};
+
+const FONT_SIZE: f32 = 20.0;
+const LINE_HEIGHT: f32 = 24.0;
+const PANEL_WIDTH: f32 = 300.0;
+const BACKGROUND: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.8,
+};
+
+const SPEED_STEP: f64 = 0.25;
+const SPEED_RANGE: (f64, f64) = (0.25, 4.0);
+const VOLUME_STEP: f32 = 0.1;
+pub(crate) const SEARCH_RESULTS_SHOWN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuItem {
+    Resume,
+    Reset,
+    LoadRom,
+    Settings,
+    Cheats,
+    SearchMemory,
+    Quit,
+}
+
+impl MenuItem {
+    const ALL: [MenuItem; 7] = [
+        MenuItem::Resume,
+        MenuItem::Reset,
+        MenuItem::LoadRom,
+        MenuItem::Settings,
+        MenuItem::Cheats,
+        MenuItem::SearchMemory,
+        MenuItem::Quit,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Resume => "Resume",
+            MenuItem::Reset => "Reset",
+            MenuItem::LoadRom => "Load ROM",
+            MenuItem::Settings => "Settings",
+            MenuItem::Cheats => "Cheats",
+            MenuItem::SearchMemory => "Search memory",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsItem {
+    Speed,
+    Palette,
+    Quirks,
+    Volume,
+    KeyClick,
+    DrawBatching,
+    ResumeLastSession,
+    AutoSave,
+    GameCapture,
+    Keys,
+    Back,
+}
+
+impl SettingsItem {
+    const ALL: [SettingsItem; 11] = [
+        SettingsItem::Speed,
+        SettingsItem::Palette,
+        SettingsItem::Quirks,
+        SettingsItem::Volume,
+        SettingsItem::KeyClick,
+        SettingsItem::DrawBatching,
+        SettingsItem::ResumeLastSession,
+        SettingsItem::AutoSave,
+        SettingsItem::GameCapture,
+        SettingsItem::Keys,
+        SettingsItem::Back,
+    ];
+
+    fn label(&self, config: &Config) -> String {
+        match self {
+            SettingsItem::Speed => format!("Speed: {:.2}x", config.speed),
+            SettingsItem::Palette => format!("Palette: {}", config.palette.name()),
+            SettingsItem::Quirks => format!("Quirks: {}", config.interpreter.name()),
+            SettingsItem::Volume => format!("Volume: {:.0}%", config.volume * 100.0),
+            SettingsItem::KeyClick => format!("Key click: {}", if config.key_click { "on" } else { "off" }),
+            SettingsItem::DrawBatching => {
+                format!("Draw batching: {}", if config.draw_batching { "on" } else { "off" })
+            }
+            SettingsItem::ResumeLastSession => format!(
+                "Resume last session: {}",
+                if config.resume_last_session { "on" } else { "off" }
+            ),
+            SettingsItem::AutoSave => format!("Auto-save on quit: {}", if config.auto_save { "on" } else { "off" }),
+            SettingsItem::GameCapture => format!("Game capture: {}", if config.game_capture { "on" } else { "off" }),
+            SettingsItem::Keys => "Rebind keys".into(),
+            SettingsItem::Back => "Back".into(),
+        }
+    }
+
+    /// Applies a left (-1) or right (+1) press, returns whether the config changed.
+    fn adjust(&self, config: &mut Config, direction: i32) -> bool {
+        match self {
+            SettingsItem::Speed => {
+                config.speed = (config.speed + SPEED_STEP * direction as f64).clamp(SPEED_RANGE.0, SPEED_RANGE.1);
+            }
+            SettingsItem::Palette => {
+                let idx = cycle(
+                    Palette::NAMES.iter().position(|name| *name == config.palette.name()),
+                    Palette::NAMES.len(),
+                    direction,
+                );
+                config.palette = Palette::from_name(Palette::NAMES[idx]).unwrap_or_default();
+            }
+            SettingsItem::Quirks => {
+                let idx = cycle(
+                    Interpreter::NAMES
+                        .iter()
+                        .position(|name| *name == config.interpreter.name()),
+                    Interpreter::NAMES.len(),
+                    direction,
+                );
+                config.interpreter = Interpreter::from_name(Interpreter::NAMES[idx]).unwrap_or_default();
+            }
+            SettingsItem::Volume => {
+                let volume = (config.volume + VOLUME_STEP * direction as f32).clamp(0.0, 1.0);
+                config.volume = (volume * 10.0).round() / 10.0;
+            }
+            SettingsItem::KeyClick => config.key_click = !config.key_click,
+            SettingsItem::DrawBatching => config.draw_batching = !config.draw_batching,
+            SettingsItem::ResumeLastSession => config.resume_last_session = !config.resume_last_session,
+            SettingsItem::AutoSave => config.auto_save = !config.auto_save,
+            SettingsItem::GameCapture => config.game_capture = !config.game_capture,
+            SettingsItem::Keys | SettingsItem::Back => return false,
+        }
+        true
+    }
+}
+
+fn cycle(position: Option<usize>, count: usize, direction: i32) -> usize {
+    (position.unwrap_or(0) as i32 + direction).rem_euclid(count as i32) as usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MenuAction {
+    Resume,
+    ResumeSession,
+    Reset,
+    LoadRom(String),
+    ApplySettings(Config),
+    ApplyCheats(CheatList),
+    SearchMemory(SearchFilter),
+    RestartSearch,
+    Quit,
+}
+
+/// Memory search progress shown in the menu: the number of candidates and the first few with their values.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchResults {
+    pub count: usize,
+    pub shown: Vec<(u16, u8)>,
+}
+
+/// Reads a search command: a decimal or `0x` hex value to match, `c`hanged, `u`nchanged, `+`, `-`, or `r`estart.
+fn parse_search(command: &str) -> Option<MenuAction> {
+    let filter = match command.trim() {
+        "r" => return Some(MenuAction::RestartSearch),
+        "c" => SearchFilter::Changed,
+        "u" => SearchFilter::Unchanged,
+        "+" => SearchFilter::Increased,
+        "-" => SearchFilter::Decreased,
+        value => SearchFilter::Equal(match value.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16).ok()?,
+            None => value.parse().ok()?,
+        }),
+    };
+    Some(MenuAction::SearchMemory(filter))
+}
+
+enum Screen {
+    Main,
+    RomPath(String),
+    Settings,
+    Cheats,
+    Search(String),
+    Rebind {
+        keys: [[KeyCode; 4]; 4],
+        next: usize,
+    },
+    /// Asks whether to continue from the state saved when the ROM was last closed.
+    ResumePrompt,
+}
+
+/// Keyboard driven pause menu, navigated with the arrow keys and Enter.
+pub(crate) struct PauseMenu {
+    screen: Screen,
+    selected: usize,
+    config: Config,
+    cheats: CheatList,
+    search: Option<SearchResults>,
+    error: Option<String>,
+}
+
+impl PauseMenu {
+    pub fn new(config: Config, cheats: CheatList) -> Self {
+        Self {
+            screen: Screen::Main,
+            selected: 0,
+            config,
+            cheats,
+            search: None,
+            error: None,
+        }
+    }
+
+    /// Opens on the question whether to continue the last session.
+    pub fn resume_prompt(config: Config, cheats: CheatList) -> Self {
+        Self {
+            screen: Screen::ResumePrompt,
+            ..Self::new(config, cheats)
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn set_search_results(&mut self, results: Option<SearchResults>) {
+        self.search = results;
+    }
+
+    pub fn update(&mut self) -> Option<MenuAction> {
+        match &mut self.screen {
+            Screen::Main => self.update_main(),
+            Screen::RomPath(path) => {
+                while let Some(character) = get_char_pressed() {
+                    if !character.is_control() {
+                        path.push(character);
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    path.pop();
+                }
+                if is_key_pressed(KeyCode::Enter) && !path.is_empty() {
+                    return Some(MenuAction::LoadRom(path.clone()));
+                }
+                if is_key_pressed(KeyCode::Escape) {
+                    self.open(Screen::Main, MenuItem::LoadRom as usize);
+                }
+                None
+            }
+            Screen::Settings => self.update_settings(),
+            Screen::ResumePrompt => {
+                if is_key_pressed(KeyCode::Escape) {
+                    return Some(MenuAction::Resume);
+                }
+                self.navigate(2);
+                if !is_key_pressed(KeyCode::Enter) {
+                    return None;
+                }
+                Some(if self.selected == 0 {
+                    MenuAction::ResumeSession
+                } else {
+                    MenuAction::Resume
+                })
+            }
+            Screen::Cheats => self.update_cheats(),
+            Screen::Search(command) => {
+                while let Some(character) = get_char_pressed() {
+                    if !character.is_control() {
+                        command.push(character);
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    command.pop();
+                }
+                if is_key_pressed(KeyCode::Escape) {
+                    self.open(Screen::Main, MenuItem::SearchMemory as usize);
+                    return None;
+                }
+                if !is_key_pressed(KeyCode::Enter) || command.is_empty() {
+                    return None;
+                }
+
+                let action = parse_search(command);
+                command.clear();
+                if action.is_none() {
+                    self.error = Some("Unknown search command".into());
+                }
+                action
+            }
+            Screen::Rebind { keys, next } => {
+                if is_key_pressed(KeyCode::Escape) {
+                    self.open(Screen::Settings, SettingsItem::Keys as usize);
+                    return None;
+                }
+                let key = get_last_key_pressed().filter(|key| config::key_name(*key).is_some())?;
+                keys[*next / 4][*next % 4] = key;
+                *next += 1;
+                if *next < 16 {
+                    return None;
+                }
+
+                self.config.keys = *keys;
+                self.open(Screen::Settings, SettingsItem::Keys as usize);
+                Some(MenuAction::ApplySettings(self.config.clone()))
+            }
+        }
+    }
+
+    fn open(&mut self, screen: Screen, selected: usize) {
+        self.screen = screen;
+        self.selected = selected;
+        self.error = None;
+    }
+
+    fn navigate(&mut self, count: usize) {
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    fn update_main(&mut self) -> Option<MenuAction> {
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(MenuAction::Resume);
+        }
+        self.navigate(MenuItem::ALL.len());
+        if !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+
+        match MenuItem::ALL[self.selected] {
+            MenuItem::Resume => Some(MenuAction::Resume),
+            MenuItem::Reset => Some(MenuAction::Reset),
+            MenuItem::LoadRom => {
+                // Discard keys typed while the game was running
+                while get_char_pressed().is_some() {}
+                self.open(Screen::RomPath(String::new()), 0);
+                None
+            }
+            MenuItem::Settings => {
+                self.open(Screen::Settings, 0);
+                None
+            }
+            MenuItem::Cheats => {
+                self.open(Screen::Cheats, 0);
+                None
+            }
+            MenuItem::SearchMemory => {
+                while get_char_pressed().is_some() {}
+                self.open(Screen::Search(String::new()), 0);
+                None
+            }
+            MenuItem::Quit => Some(MenuAction::Quit),
+        }
+    }
+
+    fn update_settings(&mut self) -> Option<MenuAction> {
+        if is_key_pressed(KeyCode::Escape) {
+            self.open(Screen::Main, MenuItem::Settings as usize);
+            return None;
+        }
+        self.navigate(SettingsItem::ALL.len());
+
+        let item = SettingsItem::ALL[self.selected];
+        let direction = match (is_key_pressed(KeyCode::Left), is_key_pressed(KeyCode::Right)) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => 0,
+        };
+        if direction != 0 && item.adjust(&mut self.config, direction) {
+            return Some(MenuAction::ApplySettings(self.config.clone()));
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            match item {
+                SettingsItem::Keys => self.open(
+                    Screen::Rebind {
+                        keys: self.config.keys,
+                        next: 0,
+                    },
+                    0,
+                ),
+                SettingsItem::Back => self.open(Screen::Main, MenuItem::Settings as usize),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Enter toggles the selected cheat, the entry after the last cheat goes back.
+    fn update_cheats(&mut self) -> Option<MenuAction> {
+        if is_key_pressed(KeyCode::Escape) {
+            self.open(Screen::Main, MenuItem::Cheats as usize);
+            return None;
+        }
+        let count = self.cheats.cheats.len();
+        self.navigate(count + 1);
+        if !is_key_pressed(KeyCode::Enter) {
+            return None;
+        }
+
+        if self.selected == count {
+            self.open(Screen::Main, MenuItem::Cheats as usize);
+            return None;
+        }
+        self.cheats.toggle(self.selected);
+        Some(MenuAction::ApplyCheats(self.cheats.clone()))
+    }
+
+    pub fn draw(&self) {
+        set_default_camera();
+
+        let lines = SettingsItem::ALL.len().max(self.cheats.cheats.len() + 1) + 3;
+        let height = lines as f32 * LINE_HEIGHT;
+        let left = (screen_width() - PANEL_WIDTH) / 2.0;
+        let top = (screen_height() - height) / 2.0;
+        draw_rectangle(left, top, PANEL_WIDTH, height, BACKGROUND);
+
+        let x = left + 16.0;
+        let y = top + LINE_HEIGHT;
+        match &self.screen {
+            Screen::Main => {
+                let labels = MenuItem::ALL.map(|item| item.label().to_owned());
+                self.draw_list("Paused", &labels, x, y);
+            }
+            Screen::RomPath(path) => {
+                draw_text("Load ROM", x, y, FONT_SIZE, WHITE);
+                draw_text("Path:", x, y + LINE_HEIGHT, FONT_SIZE, WHITE);
+                draw_text(format!("{}_", path), x, y + 2.0 * LINE_HEIGHT, FONT_SIZE, YELLOW);
+            }
+            Screen::Settings => {
+                let labels = SettingsItem::ALL.map(|item| item.label(&self.config));
+                self.draw_list("Settings", &labels, x, y);
+            }
+            Screen::Cheats => {
+                let mut labels: Vec<String> = self
+                    .cheats
+                    .cheats
+                    .iter()
+                    .map(|cheat| format!("[{}] {}", if cheat.enabled { "x" } else { " " }, cheat.name))
+                    .collect();
+                labels.push("Back".into());
+                self.draw_list("Cheats", &labels, x, y);
+                if self.cheats.cheats.is_empty() {
+                    draw_text("No cheats for this ROM", x, y + 2.0 * LINE_HEIGHT, 16.0, WHITE);
+                }
+            }
+            Screen::Search(command) => {
+                draw_text("Search memory", x, y, FONT_SIZE, WHITE);
+                draw_text(format!("> {}_", command), x, y + LINE_HEIGHT, FONT_SIZE, YELLOW);
+                let mut line_y = y + 2.0 * LINE_HEIGHT;
+                match &self.search {
+                    Some(results) => {
+                        draw_text(format!("{} candidates", results.count), x, line_y, FONT_SIZE, WHITE);
+                        for (address, value) in &results.shown {
+                            line_y += LINE_HEIGHT;
+                            draw_text(
+                                format!("0x{:03X} = {} (0x{:02X})", address, value, value),
+                                x,
+                                line_y,
+                                FONT_SIZE,
+                                WHITE,
+                            );
+                        }
+                    }
+                    None => {
+                        draw_text("value, c, u, +, - or r", x, line_y, 16.0, WHITE);
+                    }
+                }
+            }
+            Screen::ResumePrompt => {
+                let labels = ["Resume".to_owned(), "Start over".to_owned()];
+                self.draw_list("Resume last session?", &labels, x, y);
+            }
+            Screen::Rebind { next, .. } => {
+                draw_text("Rebind keys", x, y, FONT_SIZE, WHITE);
+                let hex = HEX_LAYOUT[next / 4][next % 4];
+                draw_text(
+                    format!("Press key for {:X}", hex),
+                    x,
+                    y + LINE_HEIGHT,
+                    FONT_SIZE,
+                    YELLOW,
+                );
+                draw_text("Escape to cancel", x, y + 2.0 * LINE_HEIGHT, 16.0, WHITE);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            draw_text(error, x, top + height - 8.0, 16.0, RED);
+        }
+    }
+
+    fn draw_list(&self, title: &str, labels: &[String], x: f32, mut y: f32) {
+        draw_text(title, x, y, FONT_SIZE, WHITE);
+        y += LINE_HEIGHT;
+        for (i, label) in labels.iter().enumerate() {
+            let (prefix, color) = if i == self.selected {
+                ("> ", YELLOW)
+            } else {
+                ("  ", WHITE)
+            };
+            draw_text(format!("{}{}", prefix, label), x, y, FONT_SIZE, color);
+            y += LINE_HEIGHT;
+        }
+    }
+}
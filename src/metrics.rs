@@ -0,0 +1,75 @@
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufWriter,
+        Write,
+    },
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    emulator::Emulator,
+    runner::FrameInfo,
+};
+
+/// Appends one CSV row of runner statistics per interval, for soak testing.
+///
+/// There is no audio underrun column: macroquad's audio backend, quad-snd, recovers from ALSA underruns with only a
+/// print to stdout and its other backends don't report them at all, so there is nothing to count.
+pub struct MetricsLog {
+    writer: BufWriter<File>,
+    interval: Duration,
+    window_start: Duration,
+    frame_times: Vec<Duration>,
+    instructions: usize,
+    draws_at_window_start: u64,
+}
+
+impl MetricsLog {
+    pub fn create<P: AsRef<Path>>(path: P, interval: Duration) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "elapsed_s,ips,fps,frame_time_p95_ms,draws")?;
+        writer.flush()?;
+
+        Ok(Self {
+            writer,
+            interval,
+            window_start: Duration::ZERO,
+            frame_times: Vec::new(),
+            instructions: 0,
+            draws_at_window_start: 0,
+        })
+    }
+
+    pub(crate) fn record(&mut self, frame: &FrameInfo, emulator: &Emulator) -> io::Result<()> {
+        self.frame_times.push(frame.frame_time);
+        self.instructions += frame.instructions;
+
+        let window = frame.elapsed.saturating_sub(self.window_start);
+        if window < self.interval || window.is_zero() {
+            return Ok(());
+        }
+
+        let seconds = window.as_secs_f64();
+        self.frame_times.sort_unstable();
+        let p95_idx = ((self.frame_times.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+        writeln!(
+            self.writer,
+            "{:.3},{:.1},{:.1},{:.3},{}",
+            frame.elapsed.as_secs_f64(),
+            self.instructions as f64 / seconds,
+            self.frame_times.len() as f64 / seconds,
+            self.frame_times[p95_idx].as_secs_f64() * 1000.0,
+            emulator.draw_count() - self.draws_at_window_start
+        )?;
+        self.writer.flush()?;
+
+        self.window_start = frame.elapsed;
+        self.frame_times.clear();
+        self.instructions = 0;
+        self.draws_at_window_start = emulator.draw_count();
+        Ok(())
+    }
+}
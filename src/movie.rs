@@ -0,0 +1,161 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use thiserror::Error;
+
+/// Written as the `version` line. Movies without one were recorded before it existed and read as version 1.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum MovieError {
+    #[error("input movie io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("line {line}: expected `version = number`, `seed = number` or `frame keys`")]
+    InvalidLine { line: usize },
+
+    #[error(
+        "line {line}: input movie format version {version} is not supported, this build reads up to {FORMAT_VERSION}"
+    )]
+    UnsupportedVersion { line: usize, version: u32 },
+
+    #[error("line {line}: frame {frame} is not after the previous one")]
+    FrameOutOfOrder { line: usize, frame: u64 },
+
+    #[error("line {line}: invalid key `{value}`")]
+    InvalidKey { line: usize, value: String },
+}
+
+/// Keypad state for every frame of a run from a reset, stored as `frame keys` lines listing the hex keys held from
+/// that frame on, `-` for none. The `seed` line holds the random seed the run started with and the `version` line
+/// the format version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputMovie {
+    pub seed: u64,
+    frames: Vec<[bool; 16]>,
+}
+
+impl InputMovie {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    /// The movie recorded for the ROM at `rom`, `game.ch8` keeps its inputs in `game.inputs`.
+    pub fn path_for_rom<P: AsRef<Path>>(rom: P) -> PathBuf {
+        rom.as_ref().with_extension("inputs")
+    }
+
+    pub fn push(&mut self, keys: [bool; 16]) {
+        self.frames.push(keys);
+    }
+
+    /// Keys held on `frame`, `None` once the movie has ended.
+    pub fn keys_at(&self, frame: u64) -> Option<[bool; 16]> {
+        self.frames.get(frame as usize).copied()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.frames.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MovieError> {
+        let mut movie = Self::default();
+        let mut held = [false; 16];
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            if let Some((key, value)) = content.split_once('=') {
+                let invalid = || MovieError::InvalidLine { line };
+                match key.trim() {
+                    "version" => {
+                        let version = value.trim().parse().map_err(|_| invalid())?;
+                        if !(1..=FORMAT_VERSION).contains(&version) {
+                            Err(MovieError::UnsupportedVersion { line, version })?
+                        }
+                    }
+                    "seed" => movie.seed = value.trim().parse().map_err(|_| invalid())?,
+                    _ => Err(invalid())?,
+                }
+                continue;
+            }
+
+            let mut fields = content.split_whitespace();
+            let frame: u64 = fields
+                .next()
+                .and_then(|frame| frame.parse().ok())
+                .ok_or(MovieError::InvalidLine { line })?;
+            if frame < movie.len() {
+                Err(MovieError::FrameOutOfOrder { line, frame })?
+            }
+            while movie.len() < frame {
+                movie.push(held);
+            }
+
+            held = [false; 16];
+            for value in fields.filter(|value| *value != "-") {
+                let hex = u8::from_str_radix(value, 16)
+                    .ok()
+                    .filter(|hex| *hex < 16)
+                    .ok_or_else(|| MovieError::InvalidKey {
+                        line,
+                        value: value.to_owned(),
+                    })?;
+                held[hex as usize] = true;
+            }
+            movie.push(held);
+        }
+
+        Ok(movie)
+    }
+
+    /// Writes a line only for the frames where the held keys change, plus the last frame to keep the length.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "version = {}", FORMAT_VERSION);
+        let _ = writeln!(text, "seed = {}", self.seed);
+        for (frame, keys) in self.frames.iter().enumerate() {
+            let last = frame + 1 == self.frames.len();
+            if frame > 0 && self.frames[frame - 1] == *keys && !last {
+                continue;
+            }
+            let held: Vec<String> = (0..16)
+                .filter(|hex| keys[*hex])
+                .map(|hex| format!("{:X}", hex))
+                .collect();
+            let _ = writeln!(
+                text,
+                "{} {}",
+                frame,
+                if held.is_empty() { "-".into() } else { held.join(" ") }
+            );
+        }
+        text
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MovieError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MovieError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
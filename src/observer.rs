@@ -0,0 +1,34 @@
+use crate::emulator::{
+    Emulator,
+    InstructionData,
+};
+
+/// Something observable the program did, reported to observers after the instruction or timer tick that caused it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmulatorEvent {
+    ScreenCleared,
+    /// A sprite was drawn at `x`, `y` before wrapping.
+    SpriteDrawn {
+        x: u8,
+        y: u8,
+        collision: bool,
+    },
+    SoundStarted,
+    SoundStopped,
+    /// The delay timer counted down to zero.
+    DelayExpired,
+    /// FX0A started blocking until a key is released.
+    WaitingForKey,
+    /// A subroutine call pushed its return address.
+    StackPushed(u16),
+    /// A return popped this address off the stack.
+    StackPopped(u16),
+}
+
+pub trait Observer: Send {
+    fn before_instruction(&mut self, _emulator: &Emulator, _instruction: &InstructionData) {}
+
+    fn after_instruction(&mut self, _emulator: &Emulator, _instruction: &InstructionData) {}
+
+    fn on_event(&mut self, _emulator: &Emulator, _event: &EmulatorEvent) {}
+}
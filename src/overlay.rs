@@ -0,0 +1,445 @@
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use macroquad::{
+    camera::set_default_camera,
+    color::{
+        Color,
+        GREEN,
+        RED,
+        SKYBLUE,
+        WHITE,
+        YELLOW,
+    },
+    math::Rect,
+    shapes::{
+        draw_line,
+        draw_rectangle,
+        draw_rectangle_lines,
+    },
+    text::{
+        draw_text,
+        measure_text,
+    },
+    window::{
+        screen_height,
+        screen_width,
+    },
+};
+
+use crate::{
+    constants,
+    emulator::{
+        Emulator,
+        HEX_LAYOUT,
+    },
+    heatmap,
+    runner::FrameInfo,
+    sprites::{
+        PendingDraw,
+        SPRITE_WIDTH,
+    },
+    symbols::SymbolTable,
+    watch::Watch,
+};
+
+const GRAPH_SAMPLES: usize = 120;
+const GRAPH_HEIGHT: f32 = 60.0;
+const BATCH_HEIGHT: f32 = 16.0;
+const BAR_WIDTH: f32 = 2.0;
+const MAX_FRAME_MS: f64 = constants::MS_60HZ * 2.0;
+const BACKGROUND: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.6,
+};
+
+const KEY_SIZE: f32 = 16.0;
+const KEY_GAP: f32 = 2.0;
+const KEY_IDLE: Color = Color {
+    r: 0.3,
+    g: 0.3,
+    b: 0.3,
+    a: 0.8,
+};
+
+const TOAST_DURATION: Duration = Duration::from_millis(2500);
+const TOAST_FADE: Duration = Duration::from_millis(500);
+const MAX_TOASTS: usize = 4;
+
+const WATCH_LINE_HEIGHT: f32 = 18.0;
+
+const HEATMAP_HEIGHT: f32 = 12.0;
+const HEATMAP_COLD: Color = Color {
+    r: 0.1,
+    g: 0.1,
+    b: 0.4,
+    a: 0.8,
+};
+
+const PREVIEW_PIXEL: f32 = 6.0;
+
+const SOUND_HEIGHT: f32 = 40.0;
+const WAVE_WIDTH: f32 = 64.0;
+const WAVE_PERIODS: usize = 4;
+
+/// Just below the keypad.
+const STACK_TOP: f32 = 4.0 * KEY_SIZE + 5.0 * KEY_GAP + 4.0;
+/// Recent instructions listed under the call stack.
+const STACK_HISTORY: usize = 8;
+
+const GRID: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.08,
+};
+
+pub(crate) fn draw_pixel_grid(area: Rect, columns: i32, rows: i32) {
+    let cell_width = area.w / columns as f32;
+    let cell_height = area.h / rows as f32;
+    for column in 1..columns {
+        let x = area.x + column as f32 * cell_width;
+        draw_line(x, area.y, x, area.bottom(), 1.0, GRID);
+    }
+    for row in 1..rows {
+        let y = area.y + row as f32 * cell_height;
+        draw_line(area.x, y, area.right(), y, 1.0, GRID);
+    }
+}
+
+/// Draws the hex keypad in the top right corner, highlighting the keys currently pressed.
+pub(crate) fn draw_keypad(pressed: &[bool; 16]) {
+    set_default_camera();
+
+    let size = 4.0 * KEY_SIZE + 5.0 * KEY_GAP;
+    let left = screen_width() - size;
+    draw_rectangle(left, 0.0, size, size, BACKGROUND);
+
+    for (row, hexes) in HEX_LAYOUT.iter().enumerate() {
+        for (col, hex) in hexes.iter().enumerate() {
+            let x = left + KEY_GAP + col as f32 * (KEY_SIZE + KEY_GAP);
+            let y = KEY_GAP + row as f32 * (KEY_SIZE + KEY_GAP);
+            let color = if pressed[*hex as usize] { GREEN } else { KEY_IDLE };
+            draw_rectangle(x, y, KEY_SIZE, KEY_SIZE, color);
+            draw_text(format!("{:X}", hex), x + 4.0, y + 13.0, 16.0, WHITE);
+        }
+    }
+}
+
+/// Lists the watched values in the bottom right corner.
+pub(crate) fn draw_watches(watches: &[Watch], emulator: &Emulator) {
+    if watches.is_empty() {
+        return;
+    }
+    set_default_camera();
+
+    let lines: Vec<String> = watches
+        .iter()
+        .map(|watch| {
+            let value = watch.read(emulator);
+            if watch.is_byte() {
+                format!("{} = 0x{:02X} ({})", watch, value, value)
+            } else {
+                format!("{} = 0x{:04X} ({})", watch, value, value)
+            }
+        })
+        .collect();
+    let width = lines
+        .iter()
+        .map(|line| measure_text(line, None, 16, 1.0).width)
+        .fold(0.0, f32::max)
+        + 12.0;
+    let height = lines.len() as f32 * WATCH_LINE_HEIGHT + 6.0;
+    let (left, top) = (screen_width() - width, screen_height() - height);
+    draw_rectangle(left, top, width, height, BACKGROUND);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, left + 6.0, top + 16.0 + i as f32 * WATCH_LINE_HEIGHT, 16.0, WHITE);
+    }
+}
+
+/// Outlines where the pending DXYN draws, `target` in screen coordinates, and shows its sprite enlarged in the bottom
+/// left corner with its address and position.
+pub(crate) fn draw_sprite_preview(draw: &PendingDraw, target: Rect) {
+    set_default_camera();
+
+    draw_rectangle_lines(target.x, target.y, target.w, target.h, 2.0, YELLOW);
+
+    let label = format!(
+        "draw {} rows from 0x{:03X} at ({}, {})",
+        draw.sprite.rows.len(),
+        draw.sprite.address,
+        draw.x,
+        draw.y
+    );
+    let sprite_size = (
+        SPRITE_WIDTH as f32 * PREVIEW_PIXEL,
+        draw.sprite.rows.len() as f32 * PREVIEW_PIXEL,
+    );
+    let width = measure_text(&label, None, 16, 1.0).width.max(sprite_size.0) + 12.0;
+    let height = sprite_size.1 + WATCH_LINE_HEIGHT + 12.0;
+    let top = screen_height() - height;
+    draw_rectangle(0.0, top, width, height, BACKGROUND);
+    draw_text(&label, 6.0, top + 16.0, 16.0, WHITE);
+    for (row, bits) in draw.sprite.rows.iter().enumerate() {
+        for column in 0..SPRITE_WIDTH {
+            let color = if bits & (0x80 >> column) != 0 { YELLOW } else { KEY_IDLE };
+            draw_rectangle(
+                6.0 + column as f32 * PREVIEW_PIXEL,
+                top + WATCH_LINE_HEIGHT + 6.0 + row as f32 * PREVIEW_PIXEL,
+                PREVIEW_PIXEL - 1.0,
+                PREVIEW_PIXEL - 1.0,
+                color,
+            );
+        }
+    }
+}
+
+/// Lists the call chain below the keypad, the program counter first and then the call that entered each active
+/// subroutine, innermost first, followed by the last few instructions run, with their labels when `symbols` has any.
+pub(crate) fn draw_stack(emulator: &Emulator, symbols: &SymbolTable) {
+    set_default_camera();
+
+    let history = emulator.history();
+    let describe = |address: u16| match symbols.locate(address) {
+        Some(label) => format!("0x{:03X} {}", address, label),
+        None => format!("0x{:03X}", address),
+    };
+    let lines: Vec<String> = std::iter::once(format!("pc   {}", describe(emulator.pc() as u16)))
+        .chain(
+            emulator
+                .call_sites()
+                .into_iter()
+                .rev()
+                .enumerate()
+                .map(|(depth, call)| format!("#{:<3} {}", depth + 1, describe(call))),
+        )
+        .chain(
+            history
+                .iter()
+                .skip(history.len().saturating_sub(STACK_HISTORY))
+                .map(|entry| entry.describe(symbols)),
+        )
+        .collect();
+    let width = lines
+        .iter()
+        .map(|line| measure_text(line, None, 16, 1.0).width)
+        .fold(0.0, f32::max)
+        + 12.0;
+    let height = lines.len() as f32 * WATCH_LINE_HEIGHT + 6.0;
+    let (left, top) = (screen_width() - width, STACK_TOP);
+    draw_rectangle(left, top, width, height, BACKGROUND);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, left + 6.0, top + 16.0 + i as f32 * WATCH_LINE_HEIGHT, 16.0, WHITE);
+    }
+}
+
+/// Draws execution counts as a strip along the top edge, from the first to the last executed address, and labels
+/// the hottest one.
+pub(crate) fn draw_heatmap(counts: &[u32]) {
+    let executed = || counts.iter().enumerate().filter(|(_, count)| **count > 0);
+    let (Some((first, _)), Some((last, _))) = (executed().next(), executed().next_back()) else {
+        return;
+    };
+    let (hottest, max) = executed().max_by_key(|(_, count)| **count).unwrap_or((first, &0));
+    set_default_camera();
+
+    let width = screen_width();
+    draw_rectangle(0.0, 0.0, width, HEATMAP_HEIGHT, BACKGROUND);
+    let span = last + 1 - first;
+    let columns = (width as usize).min(span);
+    let column_width = width / columns as f32;
+    for column in 0..columns {
+        let start = first + column * span / columns;
+        let end = (first + (column + 1) * span / columns).max(start + 1);
+        let count = counts[start..end].iter().copied().max().unwrap_or(0);
+        let heat = heatmap::intensity(count, *max);
+        let color = Color {
+            r: HEATMAP_COLD.r + (1.0 - HEATMAP_COLD.r) * heat,
+            g: HEATMAP_COLD.g,
+            b: HEATMAP_COLD.b * (1.0 - heat),
+            a: HEATMAP_COLD.a,
+        };
+        draw_rectangle(
+            column as f32 * column_width,
+            0.0,
+            column_width.ceil(),
+            HEATMAP_HEIGHT,
+            color,
+        );
+    }
+
+    let label = format!("0x{:03X}-0x{:03X}, hottest 0x{:03X} ({}x)", first, last, hottest, max);
+    let label_width = measure_text(&label, None, 16, 1.0).width + 12.0;
+    draw_rectangle(0.0, HEATMAP_HEIGHT, label_width, 18.0, BACKGROUND);
+    draw_text(&label, 6.0, HEATMAP_HEIGHT + 14.0, 16.0, WHITE);
+}
+
+#[derive(Default)]
+pub(crate) struct FrameGraph {
+    samples: VecDeque<FrameInfo>,
+}
+
+impl FrameGraph {
+    pub fn push(&mut self, info: FrameInfo) {
+        if self.samples.len() == GRAPH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(info);
+    }
+
+    pub fn draw(&self) {
+        set_default_camera();
+
+        let width = GRAPH_SAMPLES as f32 * BAR_WIDTH;
+        let bottom = screen_height();
+        let top = bottom - GRAPH_HEIGHT - BATCH_HEIGHT;
+        draw_rectangle(0.0, top, width, GRAPH_HEIGHT + BATCH_HEIGHT, BACKGROUND);
+
+        let max_batch = self
+            .samples
+            .iter()
+            .map(|sample| sample.instructions)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (i, sample) in self.samples.iter().enumerate() {
+            let x = i as f32 * BAR_WIDTH;
+            let frame_ms = sample.frame_time.as_secs_f64() * 1000.0;
+            let bar = (frame_ms / MAX_FRAME_MS).min(1.0) as f32 * GRAPH_HEIGHT;
+            let color = match frame_ms {
+                ms if ms <= constants::MS_60HZ * 1.1 => GREEN,
+                ms if ms <= constants::MS_60HZ * 1.5 => YELLOW,
+                _ => RED,
+            };
+            draw_rectangle(x, top + GRAPH_HEIGHT - bar, BAR_WIDTH, bar, color);
+
+            let batch = sample.instructions as f32 / max_batch as f32 * BATCH_HEIGHT;
+            draw_rectangle(x, bottom - batch, BAR_WIDTH, batch, SKYBLUE);
+        }
+
+        let target = top + GRAPH_HEIGHT - (constants::MS_60HZ / MAX_FRAME_MS) as f32 * GRAPH_HEIGHT;
+        draw_line(0.0, target, width, target, 1.0, WHITE);
+
+        if let Some(last) = self.samples.back() {
+            draw_text(
+                format!("{:.1} ms", last.frame_time.as_secs_f64() * 1000.0),
+                4.0,
+                top + 14.0,
+                16.0,
+                WHITE,
+            );
+        }
+    }
+}
+
+/// The sound timer over the last two seconds, a bar per frame scaled to the highest value, drawn along the bottom
+/// edge next to the waveform the buzzer is playing.
+#[derive(Default)]
+pub(crate) struct SoundGraph {
+    samples: VecDeque<u8>,
+}
+
+impl SoundGraph {
+    pub fn push(&mut self, sound_timer: u8) {
+        if self.samples.len() == GRAPH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sound_timer);
+    }
+
+    /// The buzzer plays a square wave, drawn with an amplitude following `volume` while the timer runs and flat
+    /// while it's silent.
+    pub fn draw(&self, volume: f32) {
+        set_default_camera();
+
+        let graph_width = GRAPH_SAMPLES as f32 * BAR_WIDTH;
+        let width = graph_width + KEY_GAP + WAVE_WIDTH;
+        let (left, top) = ((screen_width() - width) / 2.0, screen_height() - SOUND_HEIGHT);
+        draw_rectangle(left, top, width, SOUND_HEIGHT, BACKGROUND);
+
+        let max = self.samples.iter().copied().max().unwrap_or(0).max(1);
+        for (i, timer) in self.samples.iter().enumerate() {
+            let bar = *timer as f32 / max as f32 * SOUND_HEIGHT;
+            draw_rectangle(
+                left + i as f32 * BAR_WIDTH,
+                top + SOUND_HEIGHT - bar,
+                BAR_WIDTH,
+                bar,
+                SKYBLUE,
+            );
+        }
+        let current = self.samples.back().copied().unwrap_or(0);
+        draw_text(format!("st {}", current), left + 4.0, top + 14.0, 16.0, WHITE);
+
+        let wave_left = left + graph_width + KEY_GAP;
+        let middle = top + SOUND_HEIGHT / 2.0;
+        let amplitude = if current > 0 {
+            volume.clamp(0.0, 1.0) * (SOUND_HEIGHT / 2.0 - 4.0)
+        } else {
+            0.0
+        };
+        let half_period = WAVE_WIDTH / (WAVE_PERIODS * 2) as f32;
+        for half in 0..WAVE_PERIODS * 2 {
+            let x = wave_left + half as f32 * half_period;
+            let y = if half % 2 == 0 {
+                middle - amplitude
+            } else {
+                middle + amplitude
+            };
+            draw_line(x, y, x + half_period, y, 1.0, GREEN);
+            if half > 0 {
+                draw_line(x, middle - amplitude, x, middle + amplitude, 1.0, GREEN);
+            }
+        }
+    }
+}
+
+/// Short messages shown in the top left corner that fade out after a few seconds.
+#[derive(Default)]
+pub(crate) struct Toasts {
+    messages: VecDeque<(String, Instant)>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, message: String) {
+        if self.messages.len() == MAX_TOASTS {
+            self.messages.pop_front();
+        }
+        self.messages.push_back((message, Instant::now()));
+    }
+
+    pub fn draw(&mut self) {
+        let now = Instant::now();
+        self.messages
+            .retain(|(_, shown)| now.duration_since(*shown) < TOAST_DURATION);
+        if self.messages.is_empty() {
+            return;
+        }
+
+        set_default_camera();
+        for (i, (message, shown)) in self.messages.iter().enumerate() {
+            let remaining = TOAST_DURATION.saturating_sub(now.duration_since(*shown));
+            let alpha = (remaining.as_secs_f32() / TOAST_FADE.as_secs_f32()).min(1.0);
+            let y = 4.0 + i as f32 * 22.0;
+            let width = measure_text(message, None, 16, 1.0).width + 12.0;
+            draw_rectangle(
+                4.0,
+                y,
+                width,
+                20.0,
+                Color {
+                    a: BACKGROUND.a * alpha,
+                    ..BACKGROUND
+                },
+            );
+            draw_text(message, 10.0, y + 14.0, 16.0, Color { a: alpha, ..WHITE });
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use thiserror::Error;
+
+use crate::{
+    constants,
+    emulator::Emulator,
+};
+
+#[derive(Error, Debug)]
+pub enum PersistError {
+    #[error("persisted RAM io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("expected {expected} bytes of persisted RAM, found {found}")]
+    WrongSize { expected: usize, found: usize },
+}
+
+/// Addresses `start..end` of a ROM's RAM kept across runs, e.g. a game's high score table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PersistRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PersistRange {
+    /// The file the range is kept in between runs of the ROM at `rom`, `game.ch8` keeps it in `game.ram`.
+    pub fn path_for_rom<P: AsRef<Path>>(rom: P) -> PathBuf {
+        rom.as_ref().with_extension("ram")
+    }
+
+    /// Parses hex addresses like `0x3F0..0x400`, the end excluded.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (start, end) = text.split_once("..")?;
+        let range = PersistRange {
+            start: parse_hex(start.trim())?,
+            end: parse_hex(end.trim())?,
+        };
+        (range.start < range.end && range.end as usize <= constants::TOTAL_RAM).then_some(range)
+    }
+
+    pub fn text(&self) -> String {
+        format!("0x{:03X}..0x{:03X}", self.start, self.end)
+    }
+
+    pub fn len(&self) -> usize {
+        (self.end - self.start) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, emulator: &Emulator, path: P) -> Result<(), PersistError> {
+        fs::write(path, &emulator.memory()[self.start as usize..self.end as usize])?;
+        Ok(())
+    }
+
+    /// Copies the bytes saved at `path` back into the range, returning false if nothing was saved yet.
+    pub fn restore<P: AsRef<Path>>(&self, emulator: &mut Emulator, path: P) -> Result<bool, PersistError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(error.into()),
+        };
+        if bytes.len() != self.len() {
+            return Err(PersistError::WrongSize {
+                expected: self.len(),
+                found: bytes.len(),
+            });
+        }
+        emulator.write_memory(self.start as usize, &bytes);
+        Ok(true)
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u16> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u16::from_str_radix(digits, 16).ok()
+}
@@ -0,0 +1,180 @@
+//! Which CHIP-8 extensions a ROM uses, found by scanning its reachable code for their instructions.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+};
+
+use crate::{
+    analysis::ControlFlow,
+    emulator::Interpreter,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+impl Platform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Chip8 => "chip-8",
+            Platform::SuperChip => "super-chip",
+            Platform::XoChip => "xo-chip",
+        }
+    }
+
+    /// The closest interpreter to run programs for this platform under, none when any of them will do.
+    pub fn interpreter(&self) -> Option<Interpreter> {
+        match self {
+            Platform::Chip8 => None,
+            Platform::SuperChip | Platform::XoChip => Some(Interpreter::SuperChip),
+        }
+    }
+}
+
+/// Instructions only later platforms understand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Extension {
+    /// 00CN, 00FB and 00FC.
+    Scroll,
+    /// 00FD.
+    Exit,
+    /// 00FE and 00FF.
+    HiresMode,
+    /// DXY0.
+    LargeSprites,
+    /// FX30.
+    LargeFont,
+    /// FX75 and FX85.
+    Flags,
+    /// 00DN.
+    ScrollUp,
+    /// 5XY2 and 5XY3.
+    RegisterRanges,
+    /// F000 NNNN.
+    LongIndex,
+    /// FN01.
+    Planes,
+    /// F002 and FX3A.
+    Audio,
+}
+
+impl Extension {
+    pub fn decode(op_code: u16) -> Option<Self> {
+        let x = (op_code & 0x0F00) >> 8;
+        match (op_code & 0xF000, op_code & 0x00FF) {
+            (0x0000, 0xFB | 0xFC) if x == 0 => Some(Extension::Scroll),
+            (0x0000, 0xFD) if x == 0 => Some(Extension::Exit),
+            (0x0000, 0xFE | 0xFF) if x == 0 => Some(Extension::HiresMode),
+            (0x0000, nn) if x == 0 && nn & 0xF0 == 0xC0 => Some(Extension::Scroll),
+            (0x0000, nn) if x == 0 && nn & 0xF0 == 0xD0 => Some(Extension::ScrollUp),
+            (0x5000, nn) if matches!(nn & 0x0F, 0x2 | 0x3) => Some(Extension::RegisterRanges),
+            (0xD000, nn) if nn & 0x0F == 0 => Some(Extension::LargeSprites),
+            (0xF000, 0x00) if x == 0 => Some(Extension::LongIndex),
+            (0xF000, 0x01) => Some(Extension::Planes),
+            (0xF000, 0x02) if x == 0 => Some(Extension::Audio),
+            (0xF000, 0x3A) => Some(Extension::Audio),
+            (0xF000, 0x30) => Some(Extension::LargeFont),
+            (0xF000, 0x75 | 0x85) => Some(Extension::Flags),
+            _ => None,
+        }
+    }
+
+    /// The first platform to introduce the instruction.
+    pub fn platform(&self) -> Platform {
+        match self {
+            Extension::Scroll
+            | Extension::Exit
+            | Extension::HiresMode
+            | Extension::LargeSprites
+            | Extension::LargeFont
+            | Extension::Flags => Platform::SuperChip,
+            Extension::ScrollUp
+            | Extension::RegisterRanges
+            | Extension::LongIndex
+            | Extension::Planes
+            | Extension::Audio => Platform::XoChip,
+        }
+    }
+
+    /// Whether the emulator runs the instruction rather than skipping it.
+    pub fn is_implemented(&self) -> bool {
+        matches!(self, Extension::Exit)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Extension::Scroll => "scrolling",
+            Extension::Exit => "exit",
+            Extension::HiresMode => "hires mode switches",
+            Extension::LargeSprites => "16x16 sprites",
+            Extension::LargeFont => "the large font",
+            Extension::Flags => "flag registers",
+            Extension::ScrollUp => "scrolling up",
+            Extension::RegisterRanges => "register range loads and stores",
+            Extension::LongIndex => "16-bit index loads",
+            Extension::Planes => "bitplanes",
+            Extension::Audio => "audio patterns",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformScan {
+    /// Every extension used and the address it's first used at.
+    extensions: BTreeMap<Extension, u16>,
+}
+
+impl PlatformScan {
+    /// Scans the instructions reachable from the entry point, so sprite data can't be mistaken for extensions.
+    pub fn scan(rom: &[u8], load_address: u16) -> Self {
+        let flow = ControlFlow::analyze(rom, load_address);
+        let mut extensions = BTreeMap::new();
+        for &address in flow.instructions() {
+            let offset = usize::from(address - load_address);
+            let op_code = u16::from_be_bytes([rom[offset], rom[offset + 1]]);
+            if let Some(extension) = Extension::decode(op_code) {
+                extensions.entry(extension).or_insert(address);
+            }
+        }
+        Self { extensions }
+    }
+
+    pub fn extensions(&self) -> &BTreeMap<Extension, u16> {
+        &self.extensions
+    }
+
+    /// Extensions the emulator would skip, with the address each is first used at.
+    pub fn unimplemented(&self) -> impl Iterator<Item = (Extension, u16)> + '_ {
+        self.extensions
+            .iter()
+            .map(|(extension, address)| (*extension, *address))
+            .filter(|(extension, _)| !extension.is_implemented())
+    }
+
+    /// The oldest platform understanding every instruction the ROM uses.
+    pub fn platform(&self) -> Platform {
+        self.extensions
+            .keys()
+            .map(Extension::platform)
+            .max()
+            .unwrap_or(Platform::Chip8)
+    }
+}
+
+impl fmt::Display for PlatformScan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.platform().name())?;
+        for (i, (extension, address)) in self.extensions.iter().enumerate() {
+            let separator = if i == 0 { " (" } else { ", " };
+            write!(f, "{}{} at 0x{:03X}", separator, extension.description(), address)?;
+        }
+        if !self.extensions.is_empty() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
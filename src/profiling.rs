@@ -0,0 +1,62 @@
+//! Per-frame puffin scopes around the frame loop, compiled out unless the `profiling` feature is on.
+
+#[cfg(feature = "profiling")]
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+#[cfg(feature = "profiling")]
+macro_rules! scope {
+    ($name:expr) => {
+        puffin::profile_scope!($name);
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+macro_rules! scope {
+    ($name:expr) => {};
+}
+
+pub(crate) use scope;
+
+/// Marks the start of a new profiler frame.
+pub(crate) fn new_frame() {
+    #[cfg(feature = "profiling")]
+    puffin::GlobalProfiler::lock().new_frame();
+}
+
+/// Collects every profiled frame while the runner is running and writes them to a `.puffin` file for
+/// `puffin_viewer` when it stops.
+#[cfg(feature = "profiling")]
+pub struct ProfileRecorder {
+    path: PathBuf,
+    view: puffin::GlobalFrameView,
+}
+
+#[cfg(feature = "profiling")]
+impl ProfileRecorder {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        puffin::set_scopes_on(true);
+        let view = puffin::GlobalFrameView::default();
+        view.lock().set_max_recent(usize::MAX);
+
+        Self {
+            path: path.as_ref().to_owned(),
+            view,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn save(&self) -> Result<(), anyhow::Error> {
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        self.view.lock().write(&mut writer)
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    io,
+    net::{
+        TcpListener,
+        ToSocketAddrs,
+    },
+    thread::{
+        self,
+        JoinHandle,
+    },
+};
+
+use anyhow::{
+    anyhow,
+    bail,
+    Error,
+};
+use serde_json::{
+    json,
+    Value,
+};
+use tungstenite::{
+    accept,
+    Message,
+};
+
+use crate::{
+    constants,
+    handle::{
+        Command,
+        Response,
+        RunnerHandle,
+    },
+    state::Savestate,
+};
+
+/// Serves the JSON debug protocol over WebSocket on a background thread, one thread per connected client.
+///
+/// Requests look like `{"cmd": "read_memory", "address": 512, "length": 16}`. Supported commands are `pause`,
+/// `resume`, `step`, `read_registers`, `read_memory`, `set_breakpoint` and `clear_breakpoint`. Every reply carries
+/// an `ok` field, and an `error` message when it is false.
+pub fn serve<A: ToSocketAddrs>(address: A, handle: RunnerHandle) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address)?;
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let Ok(mut socket) = accept(stream) else {
+                    return;
+                };
+                while let Ok(message) = socket.read() {
+                    let reply = match message {
+                        Message::Text(text) => process_message(&handle, &text),
+                        Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    if socket.send(Message::text(reply.to_string())).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }))
+}
+
+fn process_message(handle: &RunnerHandle, text: &str) -> Value {
+    match process_request(handle, text) {
+        Ok(reply) => reply,
+        Err(error) => json!({ "ok": false, "error": error.to_string() }),
+    }
+}
+
+fn process_request(handle: &RunnerHandle, text: &str) -> Result<Value, Error> {
+    let request: Value = serde_json::from_str(text)?;
+    let address = || {
+        request["address"]
+            .as_u64()
+            .map(|address| address as usize)
+            .ok_or_else(|| anyhow!("missing `address`"))
+    };
+
+    let command = match request["cmd"].as_str().unwrap_or_default() {
+        "pause" => Command::Pause,
+        "resume" => Command::Resume,
+        "step" => Command::Step,
+        "step_over" => Command::StepOver,
+        "run_to_return" => Command::RunToReturn,
+        "step_back" => Command::StepBack,
+        "set_breakpoint" => Command::SetBreakpoint(address()?),
+        "clear_breakpoint" => Command::ClearBreakpoint(address()?),
+        "read_registers" => {
+            let state = savestate(handle)?;
+            return Ok(json!({
+                "ok": true,
+                "v": state.registers,
+                "i": state.index_register,
+                "pc": state.pc,
+                "dt": state.delay_timer,
+                "st": state.sound_timer,
+                "stack": state.stack,
+            }));
+        }
+        "read_memory" => {
+            let start = address()?.min(constants::TOTAL_RAM);
+            let length = request["length"].as_u64().unwrap_or(1) as usize;
+            let end = start.saturating_add(length).min(constants::TOTAL_RAM);
+            let state = savestate(handle)?;
+            return Ok(json!({ "ok": true, "address": start, "data": &state.memory[start..end] }));
+        }
+        other => bail!("unknown command `{}`", other),
+    };
+    handle.send(command)?;
+
+    Ok(json!({ "ok": true }))
+}
+
+fn savestate(handle: &RunnerHandle) -> Result<Box<Savestate>, Error> {
+    match handle.send(Command::SaveState)? {
+        Response::State(state) => Ok(state),
+        Response::Ok => Err(anyhow!("runner did not return a savestate")),
+    }
+}
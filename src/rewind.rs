@@ -0,0 +1,104 @@
+//! Stepping backwards through the last few seconds of emulation.
+
+use std::collections::VecDeque;
+
+use anyhow::Error;
+
+use crate::{
+    emulator::{
+        Emulator,
+        KeySnapshot,
+    },
+    rng::Rng,
+    state::Savestate,
+};
+
+/// The machine right after a timer tick. No tick happens between two checkpoints, so replaying the instructions
+/// run since one against the keys held back then arrives at exactly the state they led to.
+struct Checkpoint {
+    state: Savestate,
+    rng: Rng,
+    instructions: u64,
+    /// The keys from the checkpoint on, each with the instruction count it took effect at.
+    keys: Vec<(u64, KeySnapshot)>,
+}
+
+/// Checkpoints taken after every timer tick, dropping the oldest once there are `capacity` of them.
+pub struct Rewind {
+    checkpoints: VecDeque<Checkpoint>,
+    capacity: usize,
+}
+
+impl Rewind {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            checkpoints: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Takes a checkpoint of `emulator`, to be called right after ticking its timers.
+    pub fn record(&mut self, emulator: &Emulator) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.checkpoints.len() == self.capacity {
+            self.checkpoints.pop_front();
+        }
+        self.checkpoints.push_back(Checkpoint {
+            state: emulator.savestate(),
+            rng: emulator.rng().clone(),
+            instructions: emulator.instruction_count(),
+            keys: vec![(emulator.instruction_count(), emulator.key_snapshot())],
+        });
+    }
+
+    /// Notes the keys `emulator` reports now, to be called after every keypad poll so stepping back replays the
+    /// instructions since the last checkpoint against the keys they saw.
+    pub fn record_keys(&mut self, emulator: &Emulator) {
+        let Some(checkpoint) = self.checkpoints.back_mut() else {
+            return;
+        };
+        let snapshot = emulator.key_snapshot();
+        if checkpoint.keys.last().is_some_and(|(_, keys)| *keys == snapshot) {
+            return;
+        }
+        checkpoint.keys.push((emulator.instruction_count(), snapshot));
+    }
+
+    /// Forgets every checkpoint, for when the machine changes other than by running, like loading a state.
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+    }
+
+    /// Takes `emulator` back to before the last instruction it ran by restoring the newest checkpoint from before
+    /// that instruction and replaying the ones since with the keys recorded for them. Observers and execution counts
+    /// don't see the replay. Returns false and leaves `emulator` alone when the checkpoints don't reach back that far.
+    pub fn step_back(&mut self, emulator: &mut Emulator) -> Result<bool, Error> {
+        let Some(target) = emulator.instruction_count().checked_sub(1) else {
+            return Ok(false);
+        };
+        let Some(index) = self
+            .checkpoints
+            .iter()
+            .rposition(|checkpoint| checkpoint.instructions <= target)
+        else {
+            return Ok(false);
+        };
+        // Later checkpoints belong to a future the program may not take again
+        self.checkpoints.truncate(index + 1);
+        let checkpoint = &mut self.checkpoints[index];
+        checkpoint.keys.retain(|(instructions, _)| *instructions <= target);
+        emulator.restore_checkpoint(&checkpoint.state, checkpoint.rng.clone(), checkpoint.instructions);
+        let mut keys = checkpoint.keys.iter().peekable();
+        for instruction in checkpoint.instructions..=target {
+            while let Some((_, snapshot)) = keys.next_if(|(at, _)| *at <= instruction) {
+                emulator.restore_keys(snapshot);
+            }
+            if instruction < target {
+                emulator.replay()?;
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,60 @@
+//! Random numbers for CXNN, backed by `rand` when the feature is on and a small xorshift generator otherwise.
+
+#[cfg(feature = "rand")]
+use rand::{
+    rngs::StdRng,
+    Rng as _,
+    SeedableRng,
+};
+
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(StdRng);
+
+#[cfg(feature = "rand")]
+impl Rng {
+    pub(crate) fn new() -> Self {
+        Self(StdRng::from_entropy())
+    }
+
+    /// A generator that produces the same sequence for the same seed.
+    pub(crate) fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+
+    pub(crate) fn next_u8(&mut self) -> u8 {
+        self.0.gen()
+    }
+}
+
+#[cfg(not(feature = "rand"))]
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u32,
+}
+
+#[cfg(not(feature = "rand"))]
+impl Rng {
+    pub(crate) fn new() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos())
+            .unwrap_or(0);
+        Self::seeded(nanos as u64)
+    }
+
+    /// A generator that produces the same sequence for the same seed.
+    pub(crate) fn seeded(seed: u64) -> Self {
+        // Xorshift gets stuck on zero
+        Self {
+            state: (seed ^ (seed >> 32)) as u32 | 1,
+        }
+    }
+
+    pub(crate) fn next_u8(&mut self) -> u8 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 24) as u8
+    }
+}
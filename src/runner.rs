@@ -0,0 +1,1484 @@
+use std::{
+    collections::HashSet,
+    path::{
+        Path,
+        PathBuf,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use anyhow::{
+    Context,
+    Error,
+};
+use macroquad::{
+    input::{
+        is_quit_requested,
+        prevent_quit,
+    },
+    texture::load_texture,
+    window::next_frame,
+};
+
+use crate::{
+    audio::{
+        Beep,
+        Click,
+    },
+    audiodump::AudioDump,
+    bindings::Action,
+    cheats::CheatList,
+    clock::{
+        Clock,
+        SystemClock,
+    },
+    config::Config,
+    constants,
+    crash,
+    emulator::{
+        Buzzer,
+        Emulator,
+        InputPolling,
+        KeyPad,
+        Palette,
+        Rotation,
+        ScaleFilter,
+    },
+    font::Font,
+    framedump::FrameDump,
+    handle::{
+        Command,
+        CommandReceiver,
+        Response,
+        RunnerHandle,
+    },
+    heatmap,
+    mem::Rom,
+    menu::{
+        self,
+        MenuAction,
+        PauseMenu,
+        SearchResults,
+    },
+    metrics::MetricsLog,
+    movie::InputMovie,
+    overlay::{
+        self,
+        FrameGraph,
+        SoundGraph,
+        Toasts,
+    },
+    persist::PersistRange,
+    profiling,
+    rewind::Rewind,
+    screenshot::ScreenshotOptions,
+    script::InputScript,
+    search::MemorySearch,
+    splash,
+    sprites::{
+        PendingDraw,
+        SPRITE_WIDTH,
+    },
+    state::Savestate,
+    symbols::SymbolTable,
+    watch::Watch,
+};
+
+const SPLASH_NAME: &str = "splash";
+/// Most emulated time a decoupled frame catches up on, so a stall doesn't turn into a burst of instructions.
+const MAX_CATCH_UP_MS: f64 = 250.0;
+
+/// Timer ticks of checkpoints kept for stepping backwards, ten seconds.
+const REWIND_TICKS: usize = 600;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Trigger {
+    TimerSeconds(f64),
+    /// This many instructions ran since the event became the current one.
+    InstructionCount(usize),
+    /// The hex key went down this frame, from the keyboard or injected.
+    KeyPressed(u8),
+    /// Any hex key went down this frame.
+    AnyInput,
+}
+
+impl Trigger {
+    /// Whether an event that became the current one `elapsed` ago, with `instructions` run since, fires at the end of
+    /// a frame that saw the `just_pressed` keys go down.
+    pub fn is_met(&self, elapsed: Duration, instructions: u64, just_pressed: &[bool; 16]) -> bool {
+        match *self {
+            Trigger::TimerSeconds(seconds) => elapsed.as_secs_f64() > seconds,
+            Trigger::InstructionCount(count) => instructions >= count as u64,
+            Trigger::KeyPressed(hex) => just_pressed.get(hex as usize) == Some(&true),
+            Trigger::AnyInput => just_pressed.contains(&true),
+        }
+    }
+}
+
+/// What the runner does after an event fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// Keep the event so it fires again on its next trigger.
+    Continue,
+    /// Move on to the next event.
+    RemoveEvent,
+    /// Stop the run.
+    Stop,
+}
+
+pub type EventCallback = Box<dyn Fn(&Emulator) -> Result<EventOutcome, Error>>;
+
+/// Like [`EventCallback`], but free to change the runner itself, e.g. to load the next ROM of a demo reel.
+pub type RunnerCallback = Box<dyn FnMut(&mut Runner) -> Result<EventOutcome, Error>>;
+
+/// A change to the runner an event can make without a callback, see [`RunnerEvent::action`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunnerAction {
+    SetSpeed(f64),
+    SetPalette(Palette),
+    /// Replaces the running program with the ROM at this path.
+    LoadRom(String),
+}
+
+enum EventHandler {
+    Emulator(EventCallback),
+    Runner(RunnerCallback),
+}
+
+pub struct RunnerEvent {
+    trigger: Trigger,
+    on_trigger: EventHandler,
+}
+
+impl RunnerEvent {
+    /// An error returned by `on_trigger` ends the run and is returned from [`Runner::run`].
+    pub fn new(trigger: Trigger, on_trigger: EventCallback) -> Self {
+        RunnerEvent {
+            trigger,
+            on_trigger: EventHandler::Emulator(on_trigger),
+        }
+    }
+
+    /// Like [`RunnerEvent::new`], with the whole runner handed to `on_trigger`.
+    pub fn with_runner(trigger: Trigger, on_trigger: RunnerCallback) -> Self {
+        RunnerEvent {
+            trigger,
+            on_trigger: EventHandler::Runner(on_trigger),
+        }
+    }
+
+    /// Applies `action` once `trigger` fires and moves on to the next event, so a list of these scripts an attract
+    /// mode like "run each ROM for 30 seconds at 2x, then load the next".
+    pub fn action(trigger: Trigger, action: RunnerAction) -> Self {
+        Self::with_runner(
+            trigger,
+            Box::new(move |runner| {
+                runner.apply(&action)?;
+                Ok(EventOutcome::RemoveEvent)
+            }),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInfo {
+    pub frame: u64,
+    pub frame_time: Duration,
+    pub elapsed: Duration,
+    pub instructions: usize,
+    pub timer_ticks: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Stopped,
+    EventsCompleted,
+    ProgramExited,
+}
+
+pub type FrameCallback = Box<dyn FnMut(&mut Emulator, FrameInfo)>;
+
+/// Decides the held hex keys for a frame from the emulator state and frame number, for bots and attract modes.
+pub type BotCallback = Box<dyn FnMut(&Emulator, u64) -> [bool; 16]>;
+
+/// Whether a bot plays alongside the keyboard or alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotInput {
+    Merge,
+    Override,
+}
+
+/// How emulation keeps time against presented frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pacing {
+    /// At most one instruction and one timer tick per presented frame, so the speed depends on how often frames are
+    /// presented.
+    #[default]
+    PerFrame,
+    /// Every instruction and timer tick due since the previous frame runs before presenting, so the speed holds
+    /// however long presenting blocks.
+    Decoupled,
+    /// Exactly one timer tick and its share of instructions per presented frame, never looking at the wall clock, so
+    /// the same inputs always give the same run.
+    Fixed,
+}
+
+pub struct Runner {
+    path: String,
+    emulator: Emulator,
+    events: Vec<RunnerEvent>,
+    stop_after_events: bool,
+    crash_dir: Option<PathBuf>,
+    symbols: SymbolTable,
+    on_frame: Option<FrameCallback>,
+    bot: Option<BotCallback>,
+    /// Polling asked for, the emulator falls back to once per frame while input is recorded or played back.
+    input_polling: InputPolling,
+    /// Seed of a run locked down with [`Runner::with_deterministic`].
+    deterministic_seed: Option<u64>,
+    metrics: Option<MetricsLog>,
+    frame_dump: Option<FrameDump>,
+    audio_dump: Option<AudioDump>,
+    recording: Option<InputMovie>,
+    /// The movie being played back and the frame it's at.
+    playback: Option<(InputMovie, u64)>,
+    /// The input script being run and the frame it's at.
+    script: Option<(InputScript, u64)>,
+    #[cfg(feature = "profiling")]
+    profile: Option<profiling::ProfileRecorder>,
+    frame_graph: FrameGraph,
+    show_frame_graph: bool,
+    sound_graph: SoundGraph,
+    show_sound_graph: bool,
+    show_keypad: bool,
+    watches: Vec<Watch>,
+    show_watches: bool,
+    show_heatmap: bool,
+    show_stack: bool,
+    heatmap_file: Option<PathBuf>,
+    menu: Option<PauseMenu>,
+    /// Last session offered by the resume prompt.
+    pending_session: Option<Savestate>,
+    memory_search: Option<MemorySearch>,
+    toasts: Toasts,
+    commands: Option<(RunnerHandle, CommandReceiver)>,
+    paused: bool,
+    exit_reason: Option<ExitReason>,
+    step_requested: bool,
+    /// Pauses once the stack is back down to this depth, for step over and run to return.
+    return_depth: Option<usize>,
+    rewind: Rewind,
+    skip_breakpoint: bool,
+    breakpoints: HashSet<usize>,
+    config: Config,
+    config_file: Option<PathBuf>,
+    clock: Box<dyn Clock>,
+    start: Instant,
+    /// Instruction count the current event's [`Trigger::InstructionCount`] counts from.
+    event_instructions: u64,
+    run_start: Instant,
+    pacing: Pacing,
+    /// Frames emulated without presenting them after each presented one.
+    frame_skip: u32,
+    /// Instructions and timer ticks due but not yet run while decoupled.
+    instruction_budget: f64,
+    timer_budget: f64,
+    t: Instant,
+    t_sound: Instant,
+    last_frame: Instant,
+    frame: u64,
+}
+
+impl Runner {
+    pub async fn new(path: &str, pixel_size: i32, window_size: (i32, i32)) -> Result<Self, Error> {
+        Self::open(path, Rom::load(path)?, pixel_size, window_size).await
+    }
+
+    /// Runs a ROM held in memory, like a [`Demo`](crate::demo::Demo). Cheats, symbols and saved sessions are looked
+    /// up as if `name` were the ROM's path.
+    pub async fn from_bytes(name: &str, data: &[u8], pixel_size: i32, window_size: (i32, i32)) -> Result<Self, Error> {
+        Self::open(name, Rom::from_bytes(data.to_vec())?, pixel_size, window_size).await
+    }
+
+    async fn open(path: &str, rom: Rom, pixel_size: i32, window_size: (i32, i32)) -> Result<Self, Error> {
+        let warning = rom.unimplemented_warning();
+        let mut runner = Self::from_rom(path, rom, pixel_size, window_size).await?;
+        if let Some(warning) = warning {
+            runner.notify(warning);
+        }
+        runner.load_cheats();
+        runner.load_symbols();
+        Ok(runner)
+    }
+
+    /// Starts on the built-in splash screen, for when no ROM was given.
+    pub async fn splash(pixel_size: i32, window_size: (i32, i32)) -> Result<Self, Error> {
+        Self::from_rom(SPLASH_NAME, splash::rom(), pixel_size, window_size).await
+    }
+
+    async fn from_rom(path: &str, rom: Rom, pixel_size: i32, window_size: (i32, i32)) -> Result<Self, Error> {
+        let sound = Beep::load_default().await?;
+        let click = Click::load().await?;
+
+        thread::sleep(Duration::new(1, 0));
+
+        let start = Instant::now();
+        Ok(Self {
+            path: path.to_owned(),
+            emulator: Emulator::start(rom, pixel_size, window_size, sound, click),
+            events: Vec::new(),
+            stop_after_events: false,
+            crash_dir: None,
+            symbols: SymbolTable::default(),
+            on_frame: None,
+            bot: None,
+            input_polling: InputPolling::default(),
+            deterministic_seed: None,
+            metrics: None,
+            frame_dump: None,
+            audio_dump: None,
+            recording: None,
+            playback: None,
+            script: None,
+            #[cfg(feature = "profiling")]
+            profile: None,
+            frame_graph: FrameGraph::default(),
+            show_frame_graph: false,
+            sound_graph: SoundGraph::default(),
+            show_sound_graph: false,
+            show_keypad: false,
+            watches: Vec::new(),
+            show_watches: true,
+            show_heatmap: false,
+            show_stack: false,
+            heatmap_file: None,
+            menu: None,
+            pending_session: None,
+            memory_search: None,
+            toasts: Toasts::default(),
+            commands: None,
+            paused: false,
+            exit_reason: None,
+            step_requested: false,
+            return_depth: None,
+            rewind: Rewind::new(REWIND_TICKS),
+            skip_breakpoint: false,
+            breakpoints: HashSet::new(),
+            config: Config::default(),
+            config_file: None,
+            clock: Box::new(SystemClock),
+            start,
+            event_instructions: 0,
+            run_start: start,
+            pacing: Pacing::default(),
+            frame_skip: 0,
+            instruction_budget: 0.0,
+            timer_budget: 0.0,
+            t: start - Duration::new(1337, 0),
+            t_sound: start - Duration::new(1337, 0),
+            last_frame: start,
+            frame: 0,
+        })
+    }
+
+    /// Events fire in the given order, each one relative to the previous. The runner stops once all of them have
+    /// been removed.
+    pub fn with_events(mut self, mut events: Vec<RunnerEvent>) -> Self {
+        events.reverse();
+        self.events = events;
+        self.event_instructions = self.emulator.instruction_count();
+        self.stop_after_events = true;
+        self
+    }
+
+    pub fn with_crash_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.crash_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_on_frame<F: FnMut(&mut Emulator, FrameInfo) + 'static>(mut self, on_frame: F) -> Self {
+        self.on_frame = Some(Box::new(on_frame));
+        self
+    }
+
+    pub fn with_scale_filter(mut self, filter: ScaleFilter) -> Self {
+        self.emulator.set_scale_filter(filter);
+        self
+    }
+
+    pub fn with_pixel_aspect(mut self, ratio: f32) -> Self {
+        self.emulator.set_pixel_aspect(ratio);
+        self
+    }
+
+    pub fn with_font(mut self, font: &Font) -> Self {
+        self.emulator.set_font(font);
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation, rotate_input: bool) -> Self {
+        self.emulator.set_rotation(rotation, rotate_input);
+        self
+    }
+
+    /// Draws the image behind the display, which shrinks to leave `margin` (a fraction of the window) on each side.
+    pub async fn with_bezel(mut self, path: &str, margin: f32) -> Result<Self, Error> {
+        let texture = load_texture(path).await?;
+        self.emulator.set_bezel(texture, margin);
+        Ok(self)
+    }
+
+    /// Plays this sound file as the beep instead of the `assets/beep.wav` found next to the executable or the
+    /// synthesized tone used without one.
+    pub async fn with_beep(mut self, path: &str) -> Result<Self, Error> {
+        self.emulator.set_buzzer(Box::new(Beep::load(path).await?));
+        Ok(self)
+    }
+
+    /// Applies `config`, continuing the ROM's last session or offering to if it asks for that.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.set_config(config);
+        self.restore_session();
+        self.restore_persisted_ram();
+        self
+    }
+
+    /// File the settings menu writes changes back to.
+    pub fn with_config_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: MetricsLog) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_frame_dump(mut self, frame_dump: FrameDump) -> Self {
+        self.frame_dump = Some(frame_dump);
+        self
+    }
+
+    /// Records the buzzer every timer tick and writes it to the dump's WAV file when the run ends.
+    pub fn with_audio_dump(mut self, audio_dump: AudioDump) -> Self {
+        self.audio_dump = Some(audio_dump);
+        self
+    }
+
+    /// Drives `buzzer` from the sound timer instead of playing the beep, see [`Buzzer`].
+    pub fn with_buzzer(mut self, buzzer: Box<dyn Buzzer>) -> Self {
+        self.emulator.set_buzzer(buzzer);
+        self
+    }
+
+    /// Pulses `click` for every key press while key click is on instead of playing the built-in click.
+    pub fn with_click(mut self, click: Box<dyn Buzzer>) -> Self {
+        self.emulator.set_click(click);
+        self
+    }
+
+    /// Holds the keys `bot` returns every frame the emulator runs. With [`BotInput::Override`] the keyboard is
+    /// ignored.
+    pub fn with_bot<F: FnMut(&Emulator, u64) -> [bool; 16] + 'static>(mut self, bot: F, input: BotInput) -> Self {
+        if input == BotInput::Override {
+            self.emulator.set_keypad(KeyPad::new(Box::new(|_| false)));
+        }
+        self.bot = Some(Box::new(bot));
+        self
+    }
+
+    /// Reads the hex keys from `keypad` instead of the keyboard, see [`KeyPad::new`].
+    pub fn with_keypad(mut self, keypad: KeyPad) -> Self {
+        self.emulator.set_keypad(keypad);
+        self
+    }
+
+    /// Steps emulation by elapsed time instead of once per presented frame, see [`Pacing`].
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self.t = self.clock.now();
+        self
+    }
+
+    /// Reads the time from `clock` instead of the wall clock, see [`crate::clock::VirtualClock`] for driving it from
+    /// tests. Timers and events start counting from the clock's current time.
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        let now = clock.now();
+        self.clock = Box::new(clock);
+        self.start = now;
+        self.run_start = now;
+        self.last_frame = now;
+        self.t = now - Duration::new(1337, 0);
+        self.t_sound = self.t;
+        if self.pacing == Pacing::Decoupled {
+            self.t = now;
+        }
+        self
+    }
+
+    /// Presents one frame out of every `skip + 1` and only emulates the others, without rendering or presenting them,
+    /// for machines that can't draw at 60fps. Only applies with [`Pacing::Decoupled`], which keeps the instruction and
+    /// timer counts right.
+    pub fn with_frame_skip(mut self, skip: u32) -> Self {
+        self.frame_skip = skip;
+        self
+    }
+
+    /// Reads the keypad between instructions within a frame as well, see [`InputPolling`]. Only helps with a keypad
+    /// from [`Runner::with_keypad`]. Input recording and playback keep polling once per frame so movies stay in sync.
+    pub fn with_input_polling(mut self, polling: InputPolling) -> Self {
+        self.input_polling = polling;
+        self
+    }
+
+    /// Makes the run depend on nothing but `seed`, the ROM, the settings and the keys pressed on each frame: restarts
+    /// the ROM with `seed` for CXNN, steps it with [`Pacing::Fixed`], reads the keypad once per frame and skips
+    /// resuming a saved session. Input recordings use the same seed and screenshots are named after the frame.
+    pub fn with_deterministic(mut self, seed: u64) -> Self {
+        self.deterministic_seed = Some(seed);
+        self.pending_session = None;
+        self.menu = None;
+        self.reset();
+        self.emulator.seed_rng(seed);
+        self.input_polling = InputPolling::PerFrame;
+        self.emulator.set_input_polling(InputPolling::PerFrame);
+        self.with_pacing(Pacing::Fixed)
+    }
+
+    /// Restarts the ROM with the movie's seed and holds its recorded keys frame by frame, stepping one timer tick per
+    /// frame like it was recorded.
+    pub fn with_input_playback(mut self, movie: InputMovie) -> Self {
+        self.reset();
+        self.emulator.seed_rng(movie.seed);
+        self.playback = Some((movie, 0));
+        self
+    }
+
+    /// Holds the keys of `script` frame by frame from the current state of the program.
+    pub fn with_input_script(mut self, script: InputScript) -> Self {
+        self.script = Some((script, 0));
+        self
+    }
+
+    /// Starts from the savestate at `path`, failing if it was saved with a different ROM.
+    pub fn with_savestate<P: AsRef<Path>>(mut self, path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let state = Savestate::load(path).with_context(|| format!("can't load savestate {}", path.display()))?;
+        state.check_rom(self.emulator.rom_hash())?;
+        self.emulator.load_state(&state);
+        self.pending_session = None;
+        self.menu = None;
+        Ok(self)
+    }
+
+    /// Saves how often each address was executed to `path` as a PGM image when the run ends, see [`heatmap::encode`].
+    pub fn with_heatmap_export<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.heatmap_file = Some(path.into());
+        self
+    }
+
+    /// Values shown live in the bottom right corner, toggled with F4.
+    pub fn with_watches(mut self, watches: Vec<Watch>) -> Self {
+        self.watches = watches;
+        self
+    }
+
+    pub fn add_watch(&mut self, watch: Watch) {
+        self.watches.push(watch);
+    }
+
+    /// Records puffin scopes for every frame and saves them when [`Runner::run`] returns.
+    #[cfg(feature = "profiling")]
+    pub fn with_profile(mut self, profile: profiling::ProfileRecorder) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn handle(&mut self) -> RunnerHandle {
+        let (handle, _) = self.commands.get_or_insert_with(RunnerHandle::new);
+        handle.clone()
+    }
+
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    pub fn emulator_mut(&mut self) -> &mut Emulator {
+        &mut self.emulator
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.return_depth = None;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.skip_breakpoint = true;
+    }
+
+    /// Executes a single instruction on the next frame. Only has an effect while paused.
+    pub fn step(&mut self) {
+        self.step_requested = true;
+    }
+
+    /// Runs the 2NNN call at the program counter until it returns and pauses after it, or steps a single instruction
+    /// like [`Runner::step`] when the next one isn't a call. Only has an effect while paused.
+    pub fn step_over(&mut self) {
+        if !self.paused {
+            return;
+        }
+        let is_call = self
+            .emulator
+            .memory()
+            .get(self.emulator.pc())
+            .is_some_and(|byte| byte & 0xF0 == 0x20);
+        if is_call {
+            self.run_to_depth(self.emulator.stack().len());
+        } else {
+            self.step();
+        }
+    }
+
+    /// Runs until the current subroutine returns and pauses after its 00EE. Only has an effect while paused inside a
+    /// subroutine.
+    pub fn run_to_return(&mut self) {
+        let depth = self.emulator.stack().len();
+        if self.paused && depth > 0 {
+            self.run_to_depth(depth - 1);
+        }
+    }
+
+    /// Undoes the last instruction, see [`Rewind::step_back`]. Reaches back ten seconds of emulated time since the
+    /// last reset or loaded state. Only has an effect while paused.
+    pub fn step_back(&mut self) {
+        if !self.paused {
+            return;
+        }
+        match self.rewind.step_back(&mut self.emulator) {
+            Ok(true) => {}
+            Ok(false) => self.notify("Can't step back any further"),
+            Err(error) => self.notify(format!("Can't step back: {}", error)),
+        }
+    }
+
+    /// Breakpoints still pause on the way, like they do for [`Runner::resume`].
+    fn run_to_depth(&mut self, depth: usize) {
+        self.resume();
+        self.return_depth = Some(depth);
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) {
+        self.breakpoints.remove(&address);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Shows a transient message on screen.
+    pub fn notify<S: Into<String>>(&mut self, message: S) {
+        self.toasts.push(message.into());
+    }
+
+    /// Restarts the current ROM from a clean machine state.
+    pub fn reset(&mut self) {
+        self.emulator.reset();
+        self.rewind.clear();
+    }
+
+    /// Starts recording the keypad from a reset, or stops and saves the recording next to the ROM.
+    pub fn toggle_input_recording(&mut self) {
+        match self.recording.take() {
+            Some(movie) => {
+                let path = InputMovie::path_for_rom(&self.path);
+                match movie.save(&path) {
+                    Ok(()) => self.notify(format!("Saved {} frames of input to {}", movie.len(), path.display())),
+                    Err(error) => self.notify(format!("Can't save inputs: {}", error)),
+                }
+            }
+            None => {
+                let seed = self.deterministic_seed.unwrap_or_else(|| {
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|elapsed| elapsed.as_nanos() as u64)
+                        .unwrap_or(0)
+                });
+                self.reset();
+                self.emulator.seed_rng(seed);
+                self.recording = Some(InputMovie::new(seed));
+                self.notify("Recording inputs, F6 to stop");
+            }
+        }
+    }
+
+    fn process_bot(&mut self) {
+        let Some(bot) = self.bot.as_mut() else {
+            return;
+        };
+        for (hex, pressed) in bot(&self.emulator, self.frame).into_iter().enumerate() {
+            self.emulator.inject_key(hex as u8, pressed);
+        }
+    }
+
+    /// Holds the keys of the movie being played back or the input script, before the frame's keypad poll so they
+    /// count as pressed on this frame.
+    fn play_movies(&mut self) {
+        let polling = if self.recording.is_some() || self.playback.is_some() {
+            InputPolling::PerFrame
+        } else {
+            self.input_polling
+        };
+        self.emulator.set_input_polling(polling);
+        if let Some((movie, frame)) = self.playback.as_mut() {
+            let keys = movie.keys_at(*frame);
+            *frame += 1;
+            for (hex, pressed) in keys.unwrap_or_default().into_iter().enumerate() {
+                self.emulator.inject_key(hex as u8, pressed);
+            }
+            if keys.is_none() {
+                self.playback = None;
+                self.notify("Input playback finished");
+            }
+        }
+        if let Some((script, frame)) = self.script.as_mut() {
+            let keys = script.keys_at(*frame);
+            *frame += 1;
+            for (hex, pressed) in keys.unwrap_or_default().into_iter().enumerate() {
+                self.emulator.inject_key(hex as u8, pressed);
+            }
+            if keys.is_none() {
+                self.script = None;
+                self.notify("Input script finished");
+            }
+        }
+    }
+
+    /// Adds the keys held on this frame to the recording.
+    fn record_movie(&mut self) {
+        if let Some(movie) = self.recording.as_mut() {
+            movie.push(self.emulator.pressed_keys());
+        }
+    }
+
+    /// Pacing the frame steps with, always [`Pacing::Fixed`] while input is recorded or played back so movies replay
+    /// the same way on every machine.
+    fn effective_pacing(&self) -> Pacing {
+        if self.recording.is_some() || self.playback.is_some() {
+            Pacing::Fixed
+        } else {
+            self.pacing
+        }
+    }
+
+    /// Replaces the running program with the ROM at `path`, keeping display and input settings.
+    pub fn load_rom(&mut self, path: &str) -> Result<(), Error> {
+        let rom = Rom::load(path)?;
+        if let Some(warning) = rom.unimplemented_warning() {
+            self.notify(warning);
+        }
+        self.save_persisted_ram();
+        self.emulator.load_rom(rom);
+        self.rewind.clear();
+        self.path = path.to_owned();
+        self.memory_search = None;
+        self.load_cheats();
+        self.load_symbols();
+        self.restore_persisted_ram();
+        self.clear_program_exit();
+        Ok(())
+    }
+
+    /// Switches to the built-in splash screen, e.g. after the running program exited.
+    pub fn show_splash(&mut self) {
+        self.save_persisted_ram();
+        self.emulator.load_rom(splash::rom());
+        self.rewind.clear();
+        self.path = SPLASH_NAME.to_owned();
+        self.memory_search = None;
+        self.emulator.set_cheats(CheatList::default());
+        self.symbols = SymbolTable::default();
+        self.clear_program_exit();
+    }
+
+    /// Picks up the cheat file next to the current ROM, see [`CheatList::path_for_rom`].
+    fn load_cheats(&mut self) {
+        let path = CheatList::path_for_rom(&self.path);
+        let cheats = CheatList::load_or_default(&path).unwrap_or_else(|error| {
+            log::warn!("Ignoring cheats in {}: {}", path.display(), error);
+            CheatList::default()
+        });
+        self.emulator.set_cheats(cheats);
+    }
+
+    /// Picks up the labels next to the current ROM for crash dumps, see [`SymbolTable::path_for_rom`].
+    fn load_symbols(&mut self) {
+        let path = SymbolTable::path_for_rom(&self.path);
+        self.symbols = SymbolTable::load_or_default(&path).unwrap_or_else(|error| {
+            log::warn!("Ignoring symbols in {}: {}", path.display(), error);
+            SymbolTable::default()
+        });
+    }
+
+    /// The RAM the config persists for the current ROM, see [`Config::persist_range`]. Deterministic runs and input
+    /// movies start from a clean machine and leave the saved bytes alone, so they play out the same everywhere.
+    fn persist_range(&self) -> Option<PersistRange> {
+        if self.path == SPLASH_NAME
+            || self.deterministic_seed.is_some()
+            || self.recording.is_some()
+            || self.playback.is_some()
+        {
+            return None;
+        }
+        self.config.persist_range(&self.path)
+    }
+
+    /// Copies the persisted RAM of the current ROM back from its last run.
+    fn restore_persisted_ram(&mut self) {
+        let Some(range) = self.persist_range() else {
+            return;
+        };
+        let path = PersistRange::path_for_rom(&self.path);
+        match range.restore(&mut self.emulator, &path) {
+            Ok(true) => log::info!("Restored {} bytes of RAM from {}", range.len(), path.display()),
+            Ok(false) => {}
+            Err(error) => log::warn!("Ignoring persisted RAM in {}: {}", path.display(), error),
+        }
+    }
+
+    fn save_persisted_ram(&self) {
+        let Some(range) = self.persist_range() else {
+            return;
+        };
+        let path = PersistRange::path_for_rom(&self.path);
+        if let Err(error) = range.save(&self.emulator, &path) {
+            log::warn!("Failed to save persisted RAM to {}: {}", path.display(), error);
+        }
+    }
+
+    /// Lets [`Runner::run`] be called again once a new program is loaded.
+    fn clear_program_exit(&mut self) {
+        if self.exit_reason == Some(ExitReason::ProgramExited) {
+            self.exit_reason = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.exit(ExitReason::Stopped);
+    }
+
+    fn exit(&mut self, reason: ExitReason) {
+        self.exit_reason.get_or_insert(reason);
+    }
+
+    pub fn apply(&mut self, action: &RunnerAction) -> Result<(), Error> {
+        match action {
+            RunnerAction::SetSpeed(multiplier) => self.set_speed(*multiplier),
+            RunnerAction::SetPalette(palette) => {
+                self.config.palette = *palette;
+                self.emulator.set_palette(*palette);
+            }
+            RunnerAction::LoadRom(path) => self.load_rom(path)?,
+        }
+        Ok(())
+    }
+
+    pub fn set_speed(&mut self, multiplier: f64) {
+        self.config.speed = multiplier.max(0.01);
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.config.speed
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Applies all settings in `config` to the running emulator.
+    pub fn set_config(&mut self, config: Config) {
+        let known = self.config.conflicts();
+        for conflict in config
+            .conflicts()
+            .into_iter()
+            .filter(|conflict| !known.contains(conflict))
+        {
+            log::warn!("Key conflict: {}", conflict);
+            self.notify(format!("Key conflict: {}", conflict));
+        }
+        self.emulator.set_palette(config.palette);
+        self.emulator.set_interpreter(config.interpreter);
+        self.emulator.set_volume(config.volume);
+        self.emulator.set_key_click(config.key_click);
+        self.emulator.set_draw_batching(config.draw_batching);
+        self.emulator.set_key_layout(config.keys);
+        self.emulator.set_second_key_layout(config.second_keys);
+        self.config = config;
+        self.set_speed(self.config.speed);
+    }
+
+    /// Saves the display at the configured screenshot size, tagged with the ROM name and frame unless turned off.
+    pub fn screenshot(&self, path: &str) {
+        let metadata = self.config.screenshot_metadata;
+        let options = ScreenshotOptions {
+            size: self.config.screenshot_size,
+            rom_name: metadata.then(|| {
+                Path::new(&self.path)
+                    .file_name()
+                    .map_or_else(|| self.path.clone(), |name| name.to_string_lossy().into_owned())
+            }),
+            frame: metadata.then_some(self.frame),
+        };
+        if let Err(error) = self.emulator.save_screenshot(path, &options) {
+            log::warn!("Can't save screenshot {}: {}", path, error);
+        }
+    }
+
+    /// The state saved when the loaded ROM was last closed, if there is a usable one.
+    fn last_session(&self) -> Option<Savestate> {
+        if self.path == SPLASH_NAME {
+            return None;
+        }
+        let path = Savestate::path_for_rom(&self.path);
+        if !path.exists() {
+            return None;
+        }
+        match Savestate::load(&path).and_then(|state| {
+            state.check_rom(self.emulator.rom_hash())?;
+            Ok(state)
+        }) {
+            Ok(state) => Some(state),
+            Err(error) => {
+                log::warn!("Not resuming last session from {}: {}", path.display(), error);
+                None
+            }
+        }
+    }
+
+    /// Continues the last session of the loaded ROM with resume last session on, or asks whether to with only
+    /// auto-save on.
+    fn restore_session(&mut self) {
+        if self.deterministic_seed.is_some() || (!self.config.resume_last_session && !self.config.auto_save) {
+            return;
+        }
+        let Some(state) = self.last_session() else {
+            return;
+        };
+        if self.config.resume_last_session {
+            self.emulator.load_state(&state);
+            log::info!("Resumed last session of {}", self.path);
+        } else {
+            self.pending_session = Some(state);
+            self.menu = Some(PauseMenu::resume_prompt(
+                self.config.clone(),
+                self.emulator.cheats().clone(),
+            ));
+        }
+    }
+
+    fn save_session(&self) {
+        if self.path == SPLASH_NAME {
+            return;
+        }
+        let path = Savestate::path_for_rom(&self.path);
+        match self.emulator.savestate().save(&path) {
+            Ok(()) => log::info!("Saved session to {}", path.display()),
+            Err(error) => log::warn!("Failed to save session to {}: {}", path.display(), error),
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<ExitReason, Error> {
+        // Closing the window stops the run like quitting does, so the session and persisted RAM still get saved
+        prevent_quit();
+        let result = loop {
+            if is_quit_requested() {
+                self.stop();
+            }
+            if let Some(reason) = self.exit_reason {
+                break Ok(reason);
+            }
+            if let Err(error) = self.run_frame().await {
+                break Err(error);
+            }
+        };
+
+        if (self.config.resume_last_session || self.config.auto_save) && matches!(result, Ok(ExitReason::Stopped)) {
+            self.save_session();
+        }
+        self.save_persisted_ram();
+
+        if let Some(path) = &self.heatmap_file {
+            match heatmap::save(path, self.emulator.execution_counts()) {
+                Ok(()) => log::info!("Wrote execution heatmap to {}", path.display()),
+                Err(error) => log::warn!("Failed to write execution heatmap to {}: {}", path.display(), error),
+            }
+        }
+
+        if let Some(audio_dump) = &self.audio_dump {
+            match audio_dump.save() {
+                Ok(()) => log::info!("Wrote audio to {}", audio_dump.path().display()),
+                Err(error) => log::warn!("Failed to write audio to {}: {}", audio_dump.path().display(), error),
+            }
+        }
+
+        #[cfg(feature = "profiling")]
+        if let Some(profile) = &self.profile {
+            match profile.save() {
+                Ok(()) => log::info!("Wrote profile to {}", profile.path().display()),
+                Err(error) => log::warn!("Failed to write profile to {}: {}", profile.path().display(), error),
+            }
+        }
+
+        result
+    }
+
+    fn process_commands(&mut self) {
+        let Some((_, receiver)) = &self.commands else {
+            return;
+        };
+        let pending: Vec<_> = receiver.try_iter().collect();
+        for (command, reply) in pending {
+            let response = match command {
+                Command::Pause => {
+                    self.pause();
+                    Response::Ok
+                }
+                Command::Resume => {
+                    self.resume();
+                    Response::Ok
+                }
+                Command::Stop => {
+                    self.stop();
+                    Response::Ok
+                }
+                Command::Step => {
+                    self.step();
+                    Response::Ok
+                }
+                Command::StepOver => {
+                    self.step_over();
+                    Response::Ok
+                }
+                Command::RunToReturn => {
+                    self.run_to_return();
+                    Response::Ok
+                }
+                Command::StepBack => {
+                    self.step_back();
+                    Response::Ok
+                }
+                Command::SetSpeed(multiplier) => {
+                    self.set_speed(multiplier);
+                    Response::Ok
+                }
+                Command::SetBreakpoint(address) => {
+                    self.add_breakpoint(address);
+                    Response::Ok
+                }
+                Command::ClearBreakpoint(address) => {
+                    self.remove_breakpoint(address);
+                    Response::Ok
+                }
+                Command::SaveState => Response::State(Box::new(self.emulator.savestate())),
+                Command::LoadState(state) => {
+                    self.emulator.load_state(&state);
+                    self.rewind.clear();
+                    self.notify("State loaded");
+                    Response::Ok
+                }
+                Command::InjectKey { key, pressed } => {
+                    self.emulator.inject_key(key, pressed);
+                    Response::Ok
+                }
+                Command::Screenshot(path) => {
+                    self.screenshot(&path.to_string_lossy());
+                    Response::Ok
+                }
+            };
+            let _ = reply.send(response);
+        }
+    }
+
+    async fn execute_instruction(&mut self) -> Result<(), Error> {
+        self.skip_breakpoint = false;
+        if let Err(error) = self.emulator.run() {
+            if let Some(crash_dir) = &self.crash_dir {
+                match crash::write_crash_dump(crash_dir, &self.path, &self.emulator, &self.symbols, &error) {
+                    Ok(bundle) => log::info!("Wrote crash dump to {}", bundle.display()),
+                    Err(dump_error) => log::warn!("Failed to write crash dump: {}", dump_error),
+                }
+            }
+            return Err(error.into());
+        }
+        if self.emulator.has_exited() {
+            self.exit(ExitReason::ProgramExited);
+        }
+        if self
+            .return_depth
+            .is_some_and(|depth| self.emulator.stack().len() <= depth)
+        {
+            self.pause();
+        }
+
+        Ok(())
+    }
+
+    fn tick_timers(&mut self) {
+        if let Some(audio_dump) = self.audio_dump.as_mut() {
+            audio_dump.record_tick(&self.emulator, self.config.volume);
+        }
+        self.emulator.beep();
+        self.emulator.tick_delay();
+        self.rewind.record(&self.emulator);
+    }
+
+    /// Advances emulated time by one timer tick, running the instructions that come with it at the configured speed.
+    async fn fixed_step(&mut self) -> Result<(usize, usize), Error> {
+        self.tick_timers();
+        self.instruction_budget += constants::INSTRUCTIONS_PER_TICK as f64 * self.config.speed;
+
+        let instructions = self.spend_instruction_budget().await?;
+        Ok((instructions, 1))
+    }
+
+    /// Runs whole instructions out of the budget until it's spent, a breakpoint is hit or the program pauses, returning
+    /// how many ran. Whatever is left of the budget is dropped on pausing.
+    async fn spend_instruction_budget(&mut self) -> Result<usize, Error> {
+        let mut instructions = 0;
+        while self.instruction_budget >= 1.0 && self.exit_reason.is_none() {
+            if !self.skip_breakpoint && self.breakpoints.contains(&self.emulator.pc()) {
+                self.pause();
+                self.instruction_budget = 0.0;
+                break;
+            }
+            self.instruction_budget -= 1.0;
+            instructions += 1;
+            self.execute_instruction().await?;
+            if self.paused {
+                self.instruction_budget = 0.0;
+                break;
+            }
+        }
+        Ok(instructions)
+    }
+
+    /// Runs the timer ticks and instructions that came due since the last frame, returning how many of each ran.
+    async fn catch_up(&mut self, now: Instant) -> Result<(usize, usize), Error> {
+        let elapsed = (now.duration_since(self.t).as_secs_f64() * 1000.0 * self.config.speed).min(MAX_CATCH_UP_MS);
+        self.t = now;
+        self.instruction_budget += elapsed / constants::MS_PER_INSTRUCTION;
+        self.timer_budget += elapsed / constants::MS_60HZ;
+
+        let timer_ticks = self.timer_budget as usize;
+        self.timer_budget -= timer_ticks as f64;
+        for _ in 0..timer_ticks {
+            self.tick_timers();
+        }
+
+        let instructions = self.spend_instruction_budget().await?;
+        Ok((instructions, timer_ticks))
+    }
+
+    async fn run_frame(&mut self) -> Result<(), Error> {
+        profiling::new_frame();
+        self.process_commands();
+        let running = !self.paused && self.menu.is_none();
+        if running {
+            self.process_bot();
+            self.play_movies();
+        }
+        self.emulator.begin_frame();
+        self.rewind.record_keys(&self.emulator);
+
+        let now = self.clock.now();
+        let pacing = self.effective_pacing();
+        let mut instructions = 0;
+        let mut timer_ticks = 0;
+        if running {
+            self.record_movie();
+            profiling::scope!("emulate");
+            if pacing == Pacing::Fixed {
+                (instructions, timer_ticks) = self.fixed_step().await?;
+            } else if pacing == Pacing::Decoupled {
+                (instructions, timer_ticks) = self.catch_up(now).await?;
+            } else {
+                if now.duration_since(self.t_sound).as_secs_f64() * 1000.0 * self.config.speed >= constants::MS_60HZ {
+                    profiling::scope!("timers");
+                    self.t_sound = now;
+                    self.tick_timers();
+                    timer_ticks += 1;
+                }
+                if now.duration_since(self.t).as_secs_f64() * 1000.0 * self.config.speed
+                    >= constants::MS_PER_INSTRUCTION
+                {
+                    self.t = now;
+                    if !self.skip_breakpoint && self.breakpoints.contains(&self.emulator.pc()) {
+                        self.pause();
+                    } else {
+                        instructions += 1;
+                        self.execute_instruction().await?;
+                    }
+                }
+            }
+        } else {
+            if self.step_requested {
+                instructions += 1;
+                self.execute_instruction().await?;
+            }
+            if pacing == Pacing::Decoupled {
+                self.t = now;
+            }
+        }
+        self.step_requested = false;
+        // Skipped frames are only emulated, they draw nothing and wait out their 1/60 s instead of presenting
+        let skipped = running && pacing == Pacing::Decoupled && self.frame % (self.frame_skip as u64 + 1) != 0;
+        if !skipped {
+            profiling::scope!("render");
+            self.emulator.render();
+        }
+
+        let info = FrameInfo {
+            frame: self.frame,
+            frame_time: now.duration_since(self.last_frame),
+            elapsed: now.duration_since(self.run_start),
+            instructions,
+            timer_ticks,
+        };
+        self.frame_graph.push(info);
+        self.sound_graph.push(self.emulator.sound_timer());
+        if !skipped {
+            profiling::scope!("overlays");
+            if self.show_keypad {
+                overlay::draw_keypad(&self.emulator.pressed_keys());
+            }
+            if self.show_watches {
+                overlay::draw_watches(&self.watches, &self.emulator);
+            }
+            if self.show_heatmap {
+                overlay::draw_heatmap(self.emulator.execution_counts());
+            }
+            if self.show_stack {
+                overlay::draw_stack(&self.emulator, &self.symbols);
+            }
+            if self.paused && self.menu.is_none() {
+                if let Some(draw) = PendingDraw::next(&self.emulator) {
+                    let framebuffer = self.emulator.framebuffer();
+                    // Outline only the part on the display, as if the sprite were clipped
+                    let width = (SPRITE_WIDTH as usize).min(framebuffer.width() - draw.x);
+                    let height = draw.sprite.rows.len().min(framebuffer.height() - draw.y);
+                    let target = self.emulator.cells_on_screen(draw.x, draw.y, width, height);
+                    overlay::draw_sprite_preview(&draw, target);
+                }
+            }
+            if self.show_frame_graph {
+                self.frame_graph.draw();
+            }
+            if self.show_sound_graph {
+                self.sound_graph.draw(self.config.volume);
+            }
+            if let Some(menu) = &self.menu {
+                menu.draw();
+            }
+            self.toasts.draw();
+        }
+        if let Some(on_frame) = self.on_frame.as_mut() {
+            on_frame(&mut self.emulator, info);
+        }
+        if let Some(metrics) = self.metrics.as_mut() {
+            metrics.record(&info, &self.emulator)?;
+        }
+        if let Some(frame_dump) = self.frame_dump.as_mut() {
+            frame_dump.record(&info, &self.emulator)?;
+        }
+        self.last_frame = now;
+        self.frame += 1;
+        self.emulator.end_frame();
+
+        if skipped {
+            self.clock
+                .sleep_until(now + Duration::from_secs_f64(constants::MS_60HZ / 1000.0));
+        } else {
+            profiling::scope!("present");
+            next_frame().await;
+        }
+
+        if let Some(current_event) = self.events.last() {
+            let ran = self
+                .emulator
+                .instruction_count()
+                .saturating_sub(self.event_instructions);
+            if current_event
+                .trigger
+                .is_met(now.duration_since(self.start), ran, &self.emulator.just_pressed_keys())
+            {
+                self.fire_event(now)?;
+            }
+        } else if self.stop_after_events {
+            self.exit(ExitReason::EventsCompleted);
+        }
+
+        // Keyboard state only moves on when a frame is presented, a skipped frame would see the same presses again
+        let action = match self.menu.as_mut() {
+            Some(menu) => menu.update(),
+            None if skipped => None,
+            None => {
+                self.process_hotkeys();
+                None
+            }
+        };
+        match action {
+            Some(MenuAction::Resume) => {
+                self.pending_session = None;
+                self.menu = None;
+            }
+            Some(MenuAction::ResumeSession) => {
+                if let Some(state) = self.pending_session.take() {
+                    self.emulator.load_state(&state);
+                    self.rewind.clear();
+                    self.notify("Resumed last session");
+                }
+                self.menu = None;
+            }
+            Some(MenuAction::Reset) => {
+                self.reset();
+                self.notify("Reset");
+                self.menu = None;
+            }
+            Some(MenuAction::LoadRom(path)) => {
+                if let Err(error) = self.load_rom(&path) {
+                    if let Some(menu) = self.menu.as_mut() {
+                        menu.set_error(error.to_string());
+                    }
+                } else {
+                    self.notify(format!("Loaded {}", path));
+                    self.menu = None;
+                    self.restore_session();
+                }
+            }
+            Some(MenuAction::ApplySettings(config)) => {
+                self.set_config(config);
+                if let Some(path) = &self.config_file {
+                    if let Err(error) = self.config.save(path) {
+                        if let Some(menu) = self.menu.as_mut() {
+                            menu.set_error(error.to_string());
+                        }
+                    }
+                }
+            }
+            Some(MenuAction::ApplyCheats(cheats)) => {
+                if let Err(error) = cheats.save(CheatList::path_for_rom(&self.path)) {
+                    if let Some(menu) = self.menu.as_mut() {
+                        menu.set_error(error.to_string());
+                    }
+                }
+                self.emulator.set_cheats(cheats);
+            }
+            Some(MenuAction::SearchMemory(filter)) => {
+                let memory = self.emulator.memory();
+                self.memory_search
+                    .get_or_insert_with(|| MemorySearch::new(memory))
+                    .filter(memory, filter);
+                let results = self.search_results();
+                if let Some(menu) = self.menu.as_mut() {
+                    menu.set_search_results(results);
+                }
+            }
+            Some(MenuAction::RestartSearch) => {
+                self.memory_search = None;
+                if let Some(menu) = self.menu.as_mut() {
+                    menu.set_search_results(None);
+                }
+            }
+            Some(MenuAction::Quit) => self.stop(),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn search_results(&self) -> Option<SearchResults> {
+        let search = self.memory_search.as_ref()?;
+        let memory = self.emulator.memory();
+        Some(SearchResults {
+            count: search.candidates().len(),
+            shown: search
+                .candidates()
+                .iter()
+                .take(menu::SEARCH_RESULTS_SHOWN)
+                .map(|address| (*address, memory[*address as usize]))
+                .collect(),
+        })
+    }
+
+    /// Runs the current event and restarts the clock the next timer counts from.
+    fn fire_event(&mut self, now: Instant) -> Result<(), Error> {
+        self.start = now;
+        // Taken out while it runs so a runner callback can borrow the whole runner
+        let Some(mut event) = self.events.pop() else {
+            return Ok(());
+        };
+        let outcome = match &mut event.on_trigger {
+            EventHandler::Emulator(on_trigger) => on_trigger(&self.emulator)?,
+            EventHandler::Runner(on_trigger) => on_trigger(self)?,
+        };
+        match outcome {
+            EventOutcome::Continue => self.events.push(event),
+            EventOutcome::RemoveEvent => {}
+            EventOutcome::Stop => self.stop(),
+        }
+        // After the callback, which may have loaded another ROM and started the count over
+        self.event_instructions = self.emulator.instruction_count();
+        Ok(())
+    }
+
+    fn process_hotkeys(&mut self) {
+        for action in self.config.bindings.pressed() {
+            if !self.config.game_capture || action == Action::Menu {
+                self.run_action(action);
+            }
+        }
+    }
+
+    fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Screenshot => {
+                let stem = Path::new(&self.path).file_stem().unwrap().to_string_lossy();
+                let name = match self.deterministic_seed {
+                    Some(_) => format!(".dev/{}_frame{}.png", stem, self.frame),
+                    None => format!(
+                        ".dev/{}_{}.png",
+                        stem,
+                        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+                    ),
+                };
+                self.screenshot(&name);
+                self.notify(format!("Saved screenshot to {}", name));
+            }
+            Action::PixelGrid => self.emulator.toggle_pixel_grid(),
+            Action::Keypad => self.show_keypad = !self.show_keypad,
+            Action::FrameGraph => self.show_frame_graph = !self.show_frame_graph,
+            Action::Sound => self.show_sound_graph = !self.show_sound_graph,
+            Action::Watches => self.show_watches = !self.show_watches,
+            Action::RecordInputs => self.toggle_input_recording(),
+            Action::Heatmap => self.show_heatmap = !self.show_heatmap,
+            Action::Stack => self.show_stack = !self.show_stack,
+            Action::Menu => {
+                self.emulator.silence();
+                let mut menu = PauseMenu::new(self.config.clone(), self.emulator.cheats().clone());
+                menu.set_search_results(self.search_results());
+                self.menu = Some(menu);
+            }
+            Action::Quit => self.stop(),
+        }
+    }
+}
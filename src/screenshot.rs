@@ -0,0 +1,122 @@
+//! Screenshot sizes and the text metadata embedded in saved PNGs.
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ScreenshotError {
+    #[cfg(feature = "image")]
+    #[error("encoding the screenshot failed {0}")]
+    Encoding(#[from] image::ImageError),
+
+    #[error("writing screenshot {} failed {source}", path.display())]
+    IoError { path: PathBuf, source: std::io::Error },
+
+    #[error("screenshots need the image feature")]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScreenshotSize {
+    /// One image pixel per CHIP-8 pixel, 64x32 for the standard display.
+    Native,
+    /// Every CHIP-8 pixel scaled up to the emulator's pixel size, like on screen.
+    #[default]
+    Scaled,
+}
+
+impl ScreenshotSize {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "native" => Some(ScreenshotSize::Native),
+            "scaled" => Some(ScreenshotSize::Scaled),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScreenshotSize::Native => "native",
+            ScreenshotSize::Scaled => "scaled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenshotOptions {
+    pub size: ScreenshotSize,
+    /// `Title` text chunk.
+    pub rom_name: Option<String>,
+    /// `Frame` text chunk.
+    pub frame: Option<u64>,
+}
+
+impl ScreenshotOptions {
+    /// Keyword and text pairs for the PNG, nothing unless a ROM name or frame is set.
+    pub fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut metadata = Vec::new();
+        if let Some(rom_name) = &self.rom_name {
+            metadata.push(("Title", rom_name.clone()));
+        }
+        if let Some(frame) = self.frame {
+            metadata.push(("Frame", frame.to_string()));
+        }
+        if !metadata.is_empty() {
+            metadata.push(("Software", "chip8rs".to_owned()));
+        }
+        metadata
+    }
+}
+
+/// Encodes `image` as a PNG with `metadata` as text chunks and writes it to `path`.
+#[cfg(feature = "image")]
+pub fn save_png<P: AsRef<std::path::Path>>(
+    image: &image::RgbaImage,
+    metadata: &[(&str, String)],
+    path: P,
+) -> Result<(), ScreenshotError> {
+    let mut png = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    let path = path.as_ref();
+    std::fs::write(path, add_text_chunks(&png, metadata)).map_err(|source| ScreenshotError::IoError {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Inserts a `tEXt` chunk for every keyword and text pair right after the header of an encoded PNG. Text outside
+/// Latin-1 is replaced with `?` as the chunk type requires.
+pub fn add_text_chunks(png: &[u8], metadata: &[(&str, String)]) -> Vec<u8> {
+    // 8 byte signature, then IHDR with its length, type, 13 bytes of data and CRC
+    const HEADER_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.len() < HEADER_END || metadata.is_empty() {
+        return png.to_vec();
+    }
+
+    let mut out = png[..HEADER_END].to_vec();
+    for (keyword, text) in metadata {
+        let mut chunk = b"tEXt".to_vec();
+        chunk.extend(keyword.bytes());
+        chunk.push(0);
+        chunk.extend(text.chars().map(|c| u8::try_from(c).unwrap_or(b'?')));
+        out.extend(((chunk.len() - 4) as u32).to_be_bytes());
+        out.extend(&chunk);
+        out.extend(crc32(&chunk).to_be_bytes());
+    }
+    out.extend(&png[HEADER_END..]);
+    out
+}
+
+/// The CRC-32 PNG chunks end with, over the chunk type and data.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
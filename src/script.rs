@@ -0,0 +1,161 @@
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use thiserror::Error;
+
+/// Frames a `tap` holds its key, long enough for games that only check the keypad once per loop.
+pub const TAP_FRAMES: u64 = 6;
+
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("input script io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("line {line}: expected `at frame press|tap|release key [for frames]`, got `{statement}`")]
+    InvalidStatement { line: usize, statement: String },
+}
+
+/// A hex key held from `start` until `end`, or until the script is stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hold {
+    hex: u8,
+    start: u64,
+    end: Option<u64>,
+}
+
+/// Timed keypad input written by hand, e.g. `at frame 120 press 5 for 10 frames; at 300 tap A`. Statements are
+/// separated by `;` or newlines and frames count from the start of the run.
+///
+/// - `press K` holds the key until a later `release K`, `press K for N` for N frames.
+/// - `tap K` holds the key for [`TAP_FRAMES`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputScript {
+    holds: Vec<Hold>,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut statements = Vec::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.split('#').next().unwrap_or_default();
+            for statement in content
+                .split(';')
+                .map(str::trim)
+                .filter(|statement| !statement.is_empty())
+            {
+                let invalid = || ScriptError::InvalidStatement {
+                    line,
+                    statement: statement.to_owned(),
+                };
+                statements.push(Statement::parse(statement).ok_or_else(invalid)?);
+            }
+        }
+        // Releases may be written before the press they end as long as they happen later
+        statements.sort_by_key(|statement| statement.frame);
+
+        let mut holds: Vec<Hold> = Vec::new();
+        for statement in statements {
+            let open = holds
+                .iter_mut()
+                .find(|hold| hold.hex == statement.hex && hold.end.is_none());
+            match (statement.action, open) {
+                (Action::Release, Some(hold)) => hold.end = Some(statement.frame),
+                (Action::Release, None) => {}
+                (action, open) => {
+                    if let Some(hold) = open {
+                        hold.end = Some(statement.frame);
+                    }
+                    holds.push(Hold {
+                        hex: statement.hex,
+                        start: statement.frame,
+                        end: match action {
+                            Action::Press(frames) => frames.map(|frames| statement.frame + frames),
+                            _ => Some(statement.frame + TAP_FRAMES),
+                        },
+                    });
+                }
+            }
+        }
+
+        Ok(Self { holds })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScriptError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Keys held on `frame`, `None` once every key has been released for good.
+    pub fn keys_at(&self, frame: u64) -> Option<[bool; 16]> {
+        let open = self.holds.iter().any(|hold| hold.end.is_none());
+        if !open && frame >= self.len() {
+            return None;
+        }
+
+        let mut keys = [false; 16];
+        for hold in &self.holds {
+            if hold.start <= frame && hold.end.is_none_or(|end| frame < end) {
+                keys[hold.hex as usize] = true;
+            }
+        }
+        Some(keys)
+    }
+
+    /// The frame after the last key is released.
+    pub fn len(&self) -> u64 {
+        self.holds
+            .iter()
+            .map(|hold| hold.end.unwrap_or(hold.start + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.holds.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Press(Option<u64>),
+    Tap,
+    Release,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Statement {
+    frame: u64,
+    action: Action,
+    hex: u8,
+}
+
+impl Statement {
+    fn parse(statement: &str) -> Option<Self> {
+        let words: Vec<String> = statement.split_whitespace().map(str::to_ascii_lowercase).collect();
+        let mut words = words.iter().map(String::as_str).peekable();
+
+        if words.next()? != "at" {
+            return None;
+        }
+        words.next_if(|word| *word == "frame");
+        let frame = words.next()?.parse().ok()?;
+        let action = words.next()?;
+        let hex = u8::from_str_radix(words.next()?, 16).ok().filter(|hex| *hex < 16)?;
+        let action = match (action, words.next()) {
+            ("press", None) => Action::Press(None),
+            ("press", Some("for")) => {
+                let frames = words.next()?.parse().ok()?;
+                words.next_if(|word| *word == "frames" || *word == "frame");
+                Action::Press(Some(frames))
+            }
+            ("tap", None) => Action::Tap,
+            ("release", None) => Action::Release,
+            _ => return None,
+        };
+
+        words.next().is_none().then_some(Self { frame, action, hex })
+    }
+}
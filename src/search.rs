@@ -0,0 +1,55 @@
+/// How a search narrows its candidates, comparing against the snapshot taken by the previous filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFilter {
+    Equal(u8),
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl SearchFilter {
+    fn matches(&self, previous: u8, current: u8) -> bool {
+        match self {
+            SearchFilter::Equal(value) => current == *value,
+            SearchFilter::Changed => current != previous,
+            SearchFilter::Unchanged => current == previous,
+            SearchFilter::Increased => current > previous,
+            SearchFilter::Decreased => current < previous,
+        }
+    }
+}
+
+/// Narrows down RAM addresses across snapshots to find variables like lives or score.
+#[derive(Debug, Clone)]
+pub struct MemorySearch {
+    snapshot: Vec<u8>,
+    candidates: Vec<u16>,
+}
+
+impl MemorySearch {
+    /// Starts a search with every address of `memory` as a candidate.
+    pub fn new(memory: &[u8]) -> Self {
+        Self {
+            snapshot: memory.to_vec(),
+            candidates: (0..memory.len() as u16).collect(),
+        }
+    }
+
+    pub fn filter(&mut self, memory: &[u8], filter: SearchFilter) {
+        let snapshot = &self.snapshot;
+        self.candidates.retain(|address| {
+            let idx = *address as usize;
+            match (snapshot.get(idx), memory.get(idx)) {
+                (Some(previous), Some(current)) => filter.matches(*previous, *current),
+                _ => false,
+            }
+        });
+        self.snapshot.clear();
+        self.snapshot.extend_from_slice(memory);
+    }
+
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+}
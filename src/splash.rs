@@ -0,0 +1,63 @@
+use crate::{
+    constants,
+    emulator::Interpreter,
+    mem::Rom,
+};
+
+const SCREEN_WIDTH: u8 = 64;
+const GLYPH_ADVANCE: u8 = 5;
+
+const LINES: [(&str, u8); 4] = [("CHIP-8", 2), ("NO ROM", 10), ("ESC MENU", 18), ("LOAD ROM", 25)];
+
+#[rustfmt::skip]
+const GLYPHS: [(char, [u8; 5]); 16] = [
+    ('-', [0x00, 0x00, 0xF0, 0x00, 0x00]),
+    ('8', [0xF0, 0x90, 0xF0, 0x90, 0xF0]),
+    ('A', [0x60, 0x90, 0xF0, 0x90, 0x90]),
+    ('C', [0xF0, 0x80, 0x80, 0x80, 0xF0]),
+    ('D', [0xE0, 0x90, 0x90, 0x90, 0xE0]),
+    ('E', [0xF0, 0x80, 0xF0, 0x80, 0xF0]),
+    ('H', [0x90, 0x90, 0xF0, 0x90, 0x90]),
+    ('I', [0xE0, 0x40, 0x40, 0x40, 0xE0]),
+    ('L', [0x80, 0x80, 0x80, 0x80, 0xF0]),
+    ('M', [0x90, 0xF0, 0xF0, 0x90, 0x90]),
+    ('N', [0x90, 0xD0, 0xB0, 0x90, 0x90]),
+    ('O', [0xF0, 0x90, 0x90, 0x90, 0xF0]),
+    ('P', [0xE0, 0x90, 0xE0, 0x80, 0x80]),
+    ('R', [0xE0, 0x90, 0xE0, 0xA0, 0x90]),
+    ('S', [0xF0, 0x80, 0xF0, 0x10, 0xF0]),
+    ('U', [0x90, 0x90, 0x90, 0x90, 0xF0]),
+];
+
+/// Builds the idle program shown when no ROM is loaded: draws a few centered lines of text, then loops forever.
+pub(crate) fn rom() -> Rom {
+    let draws: Vec<(usize, u8, u8)> = LINES
+        .iter()
+        .flat_map(|(text, y)| {
+            let start = (SCREEN_WIDTH + 1 - text.len() as u8 * GLYPH_ADVANCE) / 2;
+            text.chars().enumerate().filter_map(move |(i, c)| {
+                let glyph = GLYPHS.iter().position(|(glyph, _)| *glyph == c)?;
+                Some((glyph, start + i as u8 * GLYPH_ADVANCE, *y))
+            })
+        })
+        .collect();
+
+    // Every draw is LD I, LD V0, LD V1 and DRW, followed by a single JP to itself
+    let code_len = draws.len() * 8 + 2;
+    let glyph_start = constants::MEMORY_OFFSET + code_len;
+
+    let mut program = Vec::new();
+    for (glyph, x, y) in draws {
+        let address = (glyph_start + glyph * 5) as u16;
+        program.extend_from_slice(&(0xA000 | address).to_be_bytes());
+        program.extend_from_slice(&[0x60, x, 0x61, y, 0xD0, 0x15]);
+    }
+    let idle = (constants::MEMORY_OFFSET + program.len()) as u16;
+    program.extend_from_slice(&(0x1000 | idle).to_be_bytes());
+    program.extend(GLYPHS.iter().flat_map(|(_, rows)| rows));
+
+    // Addresses above assume the usual load address, whatever interpreter is configured
+    Rom::from_bytes(program)
+        .expect("splash program fits in memory")
+        .with_interpreter(Interpreter::CosmacVIP)
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use crate::{
+    emulator::{
+        Emulator,
+        InstructionData,
+    },
+    observer::Observer,
+};
+
+/// Sprites are drawn 8 pixels wide and up to 15 rows high.
+pub const SPRITE_WIDTH: u32 = 8;
+pub const MAX_SPRITE_ROWS: u32 = 15;
+
+/// Rows of a sprite as DXYN first drew them from `address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    pub address: u16,
+    pub rows: Vec<u8>,
+}
+
+impl Sprite {
+    /// One line per row, `#` for a set pixel.
+    pub fn to_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| {
+                (0..SPRITE_WIDTH)
+                    .map(|bit| if row & (0x80 >> bit) != 0 { '#' } else { '.' })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+}
+
+/// The sprite the DXYN at the program counter is about to draw and the display cell its top left corner lands on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDraw {
+    pub sprite: Sprite,
+    pub x: usize,
+    pub y: usize,
+}
+
+impl PendingDraw {
+    /// None unless the next instruction is a DXYN with at least one row.
+    pub fn next(emulator: &Emulator) -> Option<Self> {
+        let memory = emulator.memory();
+        let pc = emulator.pc();
+        let opcode = u16::from_be_bytes([*memory.get(pc)?, *memory.get(pc + 1)?]);
+        let rows = (opcode & 0xF) as usize;
+        if opcode & 0xF000 != 0xD000 || rows == 0 {
+            return None;
+        }
+        let registers = emulator.registers();
+        let framebuffer = emulator.framebuffer();
+        let address = emulator.index_register();
+        Some(Self {
+            sprite: Sprite {
+                address,
+                rows: memory.iter().skip(address as usize).take(rows).copied().collect(),
+            },
+            // The start position wraps, the sprite itself may still be clipped
+            x: registers[(opcode >> 8 & 0xF) as usize] as usize % framebuffer.width(),
+            y: registers[(opcode >> 4 & 0xF) as usize] as usize % framebuffer.height(),
+        })
+    }
+}
+
+/// Sprite rows by address and row count.
+type SpriteMap = BTreeMap<(u16, u8), Vec<u8>>;
+
+/// Collects every sprite DXYN draws, keyed by address and row count. Add a clone to an emulator as an observer and
+/// read the sprites from the one kept.
+#[derive(Debug, Clone, Default)]
+pub struct SpriteRecorder {
+    sprites: Arc<Mutex<SpriteMap>>,
+}
+
+impl SpriteRecorder {
+    /// Recorded sprites ordered by address.
+    pub fn sprites(&self) -> Vec<Sprite> {
+        self.sprites
+            .lock()
+            .map(|sprites| {
+                sprites
+                    .iter()
+                    .map(|((address, _), rows)| Sprite {
+                        address: *address,
+                        rows: rows.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// All sprites in a grid `columns` cells wide, each cell 8x15 pixels plus a pixel of spacing, scaled up by
+    /// `pixel_size`.
+    #[cfg(feature = "image")]
+    pub fn sheet(&self, columns: u32, pixel_size: u32) -> image::RgbaImage {
+        const CELL_WIDTH: u32 = SPRITE_WIDTH + 1;
+        const CELL_HEIGHT: u32 = MAX_SPRITE_ROWS + 1;
+        const SET: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+        const UNSET: image::Rgba<u8> = image::Rgba([40, 40, 40, 255]);
+        const GAP: image::Rgba<u8> = image::Rgba([0, 0, 0, 255]);
+
+        let sprites = self.sprites();
+        let columns = columns.max(1);
+        let rows = (sprites.len() as u32).div_ceil(columns).max(1);
+        image::RgbaImage::from_fn(
+            columns * CELL_WIDTH * pixel_size,
+            rows * CELL_HEIGHT * pixel_size,
+            |x, y| {
+                let (x, y) = (x / pixel_size, y / pixel_size);
+                let cell = (y / CELL_HEIGHT * columns + x / CELL_WIDTH) as usize;
+                let (column, row) = (x % CELL_WIDTH, y % CELL_HEIGHT);
+                match sprites.get(cell).and_then(|sprite| sprite.rows.get(row as usize)) {
+                    Some(bits) if column < SPRITE_WIDTH => {
+                        if bits & (0x80 >> column) != 0 {
+                            SET
+                        } else {
+                            UNSET
+                        }
+                    }
+                    _ => GAP,
+                }
+            },
+        )
+    }
+}
+
+impl Observer for SpriteRecorder {
+    fn before_instruction(&mut self, emulator: &Emulator, instruction: &InstructionData) {
+        if instruction.instruction != 0xD000 || instruction.n == 0 {
+            return;
+        }
+        let address = emulator.index_register();
+        let Ok(mut sprites) = self.sprites.lock() else {
+            return;
+        };
+        sprites.entry((address, instruction.n as u8)).or_insert_with(|| {
+            (0..instruction.n)
+                .filter_map(|offset| emulator.memory().get(address as usize + offset as usize).copied())
+                .collect()
+        });
+    }
+}
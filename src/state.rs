@@ -0,0 +1,192 @@
+use std::{
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use thiserror::Error;
+
+use crate::constants;
+
+/// Starts states written before the format had a version, read as version 1.
+const LEGACY_MAGIC: &[u8; 4] = b"C8RS";
+/// Starts versioned states, followed by the format version.
+const MAGIC: &[u8; 4] = b"C8SV";
+
+/// Bump whenever the layout changes and teach [`Savestate::from_bytes`] to migrate the previous one.
+///
+/// 1. Registers, stack and memory with the display packed into its end, and an optional trailing ROM hash.
+/// 2. Version header, a flag byte before the ROM hash and nothing allowed after it.
+/// 3. Stack depth as a `u16`, since the stack has no limit and runaway recursion goes past 255 entries.
+pub const FORMAT_VERSION: u16 = 3;
+
+#[derive(Error, Debug)]
+pub enum SavestateError {
+    #[error("savestate io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("not a chip8rs savestate")]
+    InvalidHeader,
+
+    #[error("savestate is truncated")]
+    Truncated,
+
+    #[error("savestate format version {version} is not supported, this build reads versions 1 to {FORMAT_VERSION}")]
+    UnsupportedVersion { version: u16 },
+
+    #[error("savestate has {len} unexpected bytes at the end")]
+    TrailingData { len: usize },
+
+    #[error(
+        "savestate belongs to a different ROM (saved with ROM hash {saved:016x}, the loaded ROM is {loaded:016x})"
+    )]
+    WrongRom { saved: u64, loaded: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Savestate {
+    pub(crate) memory: [u8; constants::TOTAL_RAM],
+    pub(crate) pc: u16,
+    pub(crate) index_register: u16,
+    pub(crate) delay_timer: u8,
+    pub(crate) sound_timer: u8,
+    pub(crate) registers: [u8; 16],
+    pub(crate) stack: Vec<u16>,
+    /// Hash of the ROM the state was saved with, missing in states saved before it was stored.
+    pub(crate) rom_hash: Option<u64>,
+}
+
+impl Savestate {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 36 + self.stack.len() * 2 + constants::TOTAL_RAM);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.pc.to_le_bytes());
+        bytes.extend_from_slice(&self.index_register.to_le_bytes());
+        bytes.push(self.delay_timer);
+        bytes.push(self.sound_timer);
+        bytes.extend_from_slice(&self.registers);
+        // Only the outermost 65535 return addresses of a deeper stack fit
+        bytes.extend_from_slice(&(self.stack.len().min(u16::MAX as usize) as u16).to_le_bytes());
+        for address in self.stack.iter().take(u16::MAX as usize) {
+            bytes.extend_from_slice(&address.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.memory);
+        bytes.push(self.rom_hash.is_some() as u8);
+        if let Some(rom_hash) = self.rom_hash {
+            bytes.extend_from_slice(&rom_hash.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Reads a state of any supported format version, migrating older ones to the current layout.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SavestateError> {
+        let mut reader = Reader(bytes);
+        let magic = reader.take(MAGIC.len())?;
+        let version = if magic == LEGACY_MAGIC {
+            1
+        } else if magic == MAGIC {
+            reader.u16()?
+        } else {
+            Err(SavestateError::InvalidHeader)?
+        };
+        if !(1..=FORMAT_VERSION).contains(&version) {
+            Err(SavestateError::UnsupportedVersion { version })?
+        }
+
+        let pc = reader.u16()?;
+        let index_register = reader.u16()?;
+        let delay_timer = reader.u8()?;
+        let sound_timer = reader.u8()?;
+        let mut registers = [0; 16];
+        registers.copy_from_slice(reader.take(16)?);
+        let stack_len = match version {
+            1 | 2 => reader.u8()? as usize,
+            _ => reader.u16()? as usize,
+        };
+        let stack = (0..stack_len).map(|_| reader.u16()).collect::<Result<Vec<_>, _>>()?;
+        let mut memory = [0; constants::TOTAL_RAM];
+        memory.copy_from_slice(reader.take(constants::TOTAL_RAM)?);
+        let rom_hash = match version {
+            1 if reader.0.is_empty() => None,
+            1 => Some(reader.u64()?),
+            _ => match reader.u8()? {
+                0 => None,
+                _ => Some(reader.u64()?),
+            },
+        };
+        if !reader.0.is_empty() {
+            Err(SavestateError::TrailingData { len: reader.0.len() })?
+        }
+
+        Ok(Self {
+            memory,
+            pc,
+            index_register,
+            delay_timer,
+            sound_timer,
+            registers,
+            stack,
+            rom_hash,
+        })
+    }
+
+    /// The state saved for the ROM at `rom` when it was last closed, `game.ch8` keeps it in `game.sav`.
+    pub fn path_for_rom<P: AsRef<Path>>(rom: P) -> PathBuf {
+        rom.as_ref().with_extension("sav")
+    }
+
+    pub fn rom_hash(&self) -> Option<u64> {
+        self.rom_hash
+    }
+
+    /// Fails if the state was saved with a different ROM than the one hashing to `loaded`. States without a hash
+    /// can't be checked and pass.
+    pub fn check_rom(&self, loaded: u64) -> Result<(), SavestateError> {
+        match self.rom_hash {
+            Some(saved) if saved != loaded => Err(SavestateError::WrongRom { saved, loaded }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SavestateError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SavestateError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SavestateError> {
+        if self.0.len() < len {
+            Err(SavestateError::Truncated)?
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, SavestateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SavestateError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, SavestateError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
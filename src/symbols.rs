@@ -0,0 +1,114 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SymbolError {
+    #[error("symbol file io failed {0}")]
+    IoError(#[from] io::Error),
+
+    #[error("line {line}: expected `address label`")]
+    InvalidLine { line: usize },
+
+    #[error("line {line}: invalid address `{value}`")]
+    InvalidAddress { line: usize, value: String },
+}
+
+/// Labels for ROM addresses, stored next to the ROM as `address label` lines with hex addresses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    labels: BTreeMap<u16, String>,
+}
+
+impl SymbolTable {
+    /// The symbol file belonging to the ROM at `rom`, `game.ch8` keeps its labels in `game.sym`.
+    pub fn path_for_rom<P: AsRef<Path>>(rom: P) -> PathBuf {
+        rom.as_ref().with_extension("sym")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SymbolError> {
+        let mut labels = BTreeMap::new();
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = raw.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let mut fields = content.split_whitespace();
+            let (Some(address), Some(label), None) = (fields.next(), fields.next(), fields.next()) else {
+                Err(SymbolError::InvalidLine { line })?
+            };
+            let digits = address
+                .strip_prefix("0x")
+                .or_else(|| address.strip_prefix("0X"))
+                .unwrap_or(address);
+            let address = u16::from_str_radix(digits, 16)
+                .ok()
+                .filter(|address| *address <= 0xFFF)
+                .ok_or_else(|| SymbolError::InvalidAddress {
+                    line,
+                    value: address.to_owned(),
+                })?;
+            labels.insert(address, label.trim_end_matches(':').to_owned());
+        }
+
+        Ok(Self { labels })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SymbolError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The table as a symbol file, one `0x2A4 draw_score` line per label in address order, which [`Self::parse`]
+    /// reads back unchanged.
+    pub fn to_text(&self) -> String {
+        self.labels
+            .iter()
+            .map(|(address, label)| format!("0x{:03X} {}\n", address, label))
+            .collect()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SymbolError> {
+        Ok(fs::write(path, self.to_text())?)
+    }
+
+    /// Loads the labels at `path`, an empty table if the ROM has none.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<Self, SymbolError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn insert(&mut self, address: u16, label: &str) {
+        self.labels.insert(address, label.to_owned());
+    }
+
+    /// The label placed exactly at `address`.
+    pub fn label(&self, address: u16) -> Option<&str> {
+        self.labels.get(&address).map(String::as_str)
+    }
+
+    /// `address` relative to the closest label at or before it, e.g. `draw_score+4`.
+    pub fn locate(&self, address: u16) -> Option<String> {
+        let (start, label) = self.labels.range(..=address).next_back()?;
+        Some(match address - start {
+            0 => label.clone(),
+            offset => format!("{}+{}", label, offset),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    constants,
+    emulator::Emulator,
+};
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("invalid watch `{0}`, expected v0-vf, i, [address] or w[address]")]
+pub struct WatchError(String);
+
+/// A value shown live in the watch overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    Register(u8),
+    Index,
+    /// The byte at an address.
+    Byte(u16),
+    /// The big-endian 16-bit word starting at an address.
+    Word(u16),
+}
+
+impl Watch {
+    pub fn read(&self, emulator: &Emulator) -> u16 {
+        let memory = emulator.memory();
+        let byte = |address: u16| memory.get(address as usize).copied().unwrap_or(0) as u16;
+        match *self {
+            Watch::Register(register) => emulator.registers()[register as usize] as u16,
+            Watch::Index => emulator.index_register(),
+            Watch::Byte(address) => byte(address),
+            Watch::Word(address) => (byte(address) << 8) | byte(address.wrapping_add(1)),
+        }
+    }
+
+    /// Whether the value fits in a byte, so the overlay can size its hex output.
+    pub fn is_byte(&self) -> bool {
+        matches!(self, Watch::Register(_) | Watch::Byte(_))
+    }
+}
+
+impl FromStr for Watch {
+    type Err = WatchError;
+
+    /// Parses `v0`-`vf`, `i`, `[0x300]` for a byte or `w[0x300]` for a word, addresses in hex.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || WatchError(value.to_owned());
+        let expression = value.trim().to_ascii_lowercase();
+        let address = |brackets: &str| {
+            let hex = brackets.strip_prefix('[')?.strip_suffix(']')?;
+            let address = u16::from_str_radix(hex.strip_prefix("0x").unwrap_or(hex), 16).ok()?;
+            ((address as usize) < constants::TOTAL_RAM).then_some(address)
+        };
+
+        if expression == "i" {
+            return Ok(Watch::Index);
+        }
+        if let Some(register) = expression.strip_prefix('v') {
+            return match u8::from_str_radix(register, 16) {
+                Ok(register) if register < 16 => Ok(Watch::Register(register)),
+                _ => Err(invalid()),
+            };
+        }
+        if let Some(brackets) = expression.strip_prefix('w') {
+            return address(brackets).map(Watch::Word).ok_or_else(invalid);
+        }
+        address(&expression).map(Watch::Byte).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Watch::Register(register) => write!(f, "V{:X}", register),
+            Watch::Index => write!(f, "I"),
+            Watch::Byte(address) => write!(f, "[0x{:03X}]", address),
+            Watch::Word(address) => write!(f, "w[0x{:03X}]", address),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+        System,
+    },
+    cell::Cell,
+    fs,
+};
+
+use chip8rs::emulator::Emulator;
+
+struct CountingAllocator;
+
+thread_local! {
+    // Per thread, the test harness allocates on its own threads while the test runs
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn steady_state_execution_does_not_allocate() {
+    for path in ["assets/roms/test/3-corax+.ch8", "assets/roms/test/4-flags.ch8"] {
+        let mut emulator = Emulator::headless(&fs::read(path).unwrap()).unwrap();
+        // Fill the instruction history before measuring
+        for _ in 0..100 {
+            emulator.step().unwrap();
+        }
+
+        let before = ALLOCATIONS.with(Cell::get);
+        emulator.run_for(10_000).unwrap();
+        let allocations = ALLOCATIONS.with(Cell::get) - before;
+
+        assert_eq!(allocations, 0, "{} allocated {} times", path, allocations);
+    }
+}
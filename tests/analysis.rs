@@ -0,0 +1,71 @@
+use chip8rs::{
+    analysis::ControlFlow,
+    symbols::SymbolTable,
+};
+
+#[test]
+fn control_flow_separates_code_from_data() {
+    #[rustfmt::skip]
+    let rom = [
+        0x22, 0x08, // call 0x208
+        0x12, 0x02, // loop
+        0xF0, 0x90, 0x90, 0xF0, // sprite
+        0x30, 0x00, // skip if V0 == 0
+        0x13, 0x00, // jump out of the program
+        0x00, 0xEE, // return
+    ];
+    let flow = ControlFlow::analyze(&rom, 0x200);
+
+    assert_eq!(flow.subroutines().keys().copied().collect::<Vec<_>>(), [0x200, 0x208]);
+    assert_eq!(
+        flow.subroutines()[&0x200].calls.iter().copied().collect::<Vec<_>>(),
+        [0x208]
+    );
+    assert_eq!(
+        flow.data_ranges(),
+        vec![std::ops::Range {
+            start: 0x204,
+            end: 0x208
+        }]
+    );
+
+    let mut symbols = SymbolTable::default();
+    symbols.insert(0x208, "check");
+    let dot = flow.to_dot(&symbols);
+    assert!(dot.contains(r#""0x208" [label="check\n0x208"];"#), "{}", dot);
+    assert!(dot.contains(r#""0x200" -> "0x208";"#), "{}", dot);
+    assert!(dot.contains(r#""0x208" -> "0x300" [color=red];"#), "{}", dot);
+}
+
+#[test]
+fn control_flow_reads_long_loads_as_one_instruction() {
+    #[rustfmt::skip]
+    let rom = [
+        0x30, 0x00, // skip if V0 == 0
+        0xF0, 0x00, 0x12, 0x00, // load I with 0x1200
+        0x12, 0x08, // loop
+        0x12, 0x08,
+    ];
+    let flow = ControlFlow::analyze(&rom, 0x200);
+
+    assert_eq!(
+        flow.instructions().iter().copied().collect::<Vec<_>>(),
+        [0x200, 0x202, 0x206, 0x208]
+    );
+    assert!(flow.data_ranges().is_empty(), "{:?}", flow.data_ranges());
+}
+
+#[test]
+fn control_flow_stops_at_the_end_of_the_address_space() {
+    let mut rom = vec![0u8; 0x10000];
+    rom[..2].copy_from_slice(&[0x12, 0x00]);
+    let flow = ControlFlow::analyze(&rom, 0x200);
+
+    assert_eq!(
+        flow.data_ranges(),
+        vec![std::ops::Range {
+            start: 0x202,
+            end: 0xFFFF
+        }]
+    );
+}
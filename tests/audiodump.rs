@@ -0,0 +1,55 @@
+use chip8rs::{
+    audiodump::{
+        self,
+        AudioDump,
+        TICK_SAMPLES,
+    },
+    emulator::Emulator,
+};
+
+fn samples(wav: &[u8]) -> Vec<i16> {
+    wav[44..]
+        .chunks(2)
+        .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect()
+}
+
+#[test]
+fn encodes_a_tick_of_audio_per_tick() {
+    let wav = audiodump::encode(&[0.0, 1.0, 0.5]);
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 44_100);
+
+    let samples = samples(&wav);
+    assert_eq!(samples.len() as u64, 3 * TICK_SAMPLES);
+    let (silent, rest) = samples.split_at(TICK_SAMPLES as usize);
+    let (loud, quiet) = rest.split_at(TICK_SAMPLES as usize);
+    assert!(silent.iter().all(|sample| *sample == 0));
+    let peak = |tick: &[i16]| tick.iter().map(|sample| sample.unsigned_abs()).max().unwrap();
+    assert!(peak(loud) > 0);
+    assert_eq!(peak(quiet), peak(loud) / 2);
+}
+
+#[test]
+fn a_sound_timer_of_one_plays_one_tick() {
+    // V0 = 1, sound timer = V0
+    let mut emulator = Emulator::headless(&[0x60, 0x01, 0xF0, 0x18, 0x12, 0x04]).unwrap();
+    emulator.step().unwrap();
+    emulator.step().unwrap();
+
+    let path = std::env::temp_dir().join(format!("chip8rs_audiodump_{}.wav", std::process::id()));
+    let mut dump = AudioDump::new(&path);
+    for _ in 0..3 {
+        dump.record_tick(&emulator, 1.0);
+        emulator.beep();
+    }
+    dump.save().unwrap();
+    let samples = samples(&std::fs::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(samples.len() as u64, 3 * TICK_SAMPLES);
+    let (tone, silence) = samples.split_at(TICK_SAMPLES as usize);
+    assert!(tone.iter().any(|sample| *sample != 0));
+    assert!(silence.iter().all(|sample| *sample == 0));
+}
@@ -0,0 +1,52 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use chip8rs::emulator::Emulator;
+
+#[test]
+fn buzzer_follows_the_sound_timer() {
+    #[rustfmt::skip]
+    let rom = [
+        0x60, 0x02, // V0 = 2
+        0xF0, 0x18, // sound timer = V0
+        0xF0, 0x18, // sound timer = V0 while buzzing
+        0x12, 0x06, // loop
+    ];
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    let recorded = transitions.clone();
+    emulator.set_buzzer(Box::new(move |on| recorded.lock().unwrap().push(on)));
+
+    for _ in 0..3 {
+        emulator.step().unwrap();
+    }
+    assert_eq!(*transitions.lock().unwrap(), [true]);
+
+    emulator.beep();
+    assert_eq!(*transitions.lock().unwrap(), [true]);
+    emulator.beep();
+    emulator.beep();
+    assert_eq!(*transitions.lock().unwrap(), [true, false]);
+}
+
+#[test]
+fn key_click_pulses_its_buzzer_once_per_press() {
+    let pulses = Arc::new(Mutex::new(Vec::new()));
+    let mut emulator = Emulator::headless(&[0x12, 0x00]).unwrap();
+    let recorded = pulses.clone();
+    emulator.set_click(Box::new(move |on| recorded.lock().unwrap().push(on)));
+
+    emulator.inject_key(0x5, true);
+    emulator.begin_frame();
+    assert!(pulses.lock().unwrap().is_empty(), "key click is off");
+
+    emulator.set_key_click(true);
+    emulator.inject_key(0x5, false);
+    emulator.begin_frame();
+    emulator.inject_key(0x5, true);
+    emulator.begin_frame();
+    emulator.begin_frame();
+    assert_eq!(*pulses.lock().unwrap(), [true, false]);
+}
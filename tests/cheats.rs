@@ -0,0 +1,35 @@
+use chip8rs::{
+    cheats::CheatList,
+    emulator::Emulator,
+};
+
+#[test]
+fn cheats_round_trip_through_text() {
+    let cheats = CheatList::parse("# lives\ninfinite lives = 0x3A2 0x03\nlevel skip = 2F0 9 off\n").unwrap();
+
+    assert_eq!(cheats.cheats.len(), 2);
+    assert_eq!((cheats.cheats[0].address, cheats.cheats[0].value), (0x3A2, 0x03));
+    assert!(!cheats.cheats[1].enabled);
+    assert_eq!(CheatList::parse(&cheats.to_text()).unwrap(), cheats);
+
+    assert!(CheatList::parse("lives = 0x1000 1").is_err());
+    assert!(CheatList::parse("lives = 0x300 0x100").is_err());
+    assert!(CheatList::parse("lives = 0x300").is_err());
+}
+
+#[test]
+fn enabled_cheats_freeze_memory() {
+    // LD I, 0x300; LD V0, [I]; SE V0, 9; then a JP to itself, one address further when the skip was taken
+    let rom = [0xA3, 0x00, 0xF0, 0x65, 0x30, 0x09, 0x12, 0x06, 0x12, 0x08];
+    let run = |cheats: &str| {
+        let mut emulator = Emulator::headless(&rom).unwrap();
+        emulator.set_cheats(CheatList::parse(cheats).unwrap());
+        for _ in 0..5 {
+            emulator.step().unwrap();
+        }
+        emulator.pc()
+    };
+
+    assert_eq!(run("nine = 0x300 0x09"), 0x208);
+    assert_eq!(run("nine = 0x300 0x09 off"), 0x206);
+}
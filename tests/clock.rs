@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use chip8rs::{
+    clock::{
+        Clock,
+        VirtualClock,
+    },
+    emulator::Emulator,
+    runner::Trigger,
+};
+
+#[test]
+fn virtual_clock_moves_only_when_told() {
+    let clock = VirtualClock::new();
+    let runner_view = clock.clone();
+    let start = runner_view.now();
+    assert_eq!(runner_view.now(), start);
+
+    clock.advance(Duration::from_micros(16_667));
+    assert_eq!(runner_view.now() - start, Duration::from_micros(16_667));
+
+    // Sleeping jumps ahead but never back
+    runner_view.sleep_until(start + Duration::from_secs(2));
+    assert_eq!(clock.now() - start, Duration::from_secs(2));
+    runner_view.sleep_until(start);
+    assert_eq!(clock.now() - start, Duration::from_secs(2));
+}
+
+#[test]
+fn triggers_fire_on_virtual_time_without_waiting() {
+    let clock = VirtualClock::new();
+    let start = clock.now();
+    let mut emulator = Emulator::headless(&[0x12, 0x00]).unwrap();
+    let mut fired = Vec::new();
+
+    for frame in 1..=150 {
+        emulator.inject_key(0xA, frame == 30);
+        emulator.begin_frame();
+        emulator.run_for(12).unwrap();
+        emulator.end_frame();
+        clock.advance(Duration::from_micros(16_667));
+
+        let elapsed = clock.now() - start;
+        let just_pressed = emulator.just_pressed_keys();
+        for (name, trigger) in [
+            ("timer", Trigger::TimerSeconds(2.0)),
+            ("instructions", Trigger::InstructionCount(100)),
+            ("key", Trigger::KeyPressed(0xA)),
+        ] {
+            if trigger.is_met(elapsed, emulator.instruction_count(), &just_pressed)
+                && !fired.iter().any(|(fired_name, _)| *fired_name == name)
+            {
+                fired.push((name, frame));
+            }
+        }
+    }
+
+    assert_eq!(fired, [("instructions", 9), ("key", 30), ("timer", 120)]);
+}
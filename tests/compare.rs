@@ -1,184 +1,190 @@
 mod compare {
-
     use std::{
+        env,
         fs,
-        path::Path,
+        io,
+        path::PathBuf,
     };
 
     use chip8rs::{
-        emulator,
-        RunnerEvent,
-    };
-    use image::DynamicImage;
-    use image_compare::{
-        Algorithm,
-        Similarity,
-    };
-    use macroquad::{
-        miniquad::window::set_fullscreen,
-        window::{
-            next_frame,
-            request_new_screen_size,
+        display::Framebuffer,
+        emulator::{
+            Emulator,
+            Interpreter,
         },
     };
 
-    pub const SCREEN_WIDTH: i32 = 64;
-    pub const SCREEN_HEIGHT: i32 = 32;
-    pub const PIXEL_SIZE: i32 = 10;
-
-    pub async fn set_window_conf() {
-        set_fullscreen(false);
-        request_new_screen_size((SCREEN_WIDTH * PIXEL_SIZE) as f32, (SCREEN_HEIGHT * PIXEL_SIZE) as f32);
-
-        next_frame().await;
-        next_frame().await;
+    pub const MANIFEST: &str = "tests/baseline/manifest.txt";
+    /// Two seconds at the default speed, long enough for every test ROM to finish drawing.
+    const DEFAULT_INSTRUCTIONS: u64 = 1400;
+    const MISMATCHES_SHOWN: usize = 10;
+
+    /// A ROM run headless and the baseline its display is compared against, one line of [`MANIFEST`].
+    pub struct Scenario {
+        pub baseline: String,
+        rom: String,
+        instructions: u64,
+        interpreter: Interpreter,
     }
 
-    pub async fn run_emulator(rom_path: &str, events: &mut Option<Vec<RunnerEvent>>) {
-        set_window_conf().await;
+    /// Reads `baseline | rom [| instructions [| interpreter]]` lines, skipping blanks and `#` comments.
+    pub fn load_manifest(path: &str) -> Vec<Scenario> {
+        let text = fs::read_to_string(path).unwrap();
+        text.lines()
+            .enumerate()
+            .filter_map(|(idx, raw)| {
+                let content = raw.split('#').next().unwrap_or_default().trim();
+                (!content.is_empty()).then(|| parse_scenario(content, idx + 1))
+            })
+            .collect()
+    }
 
-        if (chip8rs::run(rom_path.into(), PIXEL_SIZE, (SCREEN_WIDTH, SCREEN_HEIGHT), events).await).is_err() {
-            panic!();
+    fn parse_scenario(content: &str, line: usize) -> Scenario {
+        let fields: Vec<&str> = content.split('|').map(str::trim).collect();
+        let (baseline, rom) = match fields[..] {
+            [baseline, rom, ..] if fields.len() <= 4 => (baseline, rom),
+            _ => panic!(
+                "{} line {}: expected `baseline | rom [| instructions [| interpreter]]`",
+                MANIFEST, line
+            ),
+        };
+        let instructions = fields.get(2).map_or(DEFAULT_INSTRUCTIONS, |value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} line {}: invalid instruction count `{}`", MANIFEST, line, value))
+        });
+        let interpreter = fields.get(3).map_or(Interpreter::default(), |value| {
+            Interpreter::from_name(value)
+                .unwrap_or_else(|| panic!("{} line {}: unknown interpreter `{}`", MANIFEST, line, value))
+        });
+
+        Scenario {
+            baseline: baseline.to_owned(),
+            rom: rom.to_owned(),
+            instructions,
+            interpreter,
         }
     }
 
-    pub fn compare_images(first: DynamicImage, second: DynamicImage) -> Similarity {
-        image_compare::rgb_similarity_structure(&Algorithm::MSSIMSimple, &first.into_rgb8(), &second.into_rgb8())
-            .unwrap()
+    /// Where baselines are read from and written to, `CHIP8RS_BASELINE_DIR` or `tests/baseline`.
+    fn baseline_dir() -> PathBuf {
+        env::var_os("CHIP8RS_BASELINE_DIR").map_or_else(|| PathBuf::from("tests/baseline"), PathBuf::from)
     }
 
-    pub fn save_screenshot(emulator: &emulator::Emulator, path: &str) {
-        let path = Path::new(path);
-        if let Some(folders) = path.parent() {
-            let _ = fs::create_dir_all(folders);
-        }
-        emulator.export_render_target(path.to_str().unwrap());
+    /// Differing pixels a comparison still passes with, `CHIP8RS_COMPARE_TOLERANCE` or none.
+    fn tolerance() -> usize {
+        env::var("CHIP8RS_COMPARE_TOLERANCE").map_or(0, |value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid CHIP8RS_COMPARE_TOLERANCE `{}`", value))
+        })
     }
-}
-#[cfg(test)]
-mod test {
-
-    use std::env;
-
-    use chip8rs::RunnerEvent;
 
-    use crate::compare::{
-        self,
-        run_emulator,
-        save_screenshot,
-    };
-
-    #[macroquad::test]
-    async fn comparison_tests() {
-        let generated_identifier: String = env::var("GIT_SHA").unwrap_or("local".to_string());
-        let tolerance = 0.0001;
-        compare_chip8_logo(generated_identifier.clone(), tolerance).await;
-        compare_ibm(generated_identifier.clone(), tolerance).await;
-        compare_corax(generated_identifier.clone(), tolerance).await;
-        compare_flags(generated_identifier.clone(), tolerance).await;
+    /// The display as a PPM image with lit pixels white and mismatched ones red.
+    fn diff_image(framebuffer: &Framebuffer, mismatches: &[(usize, usize)]) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", framebuffer.width(), framebuffer.height()).into_bytes();
+        for y in 0..framebuffer.height() {
+            for x in 0..framebuffer.width() {
+                let pixel = match (mismatches.contains(&(x, y)), framebuffer.get(x, y)) {
+                    (true, _) => [255, 0, 0],
+                    (false, true) => [255, 255, 255],
+                    (false, false) => [0, 0, 0],
+                };
+                bytes.extend_from_slice(&pixel);
+            }
+        }
+        bytes
     }
 
-    async fn compare_chip8_logo(generated_identifier: String, tolerance: f64) {
-        let path = r"assets/roms/test/1-chip8-logo.ch8";
-        let mut events = Some(vec![RunnerEvent::new(chip8rs::Trigger::TimerSeconds(2.0), {
-            let generated_identifier = generated_identifier.clone();
-            Box::new(move |emulator| {
-                save_screenshot(
-                    emulator,
-                    &format!("tests/generated/1-chip8-logo/{}.png", generated_identifier),
-                )
-            })
-        })]);
-
-        run_emulator(path, &mut events).await;
-
-        let baseline = image::open("tests/baseline/1-chip8-logo.png").unwrap();
-        let generated = image::open(format!("tests/generated/1-chip8-logo/{}.png", generated_identifier)).unwrap();
-
-        let comparison_result = compare::compare_images(baseline, generated);
-
-        assert!(
-            1.0 - &comparison_result.score < tolerance,
-            "Chip8 logo comparison score too low: {}",
-            comparison_result.score
-        );
+    pub fn run_headless(scenario: &Scenario) -> Emulator {
+        let rom = fs::read(&scenario.rom).unwrap_or_else(|error| panic!("can't read {}: {}", scenario.rom, error));
+        let mut emulator = Emulator::headless(&rom).unwrap();
+        emulator.set_interpreter(scenario.interpreter);
+        emulator.run_for(scenario.instructions as usize).unwrap();
+        emulator
     }
 
-    async fn compare_ibm(generated_identifier: String, tolerance: f64) {
-        let path = r"assets/roms/test/IBM Logo.ch8";
-        let mut events = Some(vec![RunnerEvent::new(chip8rs::Trigger::TimerSeconds(2.0), {
-            let generated_identifier = generated_identifier.clone();
-            Box::new(move |emulator| {
-                save_screenshot(
-                    emulator,
-                    &format!("tests/generated/IBM Logo/{}.png", generated_identifier),
-                )
+    /// Pixels that differ between the display and a baseline written by [`Framebuffer::to_bits`].
+    pub fn mismatches(framebuffer: &Framebuffer, baseline: &[u8]) -> Vec<(usize, usize)> {
+        let row_len = framebuffer.width().div_ceil(8);
+        (0..framebuffer.height())
+            .flat_map(|y| (0..framebuffer.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| {
+                let expected = baseline
+                    .get(y * row_len + x / 8)
+                    .is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0);
+                framebuffer.get(*x, *y) != expected
             })
-        })]);
-
-        run_emulator(path, &mut events).await;
-
-        let baseline = image::open("tests/baseline/IBM Logo.png").unwrap();
-        let generated = image::open(format!("tests/generated/IBM Logo/{}.png", generated_identifier)).unwrap();
-
-        let comparison_result = compare::compare_images(baseline, generated);
-
-        assert!(
-            1.0 - &comparison_result.score < tolerance,
-            "IBM logo comparison score too low: {}",
-            comparison_result.score
-        );
+            .collect()
     }
 
-    async fn compare_corax(generated_identifier: String, tolerance: f64) {
-        let path = r"assets/roms/test/3-corax+.ch8";
-        let mut events = Some(vec![RunnerEvent::new(chip8rs::Trigger::TimerSeconds(2.0), {
-            let generated_identifier = generated_identifier.clone();
-            Box::new(move |emulator| {
-                save_screenshot(
-                    emulator,
-                    &format!("tests/generated/3-corax+/{}.png", generated_identifier),
-                )
-            })
-        })]);
-
-        run_emulator(path, &mut events).await;
-
-        let baseline = image::open("tests/baseline/corax.png").unwrap();
-        let generated = image::open(format!("tests/generated/3-corax+/{}.png", generated_identifier)).unwrap();
-
-        let comparison_result = compare::compare_images(baseline, generated);
+    /// Rewrites the baseline with the current display when `CHIP8RS_UPDATE_BASELINES` is set, returning how many
+    /// pixels changed, and compares against it otherwise.
+    pub fn check(scenario: &Scenario) -> Result<String, String> {
+        let emulator = run_headless(scenario);
+        let framebuffer = emulator.framebuffer();
+        let path = baseline_dir().join(&scenario.baseline);
+
+        if env::var_os("CHIP8RS_UPDATE_BASELINES").is_some() {
+            let summary = match fs::read(&path) {
+                Ok(previous) => match mismatches(framebuffer, &previous).len() {
+                    0 => "unchanged".to_string(),
+                    changed => format!("{} pixels changed", changed),
+                },
+                Err(error) if error.kind() == io::ErrorKind::NotFound => "new".to_string(),
+                Err(error) => return Err(format!("can't read {}: {}", path.display(), error)),
+            };
+            fs::write(&path, framebuffer.to_bits())
+                .map_err(|error| format!("can't write {}: {}", path.display(), error))?;
+            return Ok(summary);
+        }
 
-        assert!(
-            1.0 - &comparison_result.score < tolerance,
-            "Corax+ comparison score too low: {}",
-            comparison_result.score
-        );
+        let expected = fs::read(&path).map_err(|error| format!("can't read {}: {}", path.display(), error))?;
+        if expected.len() != framebuffer.to_bits().len() {
+            return Err(format!("{} has the wrong size", path.display()));
+        }
+        let mismatches = mismatches(framebuffer, &expected);
+        if mismatches.len() > tolerance() {
+            let diff = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.diff.ppm", scenario.baseline));
+            fs::write(&diff, diff_image(framebuffer, &mismatches))
+                .map_err(|error| format!("can't write {}: {}", diff.display(), error))?;
+            return Err(format!(
+                "{} pixels differ, first at (x, y) {:?}, see {}",
+                mismatches.len(),
+                &mismatches[..mismatches.len().min(MISMATCHES_SHOWN)],
+                diff.display()
+            ));
+        }
+        Ok("ok".to_string())
     }
+}
 
-    async fn compare_flags(generated_identifier: String, tolerance: f64) {
-        let path = r"assets/roms/test/4-flags.ch8";
-        let mut events = Some(vec![RunnerEvent::new(chip8rs::Trigger::TimerSeconds(2.0), {
-            let generated_identifier = generated_identifier.clone();
-            Box::new(move |emulator| {
-                save_screenshot(
-                    emulator,
-                    &format!("tests/generated/4-flags/{}.png", generated_identifier),
-                )
-            })
-        })]);
-
-        run_emulator(path, &mut events).await;
-
-        let baseline = image::open("tests/baseline/4-flags.png").unwrap();
-        let generated = image::open(format!("tests/generated/4-flags/{}.png", generated_identifier)).unwrap();
-
-        let comparison_result = compare::compare_images(baseline, generated);
+#[cfg(test)]
+mod test {
+    use crate::compare::{
+        check,
+        load_manifest,
+        MANIFEST,
+    };
 
+    #[test]
+    fn compare_baselines() {
+        let scenarios = load_manifest(MANIFEST);
+        assert!(!scenarios.is_empty(), "{} lists no comparisons", MANIFEST);
+
+        let mut failures = Vec::new();
+        for scenario in &scenarios {
+            match check(scenario) {
+                Ok(summary) => println!("{}: {}", scenario.baseline, summary),
+                Err(error) => failures.push(format!("{}: {}", scenario.baseline, error)),
+            }
+        }
         assert!(
-            1.0 - &comparison_result.score < tolerance,
-            "Flags comparison score too low: {}",
-            comparison_result.score
+            failures.is_empty(),
+            "{} of {} comparisons failed, rerun with CHIP8RS_UPDATE_BASELINES=1 if the change is intended:\n{}",
+            failures.len(),
+            scenarios.len(),
+            failures.join("\n")
         );
     }
 }
@@ -0,0 +1,85 @@
+use chip8rs::{
+    bindings::{
+        Action,
+        Binding,
+        Conflict,
+    },
+    config::Config,
+    emulator::{
+        Interpreter,
+        Palette,
+    },
+    screenshot::ScreenshotSize,
+};
+
+#[test]
+fn config_round_trips_through_text() {
+    let mut config = Config {
+        speed: 1.5,
+        palette: Palette::Amber,
+        interpreter: Interpreter::CosmacVIP,
+        volume: 0.3,
+        key_click: true,
+        resume_last_session: true,
+        auto_save: true,
+        game_capture: true,
+        screenshot_size: ScreenshotSize::Native,
+        screenshot_metadata: false,
+        ..Default::default()
+    };
+    config.keys[0][0] = chip8rs::config::key_from_name("kp7").unwrap();
+    config.second_keys = Some(Config::default().keys);
+    config.second_keys.as_mut().unwrap()[3][3] = chip8rs::config::key_from_name("kpenter").unwrap();
+    config.bindings.set(Action::Screenshot, Binding::parse("shift+f12"));
+    config.bindings.set(Action::PixelGrid, None);
+
+    assert_eq!(Config::parse(&config.to_text()).unwrap(), config);
+}
+
+#[test]
+fn conflicting_shortcuts_are_reported() {
+    let mut config = Config::default();
+    assert!(config.conflicts().is_empty());
+
+    let x = chip8rs::config::key_from_name("x").unwrap();
+    config.bindings.set(Action::Screenshot, Some(Binding::new(x)));
+    config.bindings.set(Action::Heatmap, Binding::parse("escape"));
+    assert_eq!(
+        config.conflicts(),
+        [
+            Conflict::Keypad {
+                action: Action::Screenshot,
+                key: x
+            },
+            Conflict::Shortcuts {
+                first: Action::Heatmap,
+                second: Action::Menu
+            },
+        ]
+    );
+
+    // Only the pause menu key is live while the game captures input
+    config.game_capture = true;
+    assert!(config.conflicts().is_empty());
+    config.bindings.set(Action::Menu, Some(Binding::new(x)));
+    assert_eq!(
+        config.conflicts(),
+        [Conflict::Keypad {
+            action: Action::Menu,
+            key: x
+        }]
+    );
+}
+
+#[test]
+fn config_rejects_invalid_values() {
+    assert!(Config::parse("speed = 1\n# comment\n\npalette = paper").is_ok());
+    assert!(Config::parse("volume = 2").is_err());
+    assert!(Config::parse("keys = 1 2 3").is_err());
+    assert!(Config::parse("second_keys = kp7 kp8").is_err());
+    assert!(Config::parse("key_click = yes").is_err());
+    assert!(Config::parse("colour = green").is_err());
+    assert!(Config::parse("speed").is_err());
+    assert!(Config::parse("bind.screenshot = f13").is_err());
+    assert!(Config::parse("bind.rewind = r").is_err());
+}
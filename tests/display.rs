@@ -0,0 +1,162 @@
+use chip8rs::{
+    display::{
+        draw_sprite,
+        DisplayDelta,
+        Framebuffer,
+        PixelChange,
+    },
+    emulator::Emulator,
+};
+
+fn lit(fb: &Framebuffer) -> Vec<(usize, usize)> {
+    (0..fb.height())
+        .flat_map(|y| (0..fb.width()).map(move |x| (x, y)))
+        .filter(|(x, y)| fb.get(*x, *y))
+        .collect()
+}
+
+#[test]
+fn draws_most_significant_bit_leftmost() {
+    let mut fb = Framebuffer::new(64, 32);
+
+    assert!(!draw_sprite(&mut fb, 0, 0, &[0b1000_0001, 0b0100_0000]));
+    assert_eq!(lit(&fb), vec![(0, 0), (7, 0), (1, 1)]);
+}
+
+#[test]
+fn draws_at_unaligned_positions() {
+    for x in 0..16 {
+        let mut fb = Framebuffer::new(64, 32);
+        draw_sprite(&mut fb, x, 5, &[0xFF]);
+
+        assert_eq!(
+            lit(&fb),
+            (x..x + 8).map(|col| (col, 5)).collect::<Vec<_>>(),
+            "x = {}",
+            x
+        );
+    }
+}
+
+#[test]
+fn xor_erases_and_sets_collision() {
+    let mut fb = Framebuffer::new(64, 32);
+    let sprite = [0xF0, 0x90, 0xF0];
+
+    assert!(!draw_sprite(&mut fb, 10, 10, &sprite));
+    assert!(draw_sprite(&mut fb, 10, 10, &sprite));
+    assert!(lit(&fb).is_empty());
+}
+
+#[test]
+fn partial_overlap_collides_only_on_lit_pixels() {
+    let mut fb = Framebuffer::new(64, 32);
+    draw_sprite(&mut fb, 0, 0, &[0b1000_0000]);
+
+    assert!(!draw_sprite(&mut fb, 1, 0, &[0b1000_0000]));
+    assert!(draw_sprite(&mut fb, 0, 0, &[0b1100_0000]));
+    assert!(lit(&fb).is_empty());
+}
+
+#[test]
+fn clips_at_right_and_bottom_edges() {
+    let mut fb = Framebuffer::new(64, 32);
+
+    draw_sprite(&mut fb, 60, 30, &[0xFF, 0xFF, 0xFF]);
+    assert_eq!(
+        lit(&fb),
+        vec![
+            (60, 30),
+            (61, 30),
+            (62, 30),
+            (63, 30),
+            (60, 31),
+            (61, 31),
+            (62, 31),
+            (63, 31)
+        ]
+    );
+}
+
+#[test]
+fn wraps_start_position() {
+    let mut fb = Framebuffer::new(64, 32);
+
+    draw_sprite(&mut fb, 64 + 2, 32 + 3, &[0x80]);
+    assert_eq!(lit(&fb), vec![(2, 3)]);
+}
+
+#[test]
+fn empty_sprite_draws_nothing() {
+    let mut fb = Framebuffer::new(64, 32);
+
+    assert!(!draw_sprite(&mut fb, 0, 0, &[]));
+    assert!(lit(&fb).is_empty());
+}
+
+#[test]
+fn tracks_dirty_state() {
+    let mut fb = Framebuffer::new(64, 32);
+    assert!(fb.take_dirty());
+    assert!(!fb.is_dirty());
+
+    draw_sprite(&mut fb, 0, 0, &[0x80]);
+    assert!(fb.take_dirty());
+
+    fb.clear();
+    assert!(fb.take_dirty());
+    assert!(lit(&fb).is_empty());
+}
+
+#[test]
+fn delta_lists_only_changed_pixels() {
+    let mut fb = Framebuffer::new(64, 32);
+    let mut delta = DisplayDelta::new();
+
+    let first = delta.update(&fb);
+    assert!(first.full);
+    assert_eq!(first.pixels.len(), 64 * 32);
+    assert!(delta.update(&fb).is_empty());
+
+    draw_sprite(&mut fb, 3, 4, &[0b1000_0000, 0b0100_0000]);
+    let changes = delta.update(&fb);
+    assert!(!changes.full);
+    assert_eq!(
+        changes.pixels,
+        [
+            PixelChange { x: 3, y: 4, lit: true },
+            PixelChange { x: 4, y: 5, lit: true }
+        ]
+    );
+    assert_eq!(changes.rows(), [4, 5]);
+
+    fb.clear();
+    assert_eq!(
+        delta
+            .update(&fb)
+            .pixels
+            .iter()
+            .map(|change| change.lit)
+            .collect::<Vec<_>>(),
+        [false, false]
+    );
+}
+
+#[test]
+fn draw_batching_shows_changes_on_timer_ticks() {
+    // I = font 0, draw it, erase it, draw it again, loop
+    let mut emulator = Emulator::headless(&[0xF0, 0x29, 0xD0, 0x05, 0xD0, 0x05, 0xD0, 0x05, 0x12, 0x08]).unwrap();
+    emulator.set_draw_batching(true);
+
+    for _ in 0..3 {
+        emulator.step().unwrap();
+    }
+    emulator.tick_delay();
+    assert!(lit(emulator.displayed()).is_empty());
+
+    emulator.step().unwrap();
+    assert!(lit(emulator.displayed()).is_empty());
+    emulator.tick_delay();
+    assert_eq!(lit(emulator.displayed()), lit(emulator.framebuffer()));
+    assert!(!lit(emulator.displayed()).is_empty());
+}
@@ -0,0 +1,37 @@
+use chip8rs::env::Env;
+
+// Draws a random hex digit at a random position every few instructions, forever
+const RANDOM_DIGITS: [u8; 12] = [0xC0, 0x0F, 0xC1, 0x3F, 0xC2, 0x1F, 0xF0, 0x29, 0xD1, 0x25, 0x12, 0x00];
+
+fn episode(seed: u64) -> Vec<Vec<u8>> {
+    let mut env = Env::new(&RANDOM_DIGITS).unwrap().with_seed(seed).with_max_steps(30);
+    let mut observations = vec![env.reset()];
+    loop {
+        let (observation, done) = env.step([false; 16]);
+        observations.push(observation);
+        if done {
+            return observations;
+        }
+    }
+}
+
+#[test]
+fn episodes_are_deterministic_per_seed() {
+    let first = episode(7);
+
+    assert_eq!(first.len(), 31);
+    assert!(first.iter().all(|observation| observation.len() == 64 * 32));
+    assert!(first.last().unwrap().contains(&1));
+    assert_eq!(first, episode(7));
+    assert_ne!(first, episode(8));
+}
+
+#[test]
+fn exiting_ends_the_episode() {
+    // EXIT
+    let mut env = Env::new(&[0x00, 0xFD]).unwrap();
+    env.reset();
+
+    assert_eq!(env.observation_shape(), (64, 32));
+    assert!(env.step([false; 16]).1);
+}
@@ -0,0 +1,22 @@
+use chip8rs::{
+    display::{
+        draw_sprite,
+        Framebuffer,
+    },
+    framedump,
+};
+
+#[test]
+fn encodes_rows_most_significant_bit_first() {
+    let mut fb = Framebuffer::new(64, 32);
+    draw_sprite(&mut fb, 9, 1, &[0b1100_0000]);
+
+    let bytes = framedump::encode(&fb);
+    let header = b"P4\n64 32\n";
+    assert_eq!(&bytes[..header.len()], header);
+
+    let rows = &bytes[header.len()..];
+    assert_eq!(rows.len(), 8 * 32);
+    assert_eq!(rows.iter().filter(|byte| **byte != 0).count(), 1);
+    assert_eq!(rows[8 + 1], 0b0110_0000);
+}
@@ -0,0 +1,29 @@
+use chip8rs::{
+    emulator::Emulator,
+    heatmap,
+};
+
+#[test]
+fn execution_counts_show_the_hot_loop() {
+    #[rustfmt::skip]
+    let rom = [
+        0x60, 0x00, // V0 = 0
+        0x70, 0x01, // V0 += 1
+        0x12, 0x02, // loop
+    ];
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    for _ in 0..21 {
+        emulator.step().unwrap();
+    }
+
+    assert_eq!(emulator.execution_counts()[0x200..0x206], [1, 0, 10, 0, 10, 0]);
+
+    let image = heatmap::encode(emulator.execution_counts());
+    let header = b"P5\n64 64\n255\n";
+    assert_eq!(&image[..header.len()], header);
+    assert_eq!(image.len(), header.len() + 4096);
+    assert_eq!(image[header.len() + 0x202], 255);
+
+    emulator.reset();
+    assert!(emulator.execution_counts().iter().all(|count| *count == 0));
+}
@@ -0,0 +1,84 @@
+use chip8rs::emulator::{
+    Emulator,
+    Interpreter,
+};
+
+#[test]
+fn hires_boot_sequence_selects_64x64_display() {
+    // 0x200: JP 0x260, padding up to 0x2C0 where the program draws the font's 0 at (0, 40)
+    let mut rom = vec![0x12, 0x60];
+    rom.resize(0xC0, 0);
+    rom.extend_from_slice(&[0x60, 0x00, 0x61, 0x28, 0xF0, 0x29, 0xD0, 0x15, 0x12, 0xC8]);
+
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    assert_eq!(emulator.interpreter(), Interpreter::HiresVIP);
+    assert_eq!(
+        (emulator.framebuffer().width(), emulator.framebuffer().height()),
+        (64, 64)
+    );
+
+    for _ in 0..5 {
+        emulator.step().unwrap();
+    }
+    assert_eq!(emulator.pc(), 0x2C8);
+    assert!(emulator.framebuffer().get(0, 40));
+}
+
+#[test]
+fn selected_interpreter_applies_without_boot_sequence() {
+    let mut emulator = Emulator::headless(&[0x00, 0xE0]).unwrap();
+    assert_eq!(emulator.framebuffer().height(), 32);
+
+    emulator.set_interpreter(Interpreter::HiresVIP);
+    assert_eq!(emulator.framebuffer().height(), 64);
+    emulator.set_interpreter(Interpreter::CosmacVIP);
+    assert_eq!(emulator.framebuffer().height(), 32);
+}
+
+#[test]
+fn eti_660_loads_programs_at_0x600() {
+    // LD V0, 0x2A then a JP to itself that only holds at the ETI-660 load address
+    let mut emulator = Emulator::headless(&[0x60, 0x2A, 0x16, 0x02]).unwrap();
+    emulator.set_interpreter(Interpreter::Eti660);
+    assert_eq!(emulator.pc(), 0x600);
+    assert_eq!(
+        (emulator.framebuffer().width(), emulator.framebuffer().height()),
+        (64, 48)
+    );
+
+    for _ in 0..3 {
+        emulator.step().unwrap();
+    }
+    assert_eq!(emulator.pc(), 0x602);
+}
+
+#[test]
+fn vip_register_loads_read_consecutive_bytes_and_advance_i() {
+    // LD I, 0x206; LD V2, [I]; data 0x01 0x02 0x03
+    let rom = [0xA2, 0x06, 0xF2, 0x65, 0x00, 0x00, 0x01, 0x02, 0x03];
+    for (interpreter, index) in [(Interpreter::CosmacVIP, 0x209), (Interpreter::SuperChip, 0x206)] {
+        let mut emulator = Emulator::headless(&rom).unwrap();
+        emulator.set_interpreter(interpreter);
+        emulator.step().unwrap();
+        emulator.step().unwrap();
+
+        assert_eq!(emulator.registers()[..3], [1, 2, 3], "{:?}", interpreter);
+        assert_eq!(emulator.index_register(), index, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn vip_register_stores_write_consecutive_bytes_and_advance_i() {
+    // LD V0, 1; LD V1, 2; LD V2, 3; LD I, 0x300; LD [I], V2
+    let rom = [0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0xA3, 0x00, 0xF2, 0x55];
+    for (interpreter, index) in [(Interpreter::CosmacVIP, 0x303), (Interpreter::SuperChip, 0x300)] {
+        let mut emulator = Emulator::headless(&rom).unwrap();
+        emulator.set_interpreter(interpreter);
+        for _ in 0..5 {
+            emulator.step().unwrap();
+        }
+
+        assert_eq!(emulator.memory()[0x300..0x304], [1, 2, 3, 0], "{:?}", interpreter);
+        assert_eq!(emulator.index_register(), index, "{:?}", interpreter);
+    }
+}
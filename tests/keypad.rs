@@ -0,0 +1,76 @@
+use std::sync::{
+    atomic::{
+        AtomicU16,
+        Ordering,
+    },
+    Arc,
+};
+
+use chip8rs::emulator::{
+    Emulator,
+    InputPolling,
+    KeyPad,
+};
+
+#[test]
+fn custom_key_source_drives_the_keypad() {
+    let held = Arc::new(AtomicU16::new(0));
+    let source = {
+        let held = held.clone();
+        move |hex: u8| held.load(Ordering::Relaxed) & (1 << hex) != 0
+    };
+    // LD V0, K
+    let mut emulator = Emulator::headless(&[0xF0, 0x0A]).unwrap();
+    emulator.set_keypad(KeyPad::new(Box::new(source)));
+
+    held.store(1 << 7, Ordering::Relaxed);
+    emulator.begin_frame();
+    emulator.step().unwrap();
+    assert!(emulator.pressed_keys()[7]);
+    assert_eq!(emulator.pc(), 0x200);
+    emulator.end_frame();
+
+    held.store(0, Ordering::Relaxed);
+    emulator.begin_frame();
+    emulator.step().unwrap();
+    assert_eq!((emulator.pc(), emulator.registers()[0]), (0x202, 7));
+}
+
+#[test]
+fn just_pressed_keys_only_report_the_first_frame() {
+    let mut emulator = Emulator::headless(&[0x12, 0x00]).unwrap();
+    emulator.inject_key(0xA, true);
+    emulator.begin_frame();
+    assert_eq!(emulator.just_pressed_keys(), std::array::from_fn(|hex| hex == 0xA));
+
+    emulator.end_frame();
+    emulator.begin_frame();
+    assert!(!emulator.just_pressed_keys().contains(&true));
+}
+
+#[test]
+fn mid_frame_polling_sees_keys_change_within_a_frame() {
+    let held = Arc::new(AtomicU16::new(0));
+    let source = {
+        let held = held.clone();
+        move |hex: u8| held.load(Ordering::Relaxed) & (1 << hex) != 0
+    };
+    // JP 0x200
+    let mut emulator = Emulator::headless(&[0x12, 0x00]).unwrap();
+    emulator.set_keypad(KeyPad::new(Box::new(source)));
+
+    emulator.begin_frame();
+    emulator.step().unwrap();
+    held.store(1 << 3, Ordering::Relaxed);
+    emulator.step().unwrap();
+    assert!(!emulator.pressed_keys()[3]);
+    emulator.end_frame();
+
+    emulator.set_input_polling(InputPolling::EveryInstructions(1));
+    held.store(0, Ordering::Relaxed);
+    emulator.begin_frame();
+    emulator.step().unwrap();
+    held.store(1 << 3, Ordering::Relaxed);
+    emulator.step().unwrap();
+    assert!(emulator.pressed_keys()[3]);
+}
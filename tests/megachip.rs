@@ -0,0 +1,68 @@
+use chip8rs::{
+    emulator::Palette,
+    megachip::{
+        BlendMode,
+        MegaChip,
+        HEIGHT,
+        WIDTH,
+    },
+};
+
+#[test]
+fn megachip_draws_palette_sprites_on_the_next_frame() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0x11,             // MegaChip mode on
+        0x01, 0x00, 0x02, 0x18, // I = 0x000218
+        0x02, 0x02,             // load 2 palette colors
+        0x03, 0x02,             // sprites 2 wide
+        0x04, 0x01,             // and 1 high
+        0x01, 0x00, 0x02, 0x20, // I = 0x000220
+        0x60, 0x03,             // V0 = 3
+        0xD0, 0x01,             // draw at 3, 3
+        0x00, 0xE0,             // show the frame
+        0x12, 0x16,             // loop
+        0xFF, 0xFF, 0x00, 0x00, // red
+        0xFF, 0x00, 0x00, 0xFF, // blue
+        0x01, 0x02,             // sprite
+    ];
+    let mut machine = MegaChip::new(&rom).unwrap();
+    machine.run_for(9).unwrap();
+
+    assert!(machine.is_mega());
+    assert_eq!(machine.palette()[1..3], [0xFFFF_0000, 0xFF00_00FF]);
+    assert_eq!(machine.blend_mode(), BlendMode::Normal);
+    let rgba = machine.rgba();
+    assert_eq!(rgba.len(), WIDTH * HEIGHT * 4);
+    let pixel = |x: usize, y: usize| &rgba[(y * WIDTH + x) * 4..(y * WIDTH + x) * 4 + 4];
+    assert_eq!(pixel(3, 3), [0xFF, 0x00, 0x00, 0xFF]);
+    assert_eq!(pixel(4, 3), [0x00, 0x00, 0xFF, 0xFF]);
+    assert_eq!(pixel(5, 3), [0x00, 0x00, 0x00, 0xFF]);
+    assert!(machine.unimplemented_opcodes().is_empty());
+}
+
+#[test]
+fn megachip_draws_chip8_sprites_in_the_palette_and_skips_long_loads() {
+    #[rustfmt::skip]
+    let rom = [
+        0x30, 0x00,             // skip the long load, V0 is 0
+        0x01, 0x00, 0x00, 0x00, // I = 0x000000
+        0xA2, 0x0E,             // I = 0x20E
+        0xD0, 0x01,             // draw at 0, 0
+        0x00, 0xFD,             // exit
+        0x00, 0x00,
+        0x80,                   // sprite
+    ];
+    let mut machine = MegaChip::new(&rom).unwrap();
+    machine.set_palette(Palette::Amber);
+    machine.run_for(10).unwrap();
+
+    assert!(machine.has_exited());
+    assert_eq!(machine.instruction_count(), 4);
+    assert_eq!(machine.index_register(), 0x20E);
+    let rgba = machine.rgba();
+    let pixel = |x: usize, y: usize| &rgba[(y * WIDTH + x) * 4..(y * WIDTH + x) * 4 + 4];
+    // The 64x32 display is scaled up 4 times and centered vertically
+    assert_eq!(pixel(3, 32 + 3), [0xFF, 0xAF, 0x00, 0xFF]);
+    assert_eq!(pixel(4, 32), [0x00, 0x00, 0x00, 0xFF]);
+}
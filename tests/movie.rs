@@ -0,0 +1,37 @@
+use chip8rs::movie::{
+    InputMovie,
+    MovieError,
+};
+
+fn keys(held: &[usize]) -> [bool; 16] {
+    std::array::from_fn(|hex| held.contains(&hex))
+}
+
+#[test]
+fn movie_round_trips_through_text() {
+    let mut movie = InputMovie::new(42);
+    for held in [&[][..], &[], &[5], &[5, 0xA], &[5, 0xA], &[]] {
+        movie.push(keys(held));
+    }
+
+    let text = movie.to_text();
+    assert_eq!(text, "version = 1\nseed = 42\n0 -\n2 5\n3 5 A\n5 -\n");
+    assert_eq!(InputMovie::parse(&text).unwrap(), movie);
+    assert_eq!(movie.keys_at(4), Some(keys(&[5, 0xA])));
+    assert_eq!(movie.keys_at(6), None);
+}
+
+#[test]
+fn movie_rejects_invalid_lines() {
+    assert!(InputMovie::parse("# comment\nseed = 1\n0 -\n10 f").is_ok());
+    assert!(InputMovie::parse("seed = x").is_err());
+    assert!(InputMovie::parse("version = 1\nseed = 1").is_ok());
+    assert!(matches!(
+        InputMovie::parse("version = 2"),
+        Err(MovieError::UnsupportedVersion { line: 1, version: 2 })
+    ));
+    assert!(InputMovie::parse("0 G").is_err());
+    assert!(InputMovie::parse("0 10").is_err());
+    assert!(InputMovie::parse("5 1\n3 2").is_err());
+    assert!(InputMovie::parse("keys").is_err());
+}
@@ -0,0 +1,43 @@
+use std::{
+    fs,
+    thread,
+};
+
+use chip8rs::{
+    display::Framebuffer,
+    emulator::Emulator,
+};
+
+fn run(rom: &[u8]) -> Framebuffer {
+    let mut emulator = Emulator::headless(rom).unwrap();
+    for _ in 0..50_000 {
+        emulator.step().unwrap();
+    }
+    emulator.framebuffer().clone()
+}
+
+#[test]
+fn instances_run_independently_on_threads() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Emulator>();
+
+    let roms: Vec<Vec<u8>> = ["assets/roms/test/3-corax+.ch8", "assets/roms/test/4-flags.ch8"]
+        .iter()
+        .map(|path| fs::read(path).unwrap())
+        .collect();
+    let expected: Vec<Framebuffer> = roms.iter().map(|rom| run(rom)).collect();
+
+    let parallel: Vec<Framebuffer> = thread::scope(|scope| {
+        let workers: Vec<_> = roms
+            .iter()
+            .cycle()
+            .take(8)
+            .map(|rom| scope.spawn(move || run(rom)))
+            .collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+
+    for (idx, framebuffer) in parallel.iter().enumerate() {
+        assert_eq!(framebuffer, &expected[idx % roms.len()], "instance {}", idx);
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use chip8rs::{
+    emulator::Emulator,
+    observer::{
+        EmulatorEvent,
+        Observer,
+    },
+};
+
+struct Recorder(Arc<Mutex<Vec<EmulatorEvent>>>);
+
+impl Observer for Recorder {
+    fn on_event(&mut self, _emulator: &Emulator, event: &EmulatorEvent) {
+        self.0.lock().unwrap().push(*event);
+    }
+}
+
+#[test]
+fn observers_receive_emulator_events() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0xE0, // clear
+        0x22, 0x08, // call 0x208
+        0xF0, 0x0A, // wait for a key
+        0x12, 0x06, // loop
+        0x60, 0x05, // V0 = 5
+        0xF0, 0x18, // sound timer = V0
+        0xD0, 0x01, // draw at V0, V0
+        0x00, 0xEE, // return
+    ];
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    emulator.add_observer(Box::new(Recorder(events.clone())));
+    for _ in 0..8 {
+        emulator.step().unwrap();
+    }
+    for _ in 0..5 {
+        emulator.beep();
+    }
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            EmulatorEvent::ScreenCleared,
+            EmulatorEvent::StackPushed(0x204),
+            EmulatorEvent::SoundStarted,
+            EmulatorEvent::SpriteDrawn {
+                x: 5,
+                y: 5,
+                collision: false
+            },
+            EmulatorEvent::StackPopped(0x204),
+            EmulatorEvent::WaitingForKey,
+            EmulatorEvent::SoundStopped,
+        ]
+    );
+}
+
+#[test]
+fn setting_timers_reports_transitions() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut emulator = Emulator::headless(&[0x12, 0x00]).unwrap();
+    emulator.add_observer(Box::new(Recorder(events.clone())));
+
+    emulator.set_sound_timer(10);
+    emulator.set_sound_timer(4);
+    emulator.set_sound_timer(0);
+    emulator.set_delay_timer(2);
+    emulator.tick_delay();
+    emulator.tick_delay();
+    emulator.set_delay_timer(3);
+    emulator.set_delay_timer(0);
+
+    assert_eq!((emulator.sound_timer(), emulator.delay_timer()), (0, 0));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            EmulatorEvent::SoundStarted,
+            EmulatorEvent::SoundStopped,
+            EmulatorEvent::DelayExpired,
+            EmulatorEvent::DelayExpired,
+        ]
+    );
+}
@@ -0,0 +1,364 @@
+use chip8rs::emulator::{
+    Emulator,
+    Interpreter,
+};
+
+/// The two quirk sets, every instruction is checked under both.
+const QUIRKS: [Interpreter; 2] = [Interpreter::CosmacVIP, Interpreter::SuperChip];
+
+fn run(interpreter: Interpreter, program: &[u8], steps: usize) -> Emulator {
+    let mut emulator = Emulator::headless(program).unwrap();
+    emulator.set_interpreter(interpreter);
+    emulator.seed_rng(0);
+    for _ in 0..steps {
+        emulator.step().unwrap();
+    }
+    emulator
+}
+
+/// Runs `program` to completion under every quirk set, `steps` instructions long.
+fn run_all(program: &[u8], steps: usize) -> impl Iterator<Item = (Interpreter, Emulator)> + '_ {
+    QUIRKS
+        .into_iter()
+        .map(move |interpreter| (interpreter, run(interpreter, program, steps)))
+}
+
+#[test]
+fn clear_screen() {
+    // Draw the font's 0, then CLS
+    for (interpreter, emulator) in run_all(&[0xF0, 0x29, 0xD0, 0x05, 0x00, 0xE0], 3) {
+        assert!(
+            emulator.framebuffer().pixels().iter().all(|lit| !lit),
+            "{:?}",
+            interpreter
+        );
+    }
+}
+
+#[test]
+fn call_and_return() {
+    // CALL 0x206, JP 0x202 (never reached twice), padding, RET
+    let program = [0x22, 0x06, 0x12, 0x02, 0x00, 0x00, 0x00, 0xEE];
+    for (interpreter, emulator) in run_all(&program, 1) {
+        assert_eq!(
+            (emulator.pc(), emulator.stack()),
+            (0x206, &[0x202][..]),
+            "{:?}",
+            interpreter
+        );
+        assert_eq!(emulator.call_sites(), [0x200], "{:?}", interpreter);
+    }
+    for (interpreter, emulator) in run_all(&program, 2) {
+        assert_eq!((emulator.pc(), emulator.stack()), (0x202, &[][..]), "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn exit() {
+    for (interpreter, emulator) in run_all(&[0x00, 0xFD], 1) {
+        assert!(emulator.has_exited(), "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn jump() {
+    for (interpreter, emulator) in run_all(&[0x13, 0x45], 1) {
+        assert_eq!(emulator.pc(), 0x345, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn conditional_skips() {
+    // V0 = 0x12, V1 = 0x12, V2 = 0x34 then the skip under test
+    let setup = [0x60, 0x12, 0x61, 0x12, 0x62, 0x34];
+    let cases: [([u8; 2], bool); 8] = [
+        ([0x30, 0x12], true),
+        ([0x30, 0x13], false),
+        ([0x40, 0x13], true),
+        ([0x40, 0x12], false),
+        ([0x50, 0x10], true),
+        ([0x50, 0x20], false),
+        ([0x90, 0x20], true),
+        ([0x90, 0x10], false),
+    ];
+    for (skip, taken) in cases {
+        let program = [&setup[..], &skip[..]].concat();
+        for (interpreter, emulator) in run_all(&program, 4) {
+            let expected = if taken { 0x20A } else { 0x208 };
+            assert_eq!(
+                emulator.pc(),
+                expected,
+                "{:02X}{:02X} {:?}",
+                skip[0],
+                skip[1],
+                interpreter
+            );
+        }
+    }
+}
+
+#[test]
+fn load_and_add_immediate() {
+    // V3 = 0xF0, V3 += 0x20 wraps without touching VF
+    for (interpreter, emulator) in run_all(&[0x6F, 0x55, 0x63, 0xF0, 0x73, 0x20], 3) {
+        let registers = emulator.registers();
+        assert_eq!((registers[3], registers[0xF]), (0x10, 0x55), "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn register_logic() {
+    // V0 = 0b1100, V1 = 0b1010 then 8XYN on a copy in V2
+    for (n, expected) in [(0x0, 0b1010), (0x1, 0b1110), (0x2, 0b1000), (0x3, 0b0110)] {
+        let program = [0x60, 0x0C, 0x61, 0x0A, 0x82, 0x00, 0x82, 0x10 | n];
+        for (interpreter, emulator) in run_all(&program, 4) {
+            assert_eq!(emulator.registers()[2], expected, "8XY{:X} {:?}", n, interpreter);
+        }
+    }
+}
+
+#[test]
+fn register_arithmetic_sets_flags() {
+    // (V0, V1, 8XYN) -> (V0, VF)
+    let cases = [
+        (0xF0, 0x20, 0x4, 0x10, 1),
+        (0x10, 0x20, 0x4, 0x30, 0),
+        (0x30, 0x10, 0x5, 0x20, 1),
+        (0x10, 0x30, 0x5, 0xE0, 0),
+        (0x10, 0x30, 0x7, 0x20, 1),
+        (0x30, 0x10, 0x7, 0xE0, 0),
+    ];
+    for (v0, v1, n, result, flag) in cases {
+        let program = [0x60, v0, 0x61, v1, 0x80, 0x10 | n];
+        for (interpreter, emulator) in run_all(&program, 3) {
+            let registers = emulator.registers();
+            assert_eq!(
+                (registers[0], registers[0xF]),
+                (result, flag),
+                "8XY{:X} {:?}",
+                n,
+                interpreter
+            );
+        }
+    }
+}
+
+#[test]
+fn flag_is_written_last() {
+    // VF = 0xF0, VF += VF overflows and the carry overwrites the sum
+    for (interpreter, emulator) in run_all(&[0x6F, 0xF0, 0x8F, 0xF4], 2) {
+        assert_eq!(emulator.registers()[0xF], 1, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn shifts_use_vy_on_the_vip() {
+    // V0 = 0x01, V1 = 0x82 then SHR V0, V1 / SHL V0, V1
+    for (n, vip, schip) in [(0x6, (0x41, 0), (0x00, 1)), (0xE, (0x04, 1), (0x02, 0))] {
+        let program = [0x60, 0x01, 0x61, 0x82, 0x80, 0x10 | n];
+        for (interpreter, emulator) in run_all(&program, 3) {
+            let registers = emulator.registers();
+            let expected = if interpreter == Interpreter::CosmacVIP {
+                vip
+            } else {
+                schip
+            };
+            assert_eq!((registers[0], registers[0xF]), expected, "8XY{:X} {:?}", n, interpreter);
+        }
+    }
+}
+
+#[test]
+fn set_index() {
+    for (interpreter, emulator) in run_all(&[0xA1, 0x23], 1) {
+        assert_eq!(emulator.index_register(), 0x123, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn jump_with_offset_uses_vx_on_the_super_chip() {
+    // V0 = 0x10, V3 = 0x20, JP V0, 0x300 / JP V3, 0x300
+    for (interpreter, emulator) in run_all(&[0x60, 0x10, 0x63, 0x20, 0xB3, 0x00], 3) {
+        let expected = if interpreter == Interpreter::CosmacVIP {
+            0x310
+        } else {
+            0x320
+        };
+        assert_eq!(emulator.pc(), expected, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn random_is_masked() {
+    for (interpreter, emulator) in run_all(&[0xC0, 0x0F, 0xC1, 0x00], 2) {
+        let registers = emulator.registers();
+        assert_eq!((registers[0] & 0xF0, registers[1]), (0, 0), "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn draw_reports_collisions() {
+    // I = font 0, V0 = 2, V1 = 3, draw once then again over it
+    let program = [0xA0, 0x00, 0x60, 0x02, 0x61, 0x03, 0xD0, 0x15, 0xD0, 0x15];
+    for (interpreter, emulator) in run_all(&program, 4) {
+        let framebuffer = emulator.framebuffer();
+        assert!(
+            framebuffer.get(2, 3) && framebuffer.get(5, 3) && !framebuffer.get(6, 3),
+            "{:?}",
+            interpreter
+        );
+        assert_eq!(emulator.registers()[0xF], 0, "{:?}", interpreter);
+    }
+    for (interpreter, emulator) in run_all(&program, 5) {
+        assert!(
+            emulator.framebuffer().pixels().iter().all(|lit| !lit),
+            "{:?}",
+            interpreter
+        );
+        assert_eq!(emulator.registers()[0xF], 1, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn key_skips() {
+    // V0 = 5 then SKP V0 and SKNP V0
+    for (op, skips_when_pressed) in [(0x9E, true), (0xA1, false)] {
+        for pressed in [true, false] {
+            for interpreter in QUIRKS {
+                let mut emulator = run(interpreter, &[0x60, 0x05, 0xE0, op], 1);
+                emulator.inject_key(5, pressed);
+                emulator.step().unwrap();
+                let expected = if pressed == skips_when_pressed { 0x206 } else { 0x204 };
+                assert_eq!(
+                    emulator.pc(),
+                    expected,
+                    "EX{:02X} pressed {} {:?}",
+                    op,
+                    pressed,
+                    interpreter
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn wait_for_key_completes_on_release() {
+    for interpreter in QUIRKS {
+        let mut emulator = run(interpreter, &[0xF3, 0x0A], 1);
+        assert_eq!(emulator.pc(), 0x200, "{:?}", interpreter);
+
+        emulator.inject_key(0xB, true);
+        emulator.step().unwrap();
+        assert_eq!(emulator.pc(), 0x200, "{:?}", interpreter);
+
+        emulator.inject_key(0xB, false);
+        emulator.step().unwrap();
+        assert_eq!(
+            (emulator.pc(), emulator.registers()[3]),
+            (0x202, 0xB),
+            "{:?}",
+            interpreter
+        );
+    }
+}
+
+#[test]
+fn timers() {
+    // V0 = 30, DT = V0, ST = V0, V1 = DT
+    let program = [0x60, 0x1E, 0xF0, 0x15, 0xF0, 0x18, 0xF1, 0x07];
+    for interpreter in QUIRKS {
+        let mut emulator = run(interpreter, &program, 3);
+        emulator.tick_delay();
+        emulator.step().unwrap();
+        assert_eq!(emulator.registers()[1], 29, "{:?}", interpreter);
+        assert_eq!(
+            (emulator.delay_timer(), emulator.sound_timer()),
+            (29, 30),
+            "{:?}",
+            interpreter
+        );
+    }
+}
+
+#[test]
+fn add_to_index() {
+    for (interpreter, emulator) in run_all(&[0xA0, 0xFF, 0x60, 0x02, 0xF0, 0x1E], 3) {
+        assert_eq!(emulator.index_register(), 0x101, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn font_character() {
+    for (interpreter, emulator) in run_all(&[0x60, 0x0A, 0xF0, 0x29], 2) {
+        let glyph = emulator.index_register() as usize;
+        assert_eq!(
+            emulator.memory()[glyph..glyph + 5],
+            [0xF0, 0x90, 0xF0, 0x90, 0x90],
+            "{:?}",
+            interpreter
+        );
+    }
+}
+
+#[test]
+fn binary_coded_decimal() {
+    for (interpreter, emulator) in run_all(&[0x60, 0xFE, 0xA3, 0x00, 0xF0, 0x33], 3) {
+        assert_eq!(emulator.memory()[0x300..0x303], [2, 5, 4], "{:?}", interpreter);
+        assert_eq!(emulator.index_register(), 0x300, "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn store_and_load_registers() {
+    // V0..V2 = 1, 2, 3, store at 0x300, clear them and load back
+    let program = [
+        0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0xA3, 0x00, 0xF2, 0x55, 0x60, 0x00, 0x61, 0x00, 0x62, 0x00, 0xA3, 0x00,
+        0xF2, 0x65,
+    ];
+    for (interpreter, emulator) in run_all(&program, 5) {
+        let index = if interpreter == Interpreter::CosmacVIP {
+            0x303
+        } else {
+            0x300
+        };
+        assert_eq!(emulator.memory()[0x300..0x304], [1, 2, 3, 0], "{:?}", interpreter);
+        assert_eq!(emulator.index_register(), index, "{:?}", interpreter);
+    }
+    for (interpreter, emulator) in run_all(&program, 10) {
+        assert_eq!(emulator.registers()[..4], [1, 2, 3, 0], "{:?}", interpreter);
+    }
+}
+
+#[test]
+fn run_until_stops_when_the_predicate_holds() {
+    // V0 += 1, loop
+    let mut emulator = Emulator::headless(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+    assert!(emulator
+        .run_until(|emulator| emulator.registers()[0] == 5, 100)
+        .unwrap());
+    assert_eq!(emulator.instruction_count(), 9);
+    assert!(!emulator.run_until(|emulator| emulator.registers()[0] == 0, 10).unwrap());
+
+    emulator.set_delay_timer(10);
+    emulator.run_for(24).unwrap();
+    assert_eq!((emulator.instruction_count(), emulator.delay_timer()), (43, 8));
+}
+
+#[test]
+fn errors_describe_the_machine_state() {
+    #[rustfmt::skip]
+    let program = [
+        0x22, 0x04, // call 0x204
+        0x12, 0x02, // loop
+        0xAF, 0xFF, // I = 0xFFF
+        0xFF, 0x55, // store V0 to VF past the end of memory
+    ];
+    let mut emulator = Emulator::headless(&program).unwrap();
+    let error = emulator.run_for(10).unwrap_err().to_string();
+
+    assert!(error.contains("failed processing op code 0xFF55"), "{}", error);
+    assert!(error.contains("after 2 instructions, 1 at this address"), "{}", error);
+    assert!(error.contains("i 0xFFF  delay 0  sound 0"), "{}", error);
+    assert!(error.contains("stack: 0x202"), "{}", error);
+}
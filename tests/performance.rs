@@ -0,0 +1,45 @@
+use std::{
+    env,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use chip8rs::{
+    bench::{
+        self,
+        BENCHMARKS,
+    },
+    emulator::Emulator,
+};
+
+const INSTRUCTIONS: usize = 2_000_000;
+/// Roughly 5x the 0.9 s a debug build needs per program, tighten per machine with `CHIP8RS_PERF_BUDGET_MS`.
+const DEFAULT_BUDGET: Duration = Duration::from_secs(5);
+
+#[test]
+fn headless_execution_stays_within_budget() {
+    let budget = env::var("CHIP8RS_PERF_BUDGET_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_BUDGET);
+
+    for (name, program) in BENCHMARKS {
+        let mut emulator = Emulator::headless(&bench::rom(program)).unwrap();
+
+        let start = Instant::now();
+        emulator.run_for(INSTRUCTIONS).unwrap();
+        let elapsed = start.elapsed();
+
+        assert!(
+            elapsed <= budget,
+            "{} took {:?} for {} instructions, budget is {:?}",
+            name,
+            elapsed,
+            INSTRUCTIONS,
+            budget
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use chip8rs::{
+    config::Config,
+    emulator::Emulator,
+    persist::PersistRange,
+};
+
+#[test]
+fn persisted_ram_survives_a_new_run() {
+    #[rustfmt::skip]
+    let rom = [
+        0xA3, 0xF0, // I = 0x3F0
+        0x60, 0x42, // V0 = 0x42
+        0xF0, 0x55, // store V0 at I
+        0x12, 0x06, // loop
+    ];
+    let range = PersistRange::parse("0x3F0..0x3F4").unwrap();
+    let path = std::env::temp_dir().join(format!("chip8rs_persist_{}.ram", std::process::id()));
+
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    assert!(!range.restore(&mut emulator, &path).unwrap());
+    for _ in 0..3 {
+        emulator.step().unwrap();
+    }
+    range.save(&emulator, &path).unwrap();
+
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    assert!(range.restore(&mut emulator, &path).unwrap());
+    assert_eq!(emulator.memory()[0x3F0..0x3F4], [0x42, 0, 0, 0]);
+
+    let wider = PersistRange::parse("3F0..400").unwrap();
+    assert!(wider.restore(&mut emulator, &path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn config_declares_ranges_per_rom() {
+    let config = Config::parse("persist.Tetris [Fran Dachille, 1991].ch8 = 0x3F0..0x3F8").unwrap();
+    assert_eq!(
+        config.persist_range("roms/Tetris [Fran Dachille, 1991].ch8"),
+        Some(PersistRange {
+            start: 0x3F0,
+            end: 0x3F8
+        })
+    );
+    assert_eq!(config.persist_range("roms/Pong.ch8"), None);
+    assert_eq!(Config::parse(&config.to_text()).unwrap(), config);
+
+    assert!(Config::parse("persist.game.ch8 = 0x400..0x3F0").is_err());
+    assert!(Config::parse("persist.game.ch8 = 0xFFF..0x2000").is_err());
+}
@@ -0,0 +1,44 @@
+use chip8rs::platform::{
+    Extension,
+    Platform,
+    PlatformScan,
+};
+
+#[test]
+fn scan_finds_extensions_in_code_only() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0xFF, // hires
+        0xD0, 0x10, // 16x16 sprite
+        0x12, 0x04, // loop
+        0xF0, 0x01, // sprite data that reads like a plane select
+    ];
+    let scan = PlatformScan::scan(&rom, 0x200);
+
+    assert_eq!(
+        scan.extensions()
+            .iter()
+            .map(|(extension, address)| (*extension, *address))
+            .collect::<Vec<_>>(),
+        [(Extension::HiresMode, 0x200), (Extension::LargeSprites, 0x202)]
+    );
+    assert_eq!(scan.platform(), Platform::SuperChip);
+    assert_eq!(
+        scan.to_string(),
+        "super-chip (hires mode switches at 0x200, 16x16 sprites at 0x202)"
+    );
+    assert_eq!(PlatformScan::scan(&[0x12, 0x00], 0x200).platform(), Platform::Chip8);
+}
+
+#[test]
+fn exit_is_the_only_implemented_extension() {
+    #[rustfmt::skip]
+    let rom = [
+        0x00, 0xC4, // scroll down
+        0x00, 0xFD, // exit
+    ];
+    let scan = PlatformScan::scan(&rom, 0x200);
+
+    assert_eq!(scan.extensions().len(), 2);
+    assert_eq!(scan.unimplemented().collect::<Vec<_>>(), [(Extension::Scroll, 0x200)]);
+}
@@ -0,0 +1,130 @@
+use std::sync::{
+    atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+    Arc,
+};
+
+use chip8rs::{
+    emulator::{
+        Emulator,
+        InstructionData,
+    },
+    observer::Observer,
+    rewind::Rewind,
+};
+
+struct Counter(Arc<AtomicUsize>);
+
+impl Observer for Counter {
+    fn after_instruction(&mut self, _emulator: &Emulator, _instruction: &InstructionData) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn machine(emulator: &Emulator) -> (usize, [u8; 16], u16, u8) {
+    (
+        emulator.pc(),
+        emulator.registers(),
+        emulator.index_register(),
+        emulator.delay_timer(),
+    )
+}
+
+#[test]
+fn steps_back_through_random_numbers_and_timer_ticks() {
+    #[rustfmt::skip]
+    let rom = [
+        0xC0, 0xFF, // V0 = random
+        0x71, 0x01, // V1 += 1
+        0xF1, 0x15, // delay timer = V1
+        0xF2, 0x07, // V2 = delay timer
+        0x12, 0x00, // loop
+    ];
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    let mut rewind = Rewind::new(8);
+    let mut history = Vec::new();
+    for i in 0..30 {
+        if i % 12 == 0 {
+            emulator.tick_delay();
+            rewind.record(&emulator);
+        }
+        history.push(machine(&emulator));
+        emulator.step().unwrap();
+    }
+
+    for expected in history.iter().rev() {
+        assert!(rewind.step_back(&mut emulator).unwrap());
+        assert_eq!(machine(&emulator), *expected);
+    }
+    assert!(!rewind.step_back(&mut emulator).unwrap());
+}
+
+#[test]
+fn stops_at_the_oldest_checkpoint() {
+    let mut emulator = Emulator::headless(&[0x71, 0x01, 0x12, 0x00]).unwrap();
+    let mut rewind = Rewind::new(1);
+    for i in 0..24 {
+        if i % 12 == 0 {
+            emulator.tick_delay();
+            rewind.record(&emulator);
+        }
+        emulator.step().unwrap();
+    }
+
+    for _ in 0..12 {
+        assert!(rewind.step_back(&mut emulator).unwrap());
+    }
+    assert_eq!(emulator.instruction_count(), 12);
+    assert!(!rewind.step_back(&mut emulator).unwrap());
+    assert_eq!(emulator.instruction_count(), 12);
+}
+
+#[test]
+fn steps_back_across_a_key_wait_with_the_keys_held_back_then() {
+    #[rustfmt::skip]
+    let rom = [
+        0xF0, 0x0A, // V0 = key, waits for a release
+        0xE0, 0x9E, // skip if V0 is held
+        0x71, 0x01, // V1 += 1
+        0x12, 0x02, // loop
+    ];
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    let instructions = Arc::new(AtomicUsize::new(0));
+    emulator.add_observer(Box::new(Counter(instructions.clone())));
+    let mut rewind = Rewind::new(8);
+    emulator.tick_delay();
+    rewind.record(&emulator);
+
+    // Each frame changes a key, then runs a few instructions
+    let mut history = Vec::new();
+    for (key, steps) in [
+        (None, 2),
+        (Some((5, true)), 2),
+        (Some((5, false)), 3),
+        (Some((5, true)), 4),
+    ] {
+        if let Some((hex, pressed)) = key {
+            emulator.inject_key(hex, pressed);
+        }
+        emulator.begin_frame();
+        rewind.record_keys(&emulator);
+        for _ in 0..steps {
+            history.push(machine(&emulator));
+            emulator.step().unwrap();
+        }
+        emulator.end_frame();
+    }
+    assert_eq!(emulator.registers()[0], 5);
+    emulator.inject_key(5, false);
+
+    let executed = instructions.load(Ordering::Relaxed);
+    let counts = emulator.execution_counts().to_vec();
+    for expected in history.iter().rev() {
+        assert!(rewind.step_back(&mut emulator).unwrap());
+        assert_eq!(machine(&emulator), *expected);
+    }
+    assert_eq!(instructions.load(Ordering::Relaxed), executed);
+    assert_eq!(emulator.execution_counts(), counts);
+}
@@ -0,0 +1,56 @@
+use std::fs;
+
+use chip8rs::{
+    demo::Demo,
+    emulator::{
+        Emulator,
+        RomWarning,
+    },
+};
+
+#[test]
+fn bundled_roms_look_like_programs() {
+    for entry in fs::read_dir("assets/roms")
+        .unwrap()
+        .chain(fs::read_dir("assets/roms/test").unwrap())
+    {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            // Programs may end in an odd number of bytes of sprite data, so only the other warnings must stay quiet
+            let warnings = RomWarning::check(&fs::read(&path).unwrap());
+            assert!(
+                warnings
+                    .iter()
+                    .all(|warning| matches!(warning, RomWarning::OddLength { .. })),
+                "{}: {:?}",
+                path.display(),
+                warnings
+            );
+        }
+    }
+}
+
+#[test]
+fn wrong_files_are_flagged() {
+    assert_eq!(
+        RomWarning::check(b"<!DOCTYPE html><html>"),
+        [RomWarning::OddLength { len: 21 }, RomWarning::Text]
+    );
+    assert_eq!(
+        RomWarning::check(&[0x10, 0x40, 0x00, 0xE0]),
+        [RomWarning::JumpBelowProgram { target: 0x040 }]
+    );
+}
+
+#[test]
+fn every_demo_runs_and_draws() {
+    for name in Demo::NAMES {
+        let demo = Demo::from_name(name).unwrap();
+        assert_eq!(demo.name(), name);
+        let mut emulator = Emulator::headless(demo.rom()).unwrap();
+        emulator.run_for(2_000).unwrap();
+        let framebuffer = emulator.framebuffer();
+        let lit = (0..framebuffer.height()).any(|y| (0..framebuffer.width()).any(|x| framebuffer.get(x, y)));
+        assert!(lit, "{} drew nothing", name);
+    }
+}
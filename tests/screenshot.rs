@@ -0,0 +1,51 @@
+use std::fs;
+
+use chip8rs::{
+    emulator::Emulator,
+    screenshot::{
+        ScreenshotOptions,
+        ScreenshotSize,
+    },
+};
+
+#[test]
+fn framebuffer_image_scales_every_pixel() {
+    let rom = fs::read("assets/roms/test/IBM Logo.ch8").unwrap();
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    for _ in 0..1000 {
+        emulator.step().unwrap();
+    }
+
+    let image = emulator.framebuffer_image(3);
+    let framebuffer = emulator.framebuffer();
+    assert_eq!(image.dimensions(), (64 * 3, 32 * 3));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let lit = framebuffer.get(x as usize / 3, y as usize / 3);
+        assert_eq!(pixel.0[1] == 255, lit, "pixel ({}, {})", x, y);
+    }
+    assert!(framebuffer.pixels().iter().any(|lit| *lit));
+}
+
+#[test]
+fn native_screenshot_keeps_its_metadata() {
+    let rom = fs::read("assets/roms/test/IBM Logo.ch8").unwrap();
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    emulator.run_for(1000).unwrap();
+
+    let path = std::env::temp_dir().join(format!("chip8rs_screenshot_{}.png", std::process::id()));
+    let options = ScreenshotOptions {
+        size: ScreenshotSize::Native,
+        rom_name: Some("IBM Logo.ch8".into()),
+        frame: Some(42),
+    };
+    emulator.save_screenshot(path.to_str().unwrap(), &options).unwrap();
+
+    let png = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let contains = |needle: &[u8]| png.windows(needle.len()).any(|window| window == needle);
+    assert!(contains(b"tEXtTitle\0IBM Logo.ch8"));
+    assert!(contains(b"tEXtFrame\x0042"));
+    // The decoder checks every chunk's CRC
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image, emulator.framebuffer_image(1));
+}
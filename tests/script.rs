@@ -0,0 +1,39 @@
+use chip8rs::script::{
+    InputScript,
+    TAP_FRAMES,
+};
+
+fn held(script: &InputScript, frame: u64) -> Vec<u8> {
+    let keys = script.keys_at(frame).unwrap();
+    (0..16).filter(|hex| keys[*hex as usize]).collect()
+}
+
+#[test]
+fn script_holds_keys_for_their_frames() {
+    let script = InputScript::parse(
+        "at frame 120 press 5 for 10 frames; at 300 tap A\n\
+         # held until released\n\
+         at 200 press 1\n\
+         AT 250 RELEASE 1",
+    )
+    .unwrap();
+
+    assert_eq!(held(&script, 119), [0u8; 0]);
+    assert_eq!(held(&script, 120), [0x5]);
+    assert_eq!(held(&script, 129), [0x5]);
+    assert_eq!(held(&script, 130), [0u8; 0]);
+    assert_eq!(held(&script, 249), [0x1]);
+    assert_eq!(held(&script, 250), [0u8; 0]);
+    assert_eq!(held(&script, 300), [0xA]);
+    assert_eq!(script.len(), 300 + TAP_FRAMES);
+    assert_eq!(script.keys_at(script.len()), None);
+}
+
+#[test]
+fn script_rejects_invalid_statements() {
+    assert!(InputScript::parse("at 10 press 5; at 20 hold 5").is_err());
+    assert!(InputScript::parse("at 10 press G").is_err());
+    assert!(InputScript::parse("at 10 tap 5 for 3").is_err());
+    assert!(InputScript::parse("press 5").is_err());
+    assert!(InputScript::parse("at 10 press 5\nat 20").is_err());
+}
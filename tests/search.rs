@@ -0,0 +1,27 @@
+use chip8rs::search::{
+    MemorySearch,
+    SearchFilter,
+};
+
+#[test]
+fn filters_narrow_candidates_across_snapshots() {
+    let mut memory = [0u8; 8];
+    memory[2] = 3;
+    memory[5] = 3;
+    let mut search = MemorySearch::new(&memory);
+
+    search.filter(&memory, SearchFilter::Equal(3));
+    assert_eq!(search.candidates(), &[2, 5]);
+
+    memory[2] = 2;
+    search.filter(&memory, SearchFilter::Decreased);
+    assert_eq!(search.candidates(), &[2]);
+
+    search.filter(&memory, SearchFilter::Unchanged);
+    assert_eq!(search.candidates(), &[2]);
+
+    memory[2] = 9;
+    search.filter(&memory, SearchFilter::Changed);
+    search.filter(&memory, SearchFilter::Increased);
+    assert!(search.candidates().is_empty());
+}
@@ -0,0 +1,62 @@
+use chip8rs::{
+    emulator::Emulator,
+    sprites::{
+        PendingDraw,
+        Sprite,
+        SpriteRecorder,
+    },
+};
+
+#[test]
+fn recorder_collects_each_drawn_sprite_once() {
+    #[rustfmt::skip]
+    let rom = [
+        0xA2, 0x0A, // I = 0x20A
+        0xD0, 0x02, // draw 2 rows
+        0xD0, 0x02, // draw the same sprite again
+        0xD0, 0x01, // draw its first row alone
+        0x12, 0x08, // loop
+        0xF0, 0x81, // sprite
+    ];
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    let recorder = SpriteRecorder::default();
+    emulator.add_observer(Box::new(recorder.clone()));
+    for _ in 0..5 {
+        emulator.step().unwrap();
+    }
+
+    let sprites = recorder.sprites();
+    assert_eq!(sprites.len(), 2);
+    assert_eq!((sprites[0].address, &sprites[0].rows[..]), (0x20A, &[0xF0][..]));
+    assert_eq!(sprites[1].to_text(), "####....\n#......#\n");
+}
+
+#[test]
+fn pending_draw_previews_the_next_dxyn() {
+    #[rustfmt::skip]
+    let rom = [
+        0x60, 0x46, // V0 = 70, wraps to 6
+        0x61, 0x03, // V1 = 3
+        0xA2, 0x0A, // I = 0x20A
+        0xD0, 0x12, // draw 2 rows at (V0, V1)
+        0x12, 0x08, // loop
+        0xF0, 0x81, // sprite
+    ];
+    let mut emulator = Emulator::headless(&rom).unwrap();
+    for _ in 0..3 {
+        assert_eq!(PendingDraw::next(&emulator), None);
+        emulator.step().unwrap();
+    }
+
+    assert_eq!(
+        PendingDraw::next(&emulator),
+        Some(PendingDraw {
+            sprite: Sprite {
+                address: 0x20A,
+                rows: vec![0xF0, 0x81],
+            },
+            x: 6,
+            y: 3,
+        })
+    );
+}
@@ -0,0 +1,61 @@
+use chip8rs::{
+    emulator::Emulator,
+    state::{
+        Savestate,
+        SavestateError,
+        FORMAT_VERSION,
+    },
+};
+
+#[test]
+fn savestates_remember_their_rom() {
+    let emulator = Emulator::headless(&[0x60, 0x01, 0x12, 0x02]).unwrap();
+    let other = Emulator::headless(&[0x60, 0x02, 0x12, 0x02]).unwrap();
+    let state = Savestate::from_bytes(&emulator.savestate().to_bytes()).unwrap();
+
+    assert_eq!(state.rom_hash(), Some(emulator.rom_hash()));
+    assert!(state.check_rom(emulator.rom_hash()).is_ok());
+    assert!(matches!(
+        state.check_rom(other.rom_hash()),
+        Err(SavestateError::WrongRom { .. })
+    ));
+
+    // Version 1 had no version header, a one byte stack depth and no flag byte before the hash, which states older
+    // still lacked
+    let bytes = state.to_bytes();
+    let mut old = [b"C8RS", &bytes[6..bytes.len() - 9]].concat();
+    old.remove(4 + 23);
+    let old = Savestate::from_bytes(&old).unwrap();
+    assert_eq!(old.rom_hash(), None);
+    assert!(old.check_rom(other.rom_hash()).is_ok());
+}
+
+#[test]
+fn savestates_reject_unknown_versions_and_garbage() {
+    let mut bytes = Emulator::headless(&[0x12, 0x00]).unwrap().savestate().to_bytes();
+    bytes.push(0);
+    assert!(matches!(
+        Savestate::from_bytes(&bytes),
+        Err(SavestateError::TrailingData { len: 1 })
+    ));
+
+    bytes.pop();
+    bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        Savestate::from_bytes(&bytes),
+        Err(SavestateError::UnsupportedVersion { version }) if version == FORMAT_VERSION + 1
+    ));
+}
+
+#[test]
+fn savestates_keep_stacks_deeper_than_a_byte() {
+    // Calls itself forever
+    let mut emulator = Emulator::headless(&[0x22, 0x00]).unwrap();
+    emulator.run_for(300).unwrap();
+    let state = emulator.savestate();
+
+    let mut restored = Emulator::headless(&[0x22, 0x00]).unwrap();
+    restored.load_state(&Savestate::from_bytes(&state.to_bytes()).unwrap());
+    assert_eq!(restored.stack().len(), 300);
+    assert_eq!(restored.stack(), emulator.stack());
+}
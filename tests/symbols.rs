@@ -0,0 +1,46 @@
+use chip8rs::{
+    disassembler,
+    history::HistoryEntry,
+    symbols::SymbolTable,
+};
+
+#[test]
+fn symbols_label_addresses_and_operands() {
+    let symbols = SymbolTable::parse("# labels\n0x200 main\n2A4 draw_score:\n").unwrap();
+
+    assert_eq!(symbols.label(0x2A4), Some("draw_score"));
+    assert_eq!(symbols.locate(0x2A8).as_deref(), Some("draw_score+4"));
+    assert_eq!(symbols.locate(0x100), None);
+    assert_eq!(disassembler::mnemonic_with_symbols(0x22A4, &symbols), "CALL draw_score");
+    assert_eq!(disassembler::mnemonic_with_symbols(0x1300, &symbols), "JP 0x300");
+
+    assert!(SymbolTable::parse("0x200").is_err());
+    assert!(SymbolTable::parse("0x1000 beyond").is_err());
+}
+
+#[test]
+fn symbols_written_out_parse_back_unchanged() {
+    let mut symbols = SymbolTable::default();
+    symbols.insert(0x200, "main");
+    symbols.insert(0x2A4, "draw_score");
+
+    assert_eq!(symbols.to_text(), "0x200 main\n0x2A4 draw_score\n");
+    assert_eq!(SymbolTable::parse(&symbols.to_text()).unwrap(), symbols);
+
+    let path = std::env::temp_dir().join(format!("chip8rs_symbols_{}.sym", std::process::id()));
+    symbols.save(&path).unwrap();
+    let loaded = SymbolTable::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), symbols);
+}
+
+#[test]
+fn history_entries_show_labels() {
+    let symbols = SymbolTable::parse("0x2A4 draw_score\n").unwrap();
+    let entry = HistoryEntry {
+        pc: 0x2A8,
+        op_code: 0x22A4,
+    };
+
+    assert_eq!(entry.describe(&symbols), "0x2A8 draw_score+4     22A4  CALL draw_score");
+}
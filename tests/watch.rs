@@ -0,0 +1,29 @@
+use chip8rs::{
+    emulator::Emulator,
+    watch::Watch,
+};
+
+#[test]
+fn parses_watch_expressions() {
+    assert_eq!("vA".parse(), Ok(Watch::Register(0xA)));
+    assert_eq!("I".parse(), Ok(Watch::Index));
+    assert_eq!("[0x300]".parse(), Ok(Watch::Byte(0x300)));
+    assert_eq!("w[2f0]".parse(), Ok(Watch::Word(0x2F0)));
+
+    for invalid in ["v16", "v", "[0x1000]", "0x300", "w300"] {
+        assert!(invalid.parse::<Watch>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn reads_watched_values() {
+    // LD V3, 0x2A; LD I, 0x200
+    let mut emulator = Emulator::headless(&[0x63, 0x2A, 0xA2, 0x00]).unwrap();
+    emulator.step().unwrap();
+    emulator.step().unwrap();
+
+    assert_eq!(Watch::Register(3).read(&emulator), 0x2A);
+    assert_eq!(Watch::Index.read(&emulator), 0x200);
+    assert_eq!(Watch::Byte(0x201).read(&emulator), 0x2A);
+    assert_eq!(Watch::Word(0x200).read(&emulator), 0x632A);
+}